@@ -9,23 +9,65 @@ export interface Player {
 	name: string;
 	nickname: string;
 	avatar_emoji: string;
+	/** False once archived. */
+	active: boolean;
 }
 
+export type MatchType = 'singles' | 'doubles';
+
 export interface MatchRecord {
 	id: string;
+	match_type: MatchType;
 	winner1_id: string;
-	winner2_id: string;
+	/** null for singles. */
+	winner2_id: string | null;
 	loser1_id: string;
-	loser2_id: string;
+	/** null for singles. */
+	loser2_id: string | null;
 	winner_score: number | null;
 	loser_score: number | null;
 	comment: string;
 	recorded_by: string;
 	played_at: string;
 	league_id: string | null;
+	forfeit: boolean;
+	/** Shared by games recorded together via `createMatchSession`. */
+	session_id: string | null;
+	/** Present when the request passed `?tz=`. */
+	played_at_local?: string;
+	/** Participants, winners first — only with `include_players=true`. */
+	players?: PlayerSummary[];
+}
+
+export interface PlayerSummary {
+	id: string;
+	name: string;
+	avatar_emoji: string;
+}
+
+export interface MatchWithNames extends MatchRecord {
+	winner1_name: string;
+	winner2_name: string | null;
+	loser1_name: string;
+	loser2_name: string | null;
+}
+
+export interface MatchFeed {
+	items: MatchWithNames[];
+	next_cursor: string | null;
+}
+
+export interface MatchSession {
+	/** YYYY-MM-DD in the server's TIMEZONE. */
+	date: string;
+	games_played: number;
+	distinct_players: number;
+	matches: MatchRecord[];
 }
 
 export interface LeaderboardEntry {
+	/** 1-based; tied players share it when SHARED_RANKS is on. */
+	rank: number;
 	player_id: string;
 	player_name: string;
 	avatar_emoji: string;
@@ -35,6 +77,17 @@ export interface LeaderboardEntry {
 	total_games: number;
 	win_rate: number;
 	streak: number;
+	on_fire: boolean;
+	longest_streak_all_time: number;
+	last_played: string | null;
+	games_last_7_days: number;
+	rating?: number;
+	rating_deviation?: number;
+	participation_rate?: number;
+	/** Only with include_points=true. */
+	points_for?: number;
+	points_against?: number;
+	avg_point_differential?: number | null;
 }
 
 export interface PlayerStats {
@@ -47,6 +100,12 @@ export interface PlayerStats {
 	total_games: number;
 	win_rate: number;
 	streak: number;
+	resilient_streak: number;
+	longest_win_streak: number;
+	longest_loss_streak: number;
+	points_for: number;
+	points_against: number;
+	avg_point_differential: number | null;
 	best_partner: { partner_id: string; partner_name: string; wins: number; losses: number } | null;
 	nemesis: {
 		opponent_id: string;
@@ -55,6 +114,28 @@ export interface PlayerStats {
 		losses_against: number;
 	} | null;
 	recent_matches: MatchRecord[];
+	days_since_last_win: number | null;
+	days_since_last_played: number | null;
+	best_month: MonthlyRecord | null;
+	worst_month: MonthlyRecord | null;
+	/** Win rate over competitive games only (see ADJUSTED_WIN_RATE_GAP). */
+	adjusted_win_rate: number | null;
+}
+
+export interface MonthlyRecord {
+	/** e.g. "2024-01". */
+	month: string;
+	wins: number;
+	losses: number;
+	win_rate: number;
+}
+
+export interface EloHistoryEntry {
+	match: MatchRecord;
+	rating_before: number;
+	/** Positive for a win, negative for a loss. */
+	delta: number;
+	rating_after: number;
 }
 
 export interface RivalryEntry {
@@ -64,6 +145,34 @@ export interface RivalryEntry {
 	player2_name: string;
 	player1_wins: number;
 	player2_wins: number;
+	intensity: number;
+	current_streak: RivalryStreak | null;
+}
+
+export interface RivalryStreak {
+	player_id: string;
+	wins: number;
+}
+
+export interface HeadToHead {
+	player1: Player;
+	player2: Player;
+	player1_wins: number;
+	player2_wins: number;
+	leader: string | null;
+	current_streak: RivalryStreak | null;
+	matches: MatchRecord[];
+}
+
+export interface DuoEntry {
+	player1_id: string;
+	player1_name: string;
+	player2_id: string;
+	player2_name: string;
+	wins: number;
+	losses: number;
+	games_together: number;
+	win_rate: number;
 }
 
 export interface AuthInfo {
@@ -72,6 +181,8 @@ export interface AuthInfo {
 	name?: string;
 	email?: string;
 	role?: string;
+	/** Admin role or listed in ADMIN_EMAILS — use this for admin UI. */
+	is_admin?: boolean;
 	player_id?: string | null;
 }
 
@@ -113,14 +224,36 @@ async function apiFetch<T>(path: string, options?: RequestInit): Promise<T> {
 
 // Auth
 export const getAuthInfo = () => apiFetch<AuthInfo>('/api/auth/me');
+export const refreshSession = () =>
+	apiFetch<AuthInfo>('/api/auth/refresh', { method: 'POST' });
 export const logout = () => apiFetch<void>('/api/auth/logout', { method: 'POST' });
 
 // Players
-export const getPlayers = () => apiFetch<Player[]>('/api/players');
-export const createPlayer = (player: Omit<Player, never>) =>
+export const getPlayers = (includeArchived = false) =>
+	apiFetch<Player[]>(`/api/players${includeArchived ? '?include_archived=true' : ''}`);
+export const createPlayer = (player: Omit<Player, 'active'>) =>
 	apiFetch<Player>('/api/players', { method: 'POST', body: JSON.stringify(player) });
 export const updatePlayer = (id: string, data: Partial<Player>) =>
-	apiFetch<Player>(`/api/players/${id}`, { method: 'PUT', body: JSON.stringify(data) });
+	apiFetch<Player & { changed_fields: string[] }>(`/api/players/${id}`, {
+		method: 'PUT',
+		body: JSON.stringify(data)
+	});
+export const archivePlayer = (id: string) =>
+	apiFetch<Player>(`/api/players/${id}/archive`, { method: 'POST' });
+export interface MergeSummary {
+	from: string;
+	into: string;
+	matches_rewritten: number;
+	skipped_matches: string[];
+	failed_matches: string[];
+	users_relinked: number;
+	from_deleted: boolean;
+}
+export const mergePlayers = (from: string, into: string) =>
+	apiFetch<MergeSummary>('/api/players/merge', {
+		method: 'POST',
+		body: JSON.stringify({ from, into }),
+	});
 export const deletePlayer = (id: string) =>
 	apiFetch<void>(`/api/players/${id}`, { method: 'DELETE' });
 
@@ -132,29 +265,76 @@ export const getMatches = (limit?: number, leagueId?: string) => {
 	const qs = params.toString();
 	return apiFetch<MatchRecord[]>(`/api/matches${qs ? `?${qs}` : ''}`);
 };
+export const getMatchFeed = (opts: {
+	cursor?: string;
+	limit?: number;
+	playerId?: string;
+	leagueId?: string;
+	from?: string;
+	to?: string;
+} = {}) => {
+	const params = new URLSearchParams();
+	if (opts.cursor) params.set('cursor', opts.cursor);
+	if (opts.limit) params.set('limit', String(opts.limit));
+	if (opts.playerId) params.set('player_id', opts.playerId);
+	if (opts.leagueId) params.set('league_id', opts.leagueId);
+	if (opts.from) params.set('from', opts.from);
+	if (opts.to) params.set('to', opts.to);
+	const qs = params.toString();
+	return apiFetch<MatchFeed>(`/api/matches/feed${qs ? `?${qs}` : ''}`);
+};
+export const getMatchSessions = (limitDays?: number) =>
+	apiFetch<MatchSession[]>(`/api/matches/sessions${limitDays ? `?limit_days=${limitDays}` : ''}`);
 export const createMatch = (data: {
 	winner1_id: string;
-	winner2_id: string;
+	/** Omit (or leave empty) both second players for singles. */
+	winner2_id?: string | null;
 	loser1_id: string;
-	loser2_id: string;
+	loser2_id?: string | null;
 	winner_score?: number;
 	loser_score?: number;
 	comment?: string;
 	league_id?: string;
+	forfeit?: boolean;
 }) => apiFetch<MatchRecord>('/api/matches', { method: 'POST', body: JSON.stringify(data) });
+export const createMatchSession = (data: {
+	played_at_start: string;
+	league_id?: string;
+	games: {
+		/** One player for singles, two for doubles. */
+		winners: string[];
+		losers: string[];
+		winner_score?: number;
+		loser_score?: number;
+		comment?: string;
+		forfeit?: boolean;
+	}[];
+}) =>
+	apiFetch<{ session_id: string; matches: MatchRecord[] }>('/api/matches/session', {
+		method: 'POST',
+		body: JSON.stringify(data),
+	});
 export const updateMatch = (id: string, data: {
 	winner1_id: string;
-	winner2_id: string;
+	/** Omit (or leave empty) both second players for singles. */
+	winner2_id?: string | null;
 	loser1_id: string;
-	loser2_id: string;
+	loser2_id?: string | null;
 	winner_score?: number | null;
 	loser_score?: number | null;
 	comment?: string;
 	league_id?: string | null;
+	forfeit?: boolean;
 }) => apiFetch<MatchRecord>(`/api/matches/${id}`, { method: 'PUT', body: JSON.stringify(data) });
 export const deleteMatch = (id: string) =>
 	apiFetch<void>(`/api/matches/${id}`, { method: 'DELETE' });
 
+export const undoLastMatch = () =>
+	apiFetch<MatchRecord>('/api/matches/undo', { method: 'POST' });
+
+export const restoreMatch = (id: string) =>
+	apiFetch<MatchRecord>(`/api/matches/${id}/restore`, { method: 'POST' });
+
 // Leaderboard & Stats
 export const getLeaderboard = (leagueId?: string) => {
 	const qs = leagueId ? `?league_id=${leagueId}` : '';
@@ -164,10 +344,24 @@ export const getPlayerStats = (id: string, leagueId?: string) => {
 	const qs = leagueId ? `?league_id=${leagueId}` : '';
 	return apiFetch<PlayerStats>(`/api/players/${id}/stats${qs}`);
 };
+export const getMyStats = (leagueId?: string) => {
+	const qs = leagueId ? `?league_id=${leagueId}` : '';
+	return apiFetch<PlayerStats>(`/api/me/stats${qs}`);
+};
+export const getEloHistory = (id: string) =>
+	apiFetch<EloHistoryEntry[]>(`/api/players/${id}/elo-history`);
 export const getRivalries = (leagueId?: string) => {
 	const qs = leagueId ? `?league_id=${leagueId}` : '';
 	return apiFetch<RivalryEntry[]>(`/api/rivalries${qs}`);
 };
+export const getHeadToHead = (id1: string, id2: string, leagueId?: string) => {
+	const qs = leagueId ? `?league_id=${leagueId}` : '';
+	return apiFetch<HeadToHead>(`/api/rivalries/${id1}/${id2}${qs}`);
+};
+export const getDuos = (leagueId?: string) => {
+	const qs = leagueId ? `?league_id=${leagueId}` : '';
+	return apiFetch<DuoEntry[]>(`/api/duos${qs}`);
+};
 
 // Users (admin)
 export const getUsers = () => apiFetch<User[]>('/api/users');
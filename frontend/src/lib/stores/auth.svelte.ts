@@ -1,6 +1,9 @@
 // lib/stores/auth.ts — Authentication state store using Svelte 5 runes.
 
-import { getAuthInfo, type AuthInfo } from '$lib/api';
+import { getAuthInfo, refreshSession, type AuthInfo } from '$lib/api';
+
+/** How often an open tab extends its session — well inside even a 1-hour SESSION_TTL_HOURS. */
+const REFRESH_INTERVAL_MS = 15 * 60 * 1000;
 
 let authState = $state<AuthInfo>({ authenticated: false });
 let loading = $state(true);
@@ -21,3 +24,16 @@ export async function checkAuth() {
 		loading = false;
 	}
 }
+
+/** Keep the session alive while the app stays open; returns a stop function. */
+export function keepSessionAlive(): () => void {
+	const timer = setInterval(async () => {
+		if (!authState.authenticated) return;
+		try {
+			authState = await refreshSession();
+		} catch {
+			authState = { authenticated: false };
+		}
+	}, REFRESH_INTERVAL_MS);
+	return () => clearInterval(timer);
+}
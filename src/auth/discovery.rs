@@ -0,0 +1,58 @@
+// auth/discovery.rs — The identity provider's OIDC discovery document.
+//
+// Everything provider-specific — where to send users to sign in, where to
+// redeem codes, where the signing keys live and what issuer tokens carry —
+// comes from `{OIDC_AUTHORITY}/.well-known/openid-configuration`. By default
+// the authority is the configured Entra tenant, but it can point at
+// `organizations`, `common` or another provider without code changes.
+//
+// The document changes about never, so it's fetched once and kept for
+// `DISCOVERY_TTL`. A failed fetch isn't cached; the next sign-in retries.
+
+use serde::Deserialize;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::AppConfig;
+
+/// How long a fetched discovery document is reused.
+const DISCOVERY_TTL: Duration = Duration::from_secs(24 * 3600);
+
+/// The parts of the discovery document we use.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    /// Expected `iss` of ID tokens. Microsoft's multi-tenant authorities
+    /// return a `{tenantid}` placeholder here (see `oidc::expected_issuer`).
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+static CACHE: LazyLock<Mutex<Option<(Instant, ProviderMetadata)>>> =
+    LazyLock::new(Mutex::default);
+
+/// The OIDC discovery URL for an authority such as
+/// `https://login.microsoftonline.com/{tenant}/v2.0`.
+pub fn discovery_url(authority: &str) -> String {
+    format!("{}/.well-known/openid-configuration", authority.trim_end_matches('/'))
+}
+
+/// The provider's metadata, from the cache or freshly fetched.
+pub async fn provider_metadata(config: &AppConfig) -> Result<ProviderMetadata, reqwest::Error> {
+    if let Some((fetched_at, metadata)) = &*CACHE.lock().unwrap()
+        && fetched_at.elapsed() < DISCOVERY_TTL
+    {
+        return Ok(metadata.clone());
+    }
+
+    let metadata: ProviderMetadata = reqwest::Client::new()
+        .get(discovery_url(&config.oidc_authority))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    *CACHE.lock().unwrap() = Some((Instant::now(), metadata.clone()));
+    Ok(metadata)
+}
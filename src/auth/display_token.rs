@@ -0,0 +1,169 @@
+// auth/display_token.rs — Signed, time-limited URLs for unattended screens.
+//
+// The office TV shows the leaderboard all day but nobody is going to sign it
+// in. Instead an admin mints a display token and the screen passes it as
+// `?token=` to the read-only `/api/display/*` routes.
+//
+// Token format: `{expires_at}.{hex}` where `expires_at` is a Unix timestamp
+// and `hex` is the HMAC-SHA256 of `display:{expires_at}` keyed with the
+// session secret. The `display:` prefix keeps these signatures from ever
+// being confused with anything else signed by the same secret. Tokens can't
+// be revoked individually; rotating `SESSION_SECRET` invalidates all of them
+// (and every session).
+
+use axum::{
+    Json,
+    extract::{Query, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+
+use crate::config::AppConfig;
+
+/// Create a display token valid until `expires_at` (Unix seconds).
+pub fn sign_display_token(config: &AppConfig, expires_at: i64) -> String {
+    let hex: String = display_mac(config, expires_at)
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("{expires_at}.{hex}")
+}
+
+/// Whether `token` was signed by us and hasn't expired as of `now`.
+pub fn verify_display_token(config: &AppConfig, token: &str, now: i64) -> bool {
+    let Some((expires_at, hex)) = token.split_once('.') else {
+        return false;
+    };
+    let (Ok(expires_at), Some(signature)) = (expires_at.parse::<i64>(), decode_hex(hex)) else {
+        return false;
+    };
+    // Constant-time comparison via `verify_slice`.
+    expires_at > now
+        && display_mac(config, expires_at)
+            .verify_slice(&signature)
+            .is_ok()
+}
+
+fn display_mac(config: &AppConfig, expires_at: i64) -> Hmac<Sha256> {
+    // HMAC accepts keys of any length, so this can't fail.
+    let mut mac = Hmac::<Sha256>::new_from_slice(config.session_secret.as_bytes())
+        .expect("HMAC can take a key of any size");
+    mac.update(format!("display:{expires_at}").as_bytes());
+    mac
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Axum middleware for the display routes: requires a valid `?token=`.
+///
+/// Deliberately separate from `require_auth` — no session cookie is read and
+/// no `SessionClaims` are injected, so nothing behind it can act as a user.
+pub async fn require_display_token(request: Request, next: Next) -> Response {
+    let Some(config) = request.extensions().get::<AppConfig>().cloned() else {
+        tracing::error!("AppConfig not found in request extensions");
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "Server configuration error"})),
+        )
+            .into_response();
+    };
+
+    let token = Query::<TokenQuery>::try_from_uri(request.uri())
+        .ok()
+        .and_then(|Query(q)| q.token);
+    let now = chrono::Utc::now().timestamp();
+    match token {
+        Some(token) if verify_display_token(&config, &token, now) => next.run(request).await,
+        _ => (
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({"error": "Invalid or expired display token"})),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn valid_tokens_verify_until_they_expire() {
+        let config = AppConfig::for_tests();
+        let token = sign_display_token(&config, NOW + 60);
+
+        assert!(verify_display_token(&config, &token, NOW));
+        assert!(verify_display_token(&config, &token, NOW + 59));
+        assert!(!verify_display_token(&config, &token, NOW + 60));
+        assert!(!verify_display_token(&config, &sign_display_token(&config, NOW - 1), NOW));
+    }
+
+    #[test]
+    fn tampered_tokens_are_rejected() {
+        let config = AppConfig::for_tests();
+        let token = sign_display_token(&config, NOW + 60);
+        let (_, hex) = token.split_once('.').unwrap();
+
+        // A later expiry with the original signature.
+        assert!(!verify_display_token(&config, &format!("{}.{hex}", NOW + 3600), NOW));
+        // One flipped signature digit.
+        let flipped = if hex.starts_with('0') { "1" } else { "0" };
+        let forged = format!("{}.{flipped}{}", NOW + 60, &hex[1..]);
+        assert!(!verify_display_token(&config, &forged, NOW));
+        // Signed with a different secret.
+        let mut other = AppConfig::for_tests();
+        other.session_secret = "another-secret".to_string();
+        assert!(!verify_display_token(&other, &token, NOW));
+
+        for garbage in ["", "nodot", "abc.def", "1.zz", "1.é"] {
+            assert!(!verify_display_token(&config, garbage, NOW), "{garbage}");
+        }
+    }
+
+    #[tokio::test]
+    async fn the_middleware_only_lets_valid_tokens_through() {
+        use axum::{Extension, Router, body::Body, routing::get};
+        use tower::ServiceExt;
+
+        let config = AppConfig::for_tests();
+        let app = Router::new()
+            .route("/display", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(require_display_token))
+            .layer(Extension(config.clone()));
+        let status = |uri: String| {
+            let app = app.clone();
+            async move {
+                let request = Request::get(uri).body(Body::empty()).unwrap();
+                app.oneshot(request).await.unwrap().status()
+            }
+        };
+        let now = chrono::Utc::now().timestamp();
+
+        let valid = sign_display_token(&config, now + 60);
+        assert_eq!(status(format!("/display?token={valid}")).await, StatusCode::OK);
+        let expired = sign_display_token(&config, now - 60);
+        let expired = status(format!("/display?token={expired}")).await;
+        assert_eq!(expired, StatusCode::UNAUTHORIZED);
+        assert_eq!(status("/display".to_string()).await, StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,108 @@
+// auth/jwks.rs — The identity provider's ID token signing keys.
+//
+// Microsoft (or whichever provider `OIDC_AUTHORITY` names) signs ID tokens
+// with RSA keys it publishes as a JSON Web Key Set (JWKS). The JWKS location
+// and the expected issuer both come from the OIDC discovery document (see
+// auth/discovery.rs).
+//
+// Keys rotate rarely, so we keep them in a process-wide cache keyed by `kid`
+// and only go back to the provider when a token names a key we haven't seen.
+// Refreshes are throttled so a stream of tokens with bogus key ids can't turn
+// us into a request amplifier against the discovery endpoint.
+
+use jsonwebtoken::DecodingKey;
+use jsonwebtoken::jwk::JwkSet;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::auth::discovery;
+use crate::config::AppConfig;
+
+/// Minimum time between two refreshes triggered by unknown key ids.
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Errors from fetching signing keys.
+#[derive(Debug, thiserror::Error)]
+pub enum JwksError {
+    /// The discovery document or key set couldn't be fetched or parsed.
+    #[error("Failed to fetch signing keys: {0}")]
+    Fetch(#[from] reqwest::Error),
+
+    /// The token's `kid` isn't in the provider's current key set.
+    #[error("Unknown signing key '{0}'")]
+    UnknownKey(String),
+}
+
+/// Keys from the last successful fetch.
+#[derive(Default)]
+struct KeyCache {
+    issuer: Option<String>,
+    keys: HashMap<String, DecodingKey>,
+    refreshed_at: Option<Instant>,
+}
+
+static CACHE: LazyLock<Mutex<KeyCache>> = LazyLock::new(Mutex::default);
+
+/// The decoding key for `kid` and the issuer tokens must carry.
+///
+/// Served from the cache when possible; otherwise the discovery document and
+/// key set are fetched again (at most once per `MIN_REFRESH_INTERVAL`).
+pub async fn signing_key(
+    config: &AppConfig,
+    kid: &str,
+) -> Result<(DecodingKey, String), JwksError> {
+    let recently_refreshed = {
+        let cache = CACHE.lock().unwrap();
+        if let (Some(key), Some(issuer)) = (cache.keys.get(kid), &cache.issuer) {
+            return Ok((key.clone(), issuer.clone()));
+        }
+        cache
+            .refreshed_at
+            .is_some_and(|at| at.elapsed() < MIN_REFRESH_INTERVAL)
+    };
+    if recently_refreshed {
+        return Err(JwksError::UnknownKey(kid.to_string()));
+    }
+
+    let (issuer, keys) = fetch_keys(config).await?;
+    let mut cache = CACHE.lock().unwrap();
+    *cache = KeyCache {
+        issuer: Some(issuer.clone()),
+        keys,
+        refreshed_at: Some(Instant::now()),
+    };
+    match cache.keys.get(kid) {
+        Some(key) => Ok((key.clone(), issuer)),
+        None => Err(JwksError::UnknownKey(kid.to_string())),
+    }
+}
+
+/// Fetch the issuer and all usable signing keys, keyed by `kid`.
+///
+/// Keys without a `kid`, or of a type `jsonwebtoken` can't use, are skipped.
+async fn fetch_keys(
+    config: &AppConfig,
+) -> Result<(String, HashMap<String, DecodingKey>), JwksError> {
+    let metadata = discovery::provider_metadata(config).await?;
+    let jwks: JwkSet = reqwest::Client::new()
+        .get(&metadata.jwks_uri)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let keys: HashMap<String, DecodingKey> = jwks
+        .keys
+        .iter()
+        .filter_map(|jwk| {
+            let kid = jwk.common.key_id.clone()?;
+            let key = DecodingKey::from_jwk(jwk).ok()?;
+            Some((kid, key))
+        })
+        .collect();
+    tracing::info!("Loaded {} ID token signing keys", keys.len());
+
+    Ok((metadata.issuer, keys))
+}
@@ -9,21 +9,36 @@
 //   `Extension(claims): Extension<SessionClaims>`
 //
 // Unprotected routes (like login, callback, and GET endpoints) skip this middleware.
+//
+// `require_admin` goes on top of it for destructive routes and turns away
+// sessions without `is_admin` with 403.
 
 use axum::{
     Json,
     extract::Request,
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
 };
 
-use crate::auth::oidc::validate_session_token;
+use crate::auth::oidc::{SessionClaims, validate_session_token};
 use crate::config::AppConfig;
 
 /// The name of the cookie where we store the session JWT.
 pub const SESSION_COOKIE_NAME: &str = "session";
 
+/// Read the value of cookie `name` from the request's `Cookie` header.
+pub fn cookie_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    let cookies = headers
+        .get(axum::http::header::COOKIE)
+        .and_then(|v| v.to_str().ok())?;
+    cookies
+        .split(';')
+        .map(|c| c.trim())
+        .find(|c| c.starts_with(&format!("{name}=")))
+        .map(|c| c[name.len() + 1..].to_string())
+}
+
 /// Axum middleware that validates the session cookie.
 ///
 /// If the cookie is missing or invalid, returns 401 Unauthorized.
@@ -51,18 +66,7 @@ pub async fn require_auth(
     };
 
     // Extract the session cookie from the Cookie header.
-    let cookie_header = request
-        .headers()
-        .get(axum::http::header::COOKIE)
-        .and_then(|v| v.to_str().ok());
-
-    let token = cookie_header.and_then(|cookies| {
-        cookies
-            .split(';')
-            .map(|c| c.trim())
-            .find(|c| c.starts_with(&format!("{SESSION_COOKIE_NAME}=")))
-            .map(|c| c[SESSION_COOKIE_NAME.len() + 1..].to_string())
-    });
+    let token = cookie_value(request.headers(), SESSION_COOKIE_NAME);
 
     let token = match token {
         Some(t) => t,
@@ -90,3 +94,23 @@ pub async fn require_auth(
             .into_response(),
     }
 }
+
+/// Axum middleware that only lets admins through (see `SessionClaims::is_admin`).
+///
+/// Must run after `require_auth`, which provides the claims: apply it with
+/// `route_layer` on routes that are themselves behind `require_auth`.
+pub async fn require_admin(request: Request, next: Next) -> Response {
+    match request.extensions().get::<SessionClaims>() {
+        Some(claims) if claims.is_admin => next.run(request).await,
+        Some(_) => (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({"error": "Admin access required"})),
+        )
+            .into_response(),
+        None => (
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({"error": "Not authenticated"})),
+        )
+            .into_response(),
+    }
+}
@@ -3,5 +3,8 @@
 // Handles OIDC login via Microsoft Entra ID (Azure AD) and session management
 // using signed JWT cookies.
 
+pub mod discovery;
+pub mod display_token;
+pub mod jwks;
 pub mod middleware;
 pub mod oidc;
@@ -6,18 +6,27 @@
 //   2. User signs in with their org account.
 //   3. Microsoft redirects back to our callback URL with an authorization code.
 //   4. We exchange the code for tokens (ID token + access token).
-//   5. We validate the ID token and extract the user's info.
+//   5. We validate the ID token (signature, issuer, audience, expiry) and
+//      extract the user's info.
 //   6. We create a signed JWT session cookie so subsequent requests are authenticated.
 //
+// The endpoints used in steps 1 and 4 come from the discovery document of
+// `OIDC_AUTHORITY` (see auth/discovery.rs), so the same flow works for a
+// single tenant, `organizations`/`common`, or another OIDC provider.
+//
 // Why OIDC?
 //   OIDC (OpenID Connect) is a standard identity protocol built on top of OAuth 2.0.
 //   Microsoft Entra ID (formerly Azure AD) supports it, which means we don't need
 //   Microsoft-specific code — just a standard OIDC client library.
 
 use chrono::{Duration, Utc};
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+};
 use serde::{Deserialize, Serialize};
 
+use crate::auth::discovery;
+use crate::auth::jwks::{self, JwksError};
 use crate::config::AppConfig;
 
 /// Claims we store in our session JWT cookie.
@@ -29,7 +38,8 @@ use crate::config::AppConfig;
 /// The `role` and `player_id` fields are populated from the users table
 /// during the login callback. They're cached in the JWT to avoid a database
 /// lookup on every API request. The trade-off is that role/player changes
-/// don't take effect until the user re-authenticates (within 24 hours).
+/// don't take effect until the user re-authenticates or refreshes the session
+/// (within `SESSION_TTL_HOURS`).
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SessionClaims {
     /// Subject: the user's unique ID from Microsoft (OID claim).
@@ -40,6 +50,10 @@ pub struct SessionClaims {
     pub email: String,
     /// User role: "admin" or "user". Determines what actions are allowed.
     pub role: String,
+    /// Whether this session has admin rights: the `admin` role, or an email
+    /// listed in `ADMIN_EMAILS`. Check this rather than `role`.
+    #[serde(default)]
+    pub is_admin: bool,
     /// Optional link to a Player profile ID. Used for match edit permissions —
     /// if a user's player_id matches a player in a match, they can edit it.
     #[serde(default)]
@@ -65,23 +79,44 @@ pub fn create_session_token(
     role: &str,
     player_id: Option<String>,
 ) -> Result<String, jsonwebtoken::errors::Error> {
+    let claims = new_session_claims(config, user_id, name, email, role, player_id);
+    sign_session_claims(config, &claims)
+}
+
+/// The claims for a session starting now, as `create_session_token` signs them.
+pub fn new_session_claims(
+    config: &AppConfig,
+    user_id: &str,
+    name: &str,
+    email: &str,
+    role: &str,
+    player_id: Option<String>,
+) -> SessionClaims {
     let now = Utc::now();
-    // Sessions last 24 hours. After that, the user must re-authenticate.
-    let exp = now + Duration::hours(24);
+    // Sessions last `SESSION_TTL_HOURS`. After that, the user must
+    // re-authenticate.
+    let exp = now + Duration::hours(config.session_ttl_hours);
 
-    let claims = SessionClaims {
+    SessionClaims {
         sub: user_id.to_string(),
         name: name.to_string(),
         email: email.to_string(),
         role: role.to_string(),
+        is_admin: role == "admin" || config.admin_emails.contains(&email.to_lowercase()),
         player_id,
         exp: exp.timestamp(),
         iat: now.timestamp(),
-    };
+    }
+}
 
+/// Sign `claims` into a session JWT.
+pub fn sign_session_claims(
+    config: &AppConfig,
+    claims: &SessionClaims,
+) -> Result<String, jsonwebtoken::errors::Error> {
     // Sign the JWT with our session secret using HMAC-SHA256.
     let key = EncodingKey::from_secret(config.session_secret.as_bytes());
-    encode(&Header::default(), &claims, &key)
+    encode(&Header::default(), claims, &key)
 }
 
 /// Validate a session JWT and extract the claims.
@@ -99,20 +134,43 @@ pub fn validate_session_token(
 
     decode::<SessionClaims>(token, &key, &validation)
         .ok()
-        .map(|data| data.claims)
+        .map(|data| {
+            let mut claims = data.claims;
+            // Sessions issued before `is_admin` existed only carry the role.
+            claims.is_admin |= claims.role == "admin";
+            claims
+        })
 }
 
-/// The OIDC discovery URL for Microsoft Entra ID.
+/// Pick the public base URL for a request, given its `Host` header.
 ///
-/// This endpoint returns the OpenID Provider Configuration, which tells us
-/// where to send the user to log in, where to exchange codes for tokens, etc.
-pub fn discovery_url(tenant_id: &str) -> String {
-    format!(
-        "https://login.microsoftonline.com/{tenant_id}/v2.0/.well-known/openid-configuration"
-    )
+/// Returns the first of `app_url` and `app_urls` whose host (and port, if
+/// any) equals `host`, ignoring case. Unknown or missing hosts get `app_url`,
+/// so a spoofed `Host` can never steer the redirect somewhere unregistered.
+pub fn base_url_for_host<'a>(config: &'a AppConfig, host: Option<&str>) -> &'a str {
+    let Some(host) = host else {
+        return &config.app_url;
+    };
+    std::iter::once(&config.app_url)
+        .chain(&config.app_urls)
+        .find(|url| url_authority(url).eq_ignore_ascii_case(host))
+        .map_or(&config.app_url, |url| url.as_str())
+}
+
+/// The `host[:port]` part of a URL like `https://host:port/path`.
+fn url_authority(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    rest.split('/').next().unwrap_or(rest)
 }
 
-/// Build the authorization URL that redirects the user to Microsoft login.
+/// The OIDC callback URL under `base_url`. Must be registered as a redirect
+/// URI in the app registration for every configured app URL.
+fn redirect_uri(base_url: &str) -> String {
+    format!("{}/api/auth/callback", base_url.trim_end_matches('/'))
+}
+
+/// Build the authorization URL that redirects the user to the provider's
+/// login page (the discovery document's `authorization_endpoint`).
 ///
 /// Query parameters:
 /// - client_id: our app registration's ID
@@ -120,40 +178,104 @@ pub fn discovery_url(tenant_id: &str) -> String {
 /// - redirect_uri: where Microsoft sends the user back after login
 /// - scope: what we're requesting access to (openid + profile + email)
 /// - response_mode: "query" means the code comes as a URL query parameter
-pub fn authorize_url(config: &AppConfig) -> String {
-    let redirect_uri = format!("{}/api/auth/callback", config.app_url);
-    format!(
-        "https://login.microsoftonline.com/{}/oauth2/v2.0/authorize?\
+/// - state: an unguessable nonce echoed back to the callback, which checks it
+///   against the state cookie to reject forged (CSRF) login responses
+///
+/// `base_url` is the app URL the user is on (see `base_url_for_host`).
+pub async fn authorize_url(
+    config: &AppConfig,
+    base_url: &str,
+    state: &str,
+) -> Result<String, reqwest::Error> {
+    let endpoint = discovery::provider_metadata(config).await?.authorization_endpoint;
+    // Keep any query the endpoint already carries.
+    let separator = if endpoint.contains('?') { '&' } else { '?' };
+    let redirect_uri = redirect_uri(base_url);
+    Ok(format!(
+        "{endpoint}{separator}\
          client_id={}&\
          response_type=code&\
          redirect_uri={}&\
          scope=openid%20profile%20email&\
-         response_mode=query",
-        config.azure_tenant_id,
+         response_mode=query&\
+         state={}",
         config.azure_client_id,
         urlencoding::encode(&redirect_uri),
-    )
+        urlencoding::encode(state),
+    ))
+}
+
+/// How much of an unexpected token endpoint body we keep for logging.
+const MAX_ERROR_BODY_LEN: usize = 1024;
+
+/// Microsoft error codes (`AADSTS…`) meaning the authorization code is no
+/// longer usable: 70008 = expired, 54005 = already redeemed.
+const STALE_CODE_ERRORS: &[u32] = &[70008, 54005];
+
+/// Errors from the OIDC code exchange.
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    /// The discovery document (and so the token endpoint's address) couldn't
+    /// be fetched.
+    #[error("OIDC discovery failed: {0}")]
+    Discovery(#[source] reqwest::Error),
+
+    /// The token endpoint couldn't be reached (DNS, TLS, timeout, ...).
+    #[error("Token endpoint request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    /// The token endpoint answered with an error status or a non-JSON body
+    /// (Microsoft returns HTML or an empty body for some failures).
+    #[error("Token exchange failed with status {status}: {body}")]
+    TokenExchange { status: u16, body: String },
+
+    /// Microsoft refused the code as expired or already used — usually a
+    /// bookmarked or reloaded callback URL. Signing in again fixes it.
+    #[error("Authorization code expired or already used: {0}")]
+    StaleCode(String),
+
+    /// The body claimed to be JSON but didn't match the expected shape.
+    #[error("Malformed token response: {0}")]
+    MalformedResponse(#[from] serde_json::Error),
 }
 
-/// Exchange an authorization code for tokens by calling Microsoft's token endpoint.
+/// Exchange an authorization code for tokens at the provider's token endpoint.
 ///
 /// This is the server-side part of the authorization code flow. The code was
 /// received in the callback URL, and we exchange it for an ID token (which
 /// contains the user's info) and an access token.
+///
+/// The body is read as text before parsing so that error responses — which
+/// aren't always JSON — surface as `AuthError::TokenExchange` with the status
+/// and (truncated) body, rather than an opaque decode error.
+///
+/// `base_url` must be the same one passed to `authorize_url` for this login —
+/// Microsoft rejects the exchange if the redirect URIs differ.
 pub async fn exchange_code(
     config: &AppConfig,
+    base_url: &str,
     code: &str,
-) -> Result<TokenResponse, Box<dyn std::error::Error>> {
-    let redirect_uri = format!("{}/api/auth/callback", config.app_url);
-    let token_url = format!(
-        "https://login.microsoftonline.com/{}/oauth2/v2.0/token",
-        config.azure_tenant_id,
-    );
-
-    // POST the code to Microsoft's token endpoint.
+) -> Result<TokenResponse, AuthError> {
+    let token_url = discovery::provider_metadata(config)
+        .await
+        .map_err(AuthError::Discovery)?
+        .token_endpoint;
+    request_tokens(config, &token_url, base_url, code).await
+}
+
+/// The token request itself, against an already discovered `token_url`.
+async fn request_tokens(
+    config: &AppConfig,
+    token_url: &str,
+    base_url: &str,
+    code: &str,
+) -> Result<TokenResponse, AuthError> {
+    let redirect_uri = redirect_uri(base_url);
+
+    // POST the code to the token endpoint.
     let client = reqwest::Client::new();
     let response = client
-        .post(&token_url)
+        .post(token_url)
         .form(&[
             ("client_id", config.azure_client_id.as_str()),
             ("client_secret", config.azure_client_secret.as_str()),
@@ -165,13 +287,52 @@ pub async fn exchange_code(
         .send()
         .await?;
 
-    if !response.status().is_success() {
-        let body = response.text().await.unwrap_or_default();
-        return Err(format!("Token exchange failed: {body}").into());
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+    let body = response.text().await?;
+
+    if status == reqwest::StatusCode::BAD_REQUEST && is_stale_code_error(&body) {
+        return Err(AuthError::StaleCode(truncate_body(&body)));
+    }
+    if !status.is_success() || !is_json {
+        return Err(AuthError::TokenExchange {
+            status: status.as_u16(),
+            body: truncate_body(&body),
+        });
     }
 
-    let token_response: TokenResponse = response.json().await?;
-    Ok(token_response)
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// The token endpoint's JSON error body (only the fields we inspect).
+#[derive(Deserialize)]
+struct TokenErrorBody {
+    error: Option<String>,
+    #[serde(default)]
+    error_codes: Vec<u32>,
+}
+
+/// Whether a token endpoint error body says the code expired or was used.
+fn is_stale_code_error(body: &str) -> bool {
+    serde_json::from_str::<TokenErrorBody>(body).is_ok_and(|e| {
+        e.error.as_deref() == Some("invalid_grant")
+            && e.error_codes.iter().any(|c| STALE_CODE_ERRORS.contains(c))
+    })
+}
+
+/// Shorten a response body for logging, respecting char boundaries.
+fn truncate_body(body: &str) -> String {
+    if body.is_empty() {
+        return "(empty body)".to_string();
+    }
+    match body.char_indices().nth(MAX_ERROR_BODY_LEN) {
+        Some((end, _)) => format!("{}…", &body[..end]),
+        None => body.to_string(),
+    }
 }
 
 /// Response from Microsoft's token endpoint.
@@ -181,14 +342,17 @@ pub struct TokenResponse {
     pub access_token: Option<String>,
 }
 
-/// Decoded claims from a Microsoft ID token.
+/// Claims from a validated Microsoft ID token.
 ///
-/// We only decode the payload (without full signature validation against JWKS)
-/// because we trust the token came directly from Microsoft via HTTPS in the
-/// server-side code exchange. For a public-facing API that accepts tokens from
-/// clients directly, you'd want full JWKS validation.
+/// Only the fields we use are listed; `aud` and `exp` are checked by
+/// `validate_ms_id_token` but not kept.
 #[derive(Debug, Deserialize)]
 pub struct MicrosoftIdClaims {
+    /// Issuer, checked against the discovery document's (see
+    /// `expected_issuer`).
+    pub iss: String,
+    /// Tenant the user signed in from (Microsoft only).
+    pub tid: Option<String>,
     /// User's unique object ID in the tenant.
     pub oid: Option<String>,
     /// Subject claim (fallback if oid is missing).
@@ -198,24 +362,218 @@ pub struct MicrosoftIdClaims {
     /// Email address.
     #[serde(rename = "preferred_username")]
     pub preferred_username: Option<String>,
+    /// Optional `email` claim — only present when the app registration
+    /// requests it, so we treat it as a secondary source.
+    pub email: Option<String>,
+}
+
+/// Stand-in address for tokens without an email claim.
+const UNKNOWN_EMAIL: &str = "unknown@unknown.com";
+
+impl MicrosoftIdClaims {
+    /// The user's email: `preferred_username` (always present for org
+    /// accounts), then the optional `email` claim. Without either, `None` if
+    /// `required` (`REQUIRE_EMAIL_CLAIM`), otherwise a placeholder address.
+    pub fn email_address(&self, required: bool) -> Option<String> {
+        match self.preferred_username.as_ref().or(self.email.as_ref()) {
+            Some(email) => Some(email.clone()),
+            None if required => None,
+            None => Some(UNKNOWN_EMAIL.to_string()),
+        }
+    }
 }
 
-/// Extract user info from a Microsoft ID token (JWT).
+/// Errors from validating a Microsoft ID token.
+#[derive(Debug, thiserror::Error)]
+pub enum IdTokenError {
+    /// The token header has no `kid`, so we can't pick a key.
+    #[error("ID token header has no key id")]
+    MissingKeyId,
+
+    /// The signing key couldn't be obtained.
+    #[error(transparent)]
+    Keys(#[from] JwksError),
+
+    /// Malformed token, bad signature, or wrong `aud`/`exp`.
+    #[error("Invalid ID token: {0}")]
+    Invalid(#[from] jsonwebtoken::errors::Error),
+
+    /// Validly signed, but issued by someone other than our authority.
+    #[error("ID token has unexpected issuer '{0}'")]
+    WrongIssuer(String),
+}
+
+/// Validate an ID token and extract its claims.
 ///
-/// This does a simple base64 decode of the JWT payload — we trust it because
-/// we received it directly from Microsoft's token endpoint over HTTPS.
-pub fn decode_id_token_claims(id_token: &str) -> Option<MicrosoftIdClaims> {
-    // A JWT has three parts separated by dots: header.payload.signature
-    let parts: Vec<&str> = id_token.split('.').collect();
-    if parts.len() != 3 {
-        return None;
-    }
-
-    // Decode the payload (second part) from base64.
-    use base64::Engine;
-    let payload_bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
-        .decode(parts[1])
-        .ok()?;
-
-    serde_json::from_slice(&payload_bytes).ok()
+/// Even though the token arrives server-side straight from the provider's
+/// token endpoint, we verify it properly as defense in depth: the RS256
+/// signature against the provider's published keys (see jwks.rs), the issuer
+/// from the discovery document, our client ID as audience, and expiry.
+pub async fn validate_ms_id_token(
+    config: &AppConfig,
+    id_token: &str,
+) -> Result<MicrosoftIdClaims, IdTokenError> {
+    let header = decode_header(id_token)?;
+    let kid = header.kid.ok_or(IdTokenError::MissingKeyId)?;
+    let (key, issuer) = jwks::signing_key(config, &kid).await?;
+
+    let mut validation = Validation::new(Algorithm::RS256);
+    validation.set_audience(&[&config.azure_client_id]);
+    // `iss` must be present, but its value is checked below: for
+    // multi-tenant authorities it depends on the token's own `tid`.
+    validation.set_required_spec_claims(&["exp", "aud", "iss"]);
+
+    let claims = decode::<MicrosoftIdClaims>(id_token, &key, &validation)?.claims;
+    if claims.iss != expected_issuer(&issuer, claims.tid.as_deref()) {
+        return Err(IdTokenError::WrongIssuer(claims.iss));
+    }
+    Ok(claims)
+}
+
+/// The issuer a token from tenant `tid` must carry.
+///
+/// Microsoft's `common` and `organizations` authorities advertise
+/// `https://login.microsoftonline.com/{tenantid}/v2.0`, since tokens are
+/// issued by whichever tenant the user belongs to; the placeholder is filled
+/// from the token's `tid`. Other issuers are used as-is.
+fn expected_issuer(issuer: &str, tid: Option<&str>) -> String {
+    match tid {
+        Some(tid) => issuer.replace("{tenantid}", tid),
+        None => issuer.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id_claims(extra: serde_json::Value) -> MicrosoftIdClaims {
+        let mut claims = serde_json::json!({
+            "iss": "https://login.microsoftonline.com/tenant/v2.0",
+            "oid": "oid-1",
+            "name": "Alice",
+        });
+        claims
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(claims).unwrap()
+    }
+
+    #[test]
+    fn email_prefers_preferred_username() {
+        let claims = id_claims(serde_json::json!({
+            "preferred_username": "alice@corp.example",
+            "email": "alice@home.example",
+        }));
+        assert_eq!(claims.email_address(true).as_deref(), Some("alice@corp.example"));
+    }
+
+    #[test]
+    fn email_falls_back_to_the_email_claim() {
+        let claims = id_claims(serde_json::json!({ "email": "alice@home.example" }));
+        assert_eq!(claims.email_address(true).as_deref(), Some("alice@home.example"));
+    }
+
+    #[test]
+    fn missing_email_is_rejected_only_when_required() {
+        let claims = id_claims(serde_json::json!({}));
+        assert_eq!(claims.email_address(true), None);
+        assert_eq!(claims.email_address(false).as_deref(), Some(UNKNOWN_EMAIL));
+    }
+
+    /// Serve one canned token endpoint response on a free local port.
+    async fn token_endpoint(
+        status: u16,
+        content_type: &'static str,
+        body: &'static str,
+    ) -> String {
+        use axum::http::{StatusCode, header};
+        let app = axum::Router::new().route(
+            "/token",
+            axum::routing::post(move || async move {
+                let status = StatusCode::from_u16(status).unwrap();
+                (status, [(header::CONTENT_TYPE, content_type)], body)
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/token", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    async fn exchange(url: &str) -> Result<TokenResponse, AuthError> {
+        let config = AppConfig::for_tests();
+        request_tokens(&config, url, "http://localhost:3000", "code").await
+    }
+
+    #[tokio::test]
+    async fn html_error_pages_become_token_exchange_errors() {
+        let url = token_endpoint(502, "text/html", "<html>Bad Gateway</html>").await;
+        match exchange(&url).await {
+            Err(AuthError::TokenExchange { status, body }) => {
+                assert_eq!(status, 502);
+                assert_eq!(body, "<html>Bad Gateway</html>");
+            }
+            other => panic!("expected TokenExchange, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn a_successful_status_without_json_is_still_an_error() {
+        let url = token_endpoint(200, "text/plain", "").await;
+        match exchange(&url).await {
+            Err(AuthError::TokenExchange { status, body }) => {
+                assert_eq!(status, 200);
+                assert_eq!(body, "(empty body)");
+            }
+            other => panic!("expected TokenExchange, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn json_token_responses_parse() {
+        let body = r#"{"id_token":"header.payload.sig","access_token":"at"}"#;
+        let url = token_endpoint(200, "application/json; charset=utf-8", body).await;
+        let tokens = exchange(&url).await.unwrap();
+        assert_eq!(tokens.id_token.as_deref(), Some("header.payload.sig"));
+    }
+
+    #[test]
+    fn redirect_uri_follows_an_allowlisted_host() {
+        let mut config = AppConfig::for_tests();
+        config.app_url = "https://scores.example.com".to_string();
+        config.app_urls = vec![
+            "https://staging.example.com/".to_string(),
+            "http://localhost:8080".to_string(),
+        ];
+        let redirect = |host| redirect_uri(base_url_for_host(&config, host));
+
+        assert_eq!(
+            redirect(Some("Staging.Example.com")),
+            "https://staging.example.com/api/auth/callback"
+        );
+        assert_eq!(redirect(Some("localhost:8080")), "http://localhost:8080/api/auth/callback");
+        assert_eq!(
+            redirect(Some("scores.example.com")),
+            "https://scores.example.com/api/auth/callback"
+        );
+        // Unknown hosts, other ports and no host at all fall back to `app_url`.
+        for host in [Some("evil.example.net"), Some("localhost:9999"), None] {
+            assert_eq!(redirect(host), "https://scores.example.com/api/auth/callback");
+        }
+    }
+
+    #[tokio::test]
+    async fn expired_and_redeemed_codes_are_stale() {
+        let expired = r#"{"error":"invalid_grant","error_description":
+            "AADSTS70008: The provided authorization code has expired.","error_codes":[70008]}"#;
+        let url = token_endpoint(400, "application/json", expired).await;
+        assert!(matches!(exchange(&url).await, Err(AuthError::StaleCode(_))));
+
+        // Other `invalid_grant` failures aren't the user's stale bookmark.
+        let mismatch = r#"{"error":"invalid_grant","error_codes":[50148]}"#;
+        let url = token_endpoint(400, "application/json", mismatch).await;
+        assert!(matches!(exchange(&url).await, Err(AuthError::TokenExchange { .. })));
+    }
 }
@@ -0,0 +1,93 @@
+// cache.rs — Small in-memory cache for serialized responses.
+//
+// Some responses (full exports) are expensive to build but only change when
+// the underlying data does. Entries are tagged with the storage data version
+// they were built from (see storage/client.rs); a lookup only hits when the
+// version still matches, so any write invalidates everything at once without
+// us having to track which keys it affected.
+//
+// The cache is bounded by entry count. When full, stale entries are dropped
+// first; if every entry is current, the whole cache is cleared. Crude, but
+// the key space (one per player × league) is tiny.
+
+use axum::body::Bytes;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// A shared, version-keyed cache of response bodies. Cheap to clone.
+#[derive(Clone)]
+pub struct ResponseCache {
+    /// Maximum number of entries. 0 disables caching entirely.
+    capacity: usize,
+    entries: Arc<Mutex<HashMap<String, (u64, Bytes)>>>,
+}
+
+impl ResponseCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// The cached body for `key`, if it was built at data version `version`.
+    pub fn get(&self, key: &str, version: u64) -> Option<Bytes> {
+        let entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((v, body)) if *v == version => Some(body.clone()),
+            _ => None,
+        }
+    }
+
+    /// Store `body` for `key` as built at data version `version`.
+    pub fn insert(&self, key: String, version: u64, body: Bytes) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, (v, _)| *v == version);
+            if entries.len() >= self.capacity {
+                entries.clear();
+            }
+        }
+        entries.insert(key, (version, body));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeat_reads_between_writes_hit_and_a_write_invalidates() {
+        let cache = ResponseCache::new(4);
+        cache.insert("export:alice".to_string(), 7, Bytes::from_static(b"v7"));
+
+        assert_eq!(cache.get("export:alice", 7).as_deref(), Some(&b"v7"[..]));
+        assert_eq!(cache.get("export:alice", 7).as_deref(), Some(&b"v7"[..]));
+        // A write bumps the data version; the old body is never served again.
+        assert_eq!(cache.get("export:alice", 8), None);
+        assert_eq!(cache.get("export:bob", 7), None);
+    }
+
+    #[test]
+    fn a_full_cache_drops_stale_entries_first() {
+        let cache = ResponseCache::new(2);
+        cache.insert("old".to_string(), 1, Bytes::from_static(b"old"));
+        cache.insert("a".to_string(), 2, Bytes::from_static(b"a"));
+        cache.insert("b".to_string(), 2, Bytes::from_static(b"b"));
+
+        assert!(cache.get("a", 2).is_some());
+        assert!(cache.get("b", 2).is_some());
+        assert!(cache.entries.lock().unwrap().get("old").is_none());
+    }
+
+    #[test]
+    fn zero_capacity_disables_caching() {
+        let cache = ResponseCache::new(0);
+        cache.insert("export".to_string(), 1, Bytes::from_static(b"body"));
+        assert_eq!(cache.get("export", 1), None);
+    }
+}
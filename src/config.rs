@@ -4,7 +4,13 @@
 // keeping dependencies minimal. Every field maps to an environment variable
 // documented in `.env.example`.
 
+use axum::http::{HeaderValue, Method};
+use chrono::NaiveTime;
+use chrono_tz::Tz;
 use std::env;
+use std::str::FromStr;
+
+use crate::storage::matches::MAX_BATCH_MATCHES;
 
 /// All configuration the app needs at runtime.
 /// Clone-able so we can share it via Axum's State extractor.
@@ -15,10 +21,15 @@ pub struct AppConfig {
     pub azure_storage_account: String,
     /// Access key for the storage account. Used to authenticate table operations.
     pub azure_storage_access_key: String,
+    /// How many times a storage call that fails with a transient error
+    /// (5xx, timeout) is retried before giving up. 0 disables retries.
+    pub storage_max_retries: u32,
 
     // ── Azure AD / Entra ID (OIDC) ──────────────────────────────────
-    /// The Azure AD tenant ID (a GUID).
-    pub azure_tenant_id: String,
+    /// Base URL of the OIDC provider, without trailing slash; its discovery
+    /// document supplies every endpoint we use. Defaults to the v2.0
+    /// authority of the `AZURE_TENANT_ID` tenant.
+    pub oidc_authority: String,
     /// The OAuth2 client ID from the Azure app registration.
     pub azure_client_id: String,
     /// The OAuth2 client secret from the Azure app registration.
@@ -27,10 +38,151 @@ pub struct AppConfig {
     // ── App settings ─────────────────────────────────────────────────
     /// The public-facing URL of this application (used for OIDC redirect URIs).
     pub app_url: String,
+    /// Additional public URLs this deployment answers on (staging, vanity
+    /// domains). A login arriving with a matching `Host` uses that URL for
+    /// its OIDC redirect; anything else falls back to `app_url`.
+    pub app_urls: Vec<String>,
     /// Secret used to sign session JWT cookies.
     pub session_secret: String,
+    /// How long a session (JWT and cookie alike) lasts, in hours. Defaults
+    /// to 24.
+    pub session_ttl_hours: i64,
+    /// Lowercased emails whose sessions are always admin, whatever their
+    /// stored role says.
+    pub admin_emails: Vec<String>,
+    /// When true, logins whose ID token carries no email claim are rejected
+    /// instead of falling back to a placeholder address.
+    pub require_email_claim: bool,
+    /// When true, requests forwarded as plain HTTP (`X-Forwarded-Proto: http`)
+    /// are redirected with a 301 to the https app URL for their host (see
+    /// https.rs). Requires an https `app_url`.
+    pub force_https: bool,
+    /// Whether session cookies carry the `Secure` attribute (sent over HTTPS
+    /// only). `COOKIE_SECURE` if set, otherwise whether `app_url` is https.
+    pub cookie_secure: bool,
     /// Port to listen on. Defaults to 3000.
     pub port: u16,
+    /// Directory holding the built SPA (`npm run build` output). Defaults
+    /// to `static`, where the Docker image puts it.
+    pub static_dir: String,
+    /// IANA timezone used for calendar-based stats (months, days). Defaults
+    /// to UTC. Matches are always stored in UTC regardless.
+    pub timezone: Tz,
+    /// When true, the match backfill migration runs once at startup (it can
+    /// also be triggered via `POST /api/admin/migrate`).
+    pub migrate_on_startup: bool,
+
+    // ── CORS ─────────────────────────────────────────────────────────
+    /// Origins allowed to call the API cross-origin (with cookies). Empty
+    /// (the default) means no CORS headers at all — same-origin only.
+    pub cors_allowed_origins: Vec<HeaderValue>,
+    /// Methods advertised in `Access-Control-Allow-Methods`.
+    pub cors_allowed_methods: Vec<Method>,
+    /// How long (seconds) browsers may cache a preflight response.
+    pub cors_max_age_secs: u64,
+
+    // ── Notifications ────────────────────────────────────────────────
+    /// Optional URL that receives a JSON POST whenever a match is recorded.
+    pub match_webhook_url: Option<String>,
+    /// Optional secret used to HMAC-sign webhook bodies.
+    pub match_webhook_secret: Option<String>,
+
+    // ── Monitoring ───────────────────────────────────────────────────
+    /// Bearer token required by `GET /api/metrics`; unset leaves it public.
+    pub metrics_token: Option<String>,
+
+    // ── Feature toggles ──────────────────────────────────────────────
+    /// When true, players created without a nickname get a generated one
+    /// (e.g. "Spicy Dinker") so the UI always has something to show.
+    pub auto_nickname: bool,
+    /// Adjectives to draw generated nicknames from.
+    pub nickname_adjectives: Vec<String>,
+    /// Nouns to draw generated nicknames from.
+    pub nickname_nouns: Vec<String>,
+    /// When true, a signed-in user without a linked player gets one created
+    /// from their profile the first time they open `/api/me/stats`.
+    pub auto_create_players: bool,
+    /// When true, unauthenticated viewers (display screens on
+    /// `/api/display/*`) see losing players replaced by a placeholder in
+    /// match listings. Signed-in users always see full data.
+    pub anonymize_losers: bool,
+    /// When true, non-admins can only record matches their linked player
+    /// took part in.
+    pub recorder_must_participate: bool,
+    /// What to do when a recorder submits the same lineup and score as their
+    /// previous match within `duplicate_match_window_secs`.
+    pub duplicate_match_policy: DuplicatePolicy,
+    /// Window (seconds of `played_at` difference) for the duplicate check.
+    pub duplicate_match_window_secs: i64,
+    /// Minimum plausible gap (seconds) between two matches of the same
+    /// player; closer pairs are flagged by the suspicious-matches report.
+    pub suspicious_match_interval_secs: i64,
+    /// Optional cap on matches per player per calendar day (in `timezone`);
+    /// creating a match that would exceed it for any participant is rejected.
+    pub max_matches_per_player_per_day: Option<u32>,
+    /// Optional: warn (without blocking) when a new match makes the same four
+    /// players' lineup occur more than this many times within
+    /// `repeat_matchup_window_days`.
+    pub repeat_matchup_threshold: Option<u32>,
+    /// Window (days before the new match's `played_at`) for the repeat check.
+    pub repeat_matchup_window_days: i64,
+    /// Optional daily window (in `timezone`) during which recording matches
+    /// is refused. `None` disables the check.
+    pub quiet_hours: Option<QuietHours>,
+    /// When true, match creations and deletions are also appended to the
+    /// "events" table with the full payload and actor.
+    pub event_log: bool,
+
+    // ── Stats tuning ─────────────────────────────────────────────────
+    /// Winning streak at which a leaderboard entry is flagged `on_fire`.
+    pub hot_streak_threshold: i32,
+    /// When true, tied leaderboard entries share a `rank` ("1224" ranking);
+    /// otherwise every entry gets its own position.
+    pub shared_ranks: bool,
+    /// Results going the other way that a player's `resilient_streak` may
+    /// absorb before it counts as broken.
+    pub streak_tolerance: u32,
+    /// Maximum number of snapshots returned by the leaderboard timeline.
+    pub timeline_max_snapshots: usize,
+    /// Fraction (0.0–1.0) of last season's ELO carried into the next season.
+    pub elo_carryover: f64,
+    /// Length in days of a Glicko-2 rating period.
+    pub glicko_period_days: i64,
+    /// Length in calendar months of each period compared by the comeback
+    /// player computation (current period vs the one before it).
+    pub comeback_period_months: u32,
+    /// Minimum games in *each* period for a player to qualify as comeback player.
+    pub comeback_min_games: u32,
+    /// Minimum games in a month for a player to be its MVP.
+    pub mvp_min_games: u32,
+    /// Minimum games in a month for it to count as a player's best or worst.
+    pub month_highlight_min_games: u32,
+    /// How far (0.0–1.0) the opponents' average win rate may fall below a
+    /// player's own before a game stops counting toward `adjusted_win_rate`.
+    pub adjusted_win_rate_gap: f64,
+
+    // ── Caching ──────────────────────────────────────────────────────
+    /// Maximum number of cached export and leaderboard responses (0 disables
+    /// the cache). The name predates the leaderboard using it.
+    pub export_cache_entries: usize,
+    /// Matches fetched (and held in memory) per page while streaming a full
+    /// match export. Azure caps pages at 1000 rows.
+    pub export_page_size: u32,
+    /// How long an `Idempotency-Key` on `POST /api/matches` is remembered
+    /// after the match it created.
+    pub idempotency_key_ttl_secs: u64,
+    /// Maximum number of remembered idempotency keys (0 disables them).
+    pub idempotency_key_entries: usize,
+
+    // ── Validation limits ────────────────────────────────────────────
+    /// Maximum match comment length in grapheme clusters (what users
+    /// perceive as characters, so "👨‍👩‍👧" counts as one).
+    pub comment_max_graphemes: usize,
+    /// Hard byte ceiling for comments, independent of the grapheme limit,
+    /// keeping stored properties well under Azure's 64 KiB limit.
+    pub comment_max_bytes: usize,
+    /// Maximum games accepted in one `POST /api/matches/session` request.
+    pub session_max_games: usize,
 }
 
 impl AppConfig {
@@ -40,22 +192,319 @@ impl AppConfig {
     /// intentional because the app can't function without these values, and we
     /// want to fail fast at startup rather than later at runtime.
     pub fn from_env() -> Self {
-        Self {
+        let app_url = required("APP_URL");
+        let config = Self {
             azure_storage_account: required("AZURE_STORAGE_ACCOUNT"),
             azure_storage_access_key: required("AZURE_STORAGE_ACCESS_KEY"),
-            azure_tenant_id: required("AZURE_TENANT_ID"),
+            storage_max_retries: parsed("STORAGE_MAX_RETRIES", 3),
+            oidc_authority: optional("OIDC_AUTHORITY")
+                .unwrap_or_else(|| {
+                    let tenant = required("AZURE_TENANT_ID");
+                    format!("https://login.microsoftonline.com/{tenant}/v2.0")
+                })
+                .trim_end_matches('/')
+                .to_string(),
             azure_client_id: required("AZURE_CLIENT_ID"),
             azure_client_secret: required("AZURE_CLIENT_SECRET"),
-            app_url: required("APP_URL"),
+            cookie_secure: cookie_secure(
+                &app_url,
+                optional("COOKIE_SECURE").map(|_| flag("COOKIE_SECURE")),
+            ),
+            app_url,
+            app_urls: list("APP_URLS", &[]),
             session_secret: required("SESSION_SECRET"),
+            session_ttl_hours: parsed("SESSION_TTL_HOURS", 24),
+            admin_emails: list("ADMIN_EMAILS", &[])
+                .into_iter()
+                .map(|email| email.to_lowercase())
+                .collect(),
+            require_email_claim: flag("REQUIRE_EMAIL_CLAIM"),
+            migrate_on_startup: flag("MIGRATE_ON_STARTUP"),
+            force_https: flag("FORCE_HTTPS"),
             port: env::var("PORT")
                 .unwrap_or_else(|_| "3000".to_string())
                 .parse()
                 .expect("PORT must be a valid u16"),
+            static_dir: env::var("STATIC_DIR").unwrap_or_else(|_| "static".to_string()),
+            timezone: parsed("TIMEZONE", Tz::UTC),
+            cors_allowed_origins: list("CORS_ALLOWED_ORIGINS", &[])
+                .into_iter()
+                .map(|origin| parse_origin(&origin))
+                .collect(),
+            cors_allowed_methods: list("CORS_ALLOWED_METHODS", &["GET", "POST", "PUT", "DELETE"])
+                .into_iter()
+                .map(|method| parse_method(&method))
+                .collect(),
+            cors_max_age_secs: parsed("CORS_MAX_AGE", 600),
+            match_webhook_url: optional("MATCH_WEBHOOK_URL"),
+            match_webhook_secret: optional("MATCH_WEBHOOK_SECRET"),
+            metrics_token: optional("METRICS_TOKEN"),
+            auto_nickname: flag("AUTO_NICKNAME"),
+            nickname_adjectives: list(
+                "NICKNAME_ADJECTIVES",
+                &["Spicy", "Sneaky", "Mighty", "Wobbly", "Turbo", "Sleepy", "Salty", "Fearless"],
+            ),
+            nickname_nouns: list(
+                "NICKNAME_NOUNS",
+                &["Dinker", "Pickle", "Smasher", "Lobster", "Volley", "Paddle", "Kitchen", "Ace"],
+            ),
+            auto_create_players: flag("AUTO_CREATE_PLAYERS"),
+            anonymize_losers: flag("ANONYMIZE_LOSERS"),
+            recorder_must_participate: flag("RECORDER_MUST_PARTICIPATE"),
+            duplicate_match_policy: parsed("DUPLICATE_MATCH_POLICY", DuplicatePolicy::Off),
+            duplicate_match_window_secs: parsed("DUPLICATE_MATCH_WINDOW_SECS", 600),
+            suspicious_match_interval_secs: parsed("SUSPICIOUS_MATCH_INTERVAL_SECS", 300),
+            max_matches_per_player_per_day: Some(parsed("MAX_MATCHES_PER_PLAYER_PER_DAY", 0))
+                .filter(|&cap| cap > 0),
+            repeat_matchup_threshold: Some(parsed("REPEAT_MATCHUP_THRESHOLD", 0))
+                .filter(|&n| n > 0),
+            repeat_matchup_window_days: parsed("REPEAT_MATCHUP_WINDOW_DAYS", 7),
+            quiet_hours: optional("QUIET_HOURS").map(|v| {
+                v.parse()
+                    .unwrap_or_else(|e| panic!("QUIET_HOURS has an invalid value: {e}"))
+            }),
+            event_log: flag("EVENT_LOG"),
+            hot_streak_threshold: parsed("HOT_STREAK_THRESHOLD", 3),
+            shared_ranks: flag("SHARED_RANKS"),
+            streak_tolerance: parsed("STREAK_TOLERANCE", 1),
+            timeline_max_snapshots: parsed("TIMELINE_MAX_SNAPSHOTS", 100),
+            elo_carryover: parsed("ELO_CARRYOVER", 0.0),
+            glicko_period_days: parsed("GLICKO_PERIOD_DAYS", 7),
+            comeback_period_months: parsed("COMEBACK_PERIOD_MONTHS", 1),
+            comeback_min_games: parsed("COMEBACK_MIN_GAMES", 3),
+            mvp_min_games: parsed("MVP_MIN_GAMES", 3),
+            month_highlight_min_games: parsed("MONTH_HIGHLIGHT_MIN_GAMES", 5),
+            adjusted_win_rate_gap: parsed("ADJUSTED_WIN_RATE_GAP", 0.25),
+            export_cache_entries: parsed("EXPORT_CACHE_ENTRIES", 64),
+            export_page_size: parsed("EXPORT_PAGE_SIZE", 500),
+            idempotency_key_ttl_secs: parsed("IDEMPOTENCY_KEY_TTL_SECS", 86_400),
+            idempotency_key_entries: parsed("IDEMPOTENCY_KEY_ENTRIES", 1000),
+            comment_max_graphemes: parsed("COMMENT_MAX_GRAPHEMES", 280),
+            comment_max_bytes: parsed("COMMENT_MAX_BYTES", 4096),
+            session_max_games: parsed("SESSION_MAX_GAMES", 20),
+        };
+        config.validate();
+        config
+    }
+
+    /// Panic on settings that parse but make no sense, naming the variable.
+    fn validate(&self) {
+        assert!(
+            !self.force_https || self.app_url.starts_with("https://"),
+            "FORCE_HTTPS requires APP_URL to start with https://"
+        );
+        // A threshold of 0 would flag every player, winless ones included.
+        assert!(
+            self.hot_streak_threshold > 0,
+            "HOT_STREAK_THRESHOLD must be at least 1"
+        );
+        // A session goes into storage as one transaction, which Azure caps.
+        assert!(
+            (1..=MAX_BATCH_MATCHES).contains(&self.session_max_games),
+            "SESSION_MAX_GAMES must be between 1 and {MAX_BATCH_MATCHES}"
+        );
+        // A stolen cookie stays usable this long, and `/api/auth/refresh`
+        // extends active sessions anyway.
+        assert!(
+            (1..=MAX_SESSION_TTL_HOURS).contains(&self.session_ttl_hours),
+            "SESSION_TTL_HOURS must be between 1 and {MAX_SESSION_TTL_HOURS}"
+        );
+        assert!(
+            (0.0..=1.0).contains(&self.elo_carryover),
+            "ELO_CARRYOVER must be between 0.0 and 1.0"
+        );
+        assert!(
+            (1..=1000).contains(&self.export_page_size),
+            "EXPORT_PAGE_SIZE must be between 1 and 1000"
+        );
+        assert!(
+            (0.0..=1.0).contains(&self.adjusted_win_rate_gap),
+            "ADJUSTED_WIN_RATE_GAP must be between 0.0 and 1.0"
+        );
+        assert!(
+            self.glicko_period_days > 0,
+            "GLICKO_PERIOD_DAYS must be at least 1"
+        );
+        assert!(
+            self.comeback_period_months > 0,
+            "COMEBACK_PERIOD_MONTHS must be at least 1"
+        );
+        // Browsers clamp anything longer (Chromium to 2 hours, Firefox to a
+        // day), so a bigger value is almost certainly a typo.
+        assert!(
+            self.cors_max_age_secs <= MAX_CORS_MAX_AGE_SECS,
+            "CORS_MAX_AGE must be at most {MAX_CORS_MAX_AGE_SECS} seconds"
+        );
+    }
+}
+
+#[cfg(test)]
+impl AppConfig {
+    /// What `from_env` yields with only the required variables set (and an
+    /// http `APP_URL`), for unit tests to adjust.
+    pub fn for_tests() -> Self {
+        let words = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+        Self {
+            azure_storage_account: "devstoreaccount1".to_string(),
+            azure_storage_access_key: "key".to_string(),
+            storage_max_retries: 3,
+            oidc_authority: "https://login.microsoftonline.com/tenant/v2.0".to_string(),
+            azure_client_id: "client".to_string(),
+            azure_client_secret: "secret".to_string(),
+            app_url: "http://localhost:3000".to_string(),
+            app_urls: Vec::new(),
+            session_secret: "test-session-secret".to_string(),
+            session_ttl_hours: 24,
+            admin_emails: Vec::new(),
+            require_email_claim: false,
+            force_https: false,
+            cookie_secure: false,
+            port: 3000,
+            static_dir: "static".to_string(),
+            timezone: Tz::UTC,
+            migrate_on_startup: false,
+            cors_allowed_origins: Vec::new(),
+            cors_allowed_methods: vec![Method::GET, Method::POST, Method::PUT, Method::DELETE],
+            cors_max_age_secs: 600,
+            match_webhook_url: None,
+            match_webhook_secret: None,
+            metrics_token: None,
+            auto_nickname: false,
+            nickname_adjectives: words(&["Spicy", "Sneaky"]),
+            nickname_nouns: words(&["Dinker", "Pickle"]),
+            auto_create_players: false,
+            anonymize_losers: false,
+            recorder_must_participate: false,
+            duplicate_match_policy: DuplicatePolicy::Off,
+            duplicate_match_window_secs: 600,
+            suspicious_match_interval_secs: 300,
+            max_matches_per_player_per_day: None,
+            repeat_matchup_threshold: None,
+            repeat_matchup_window_days: 7,
+            quiet_hours: None,
+            event_log: false,
+            hot_streak_threshold: 3,
+            shared_ranks: false,
+            streak_tolerance: 1,
+            timeline_max_snapshots: 100,
+            elo_carryover: 0.0,
+            glicko_period_days: 7,
+            comeback_period_months: 1,
+            comeback_min_games: 3,
+            mvp_min_games: 3,
+            month_highlight_min_games: 5,
+            adjusted_win_rate_gap: 0.25,
+            export_cache_entries: 64,
+            export_page_size: 500,
+            idempotency_key_ttl_secs: 86_400,
+            idempotency_key_entries: 1000,
+            comment_max_graphemes: 280,
+            comment_max_bytes: 4096,
+            session_max_games: 20,
+        }
+    }
+}
+
+/// Upper bound for `SESSION_TTL_HOURS` (30 days).
+const MAX_SESSION_TTL_HOURS: i64 = 24 * 30;
+
+/// Upper bound for `CORS_MAX_AGE` (one day).
+const MAX_CORS_MAX_AGE_SECS: u64 = 86_400;
+
+/// Methods that may be listed in `CORS_ALLOWED_METHODS`.
+const CORS_METHODS: [Method; 7] = [
+    Method::GET,
+    Method::POST,
+    Method::PUT,
+    Method::PATCH,
+    Method::DELETE,
+    Method::HEAD,
+    Method::OPTIONS,
+];
+
+/// Parse one `CORS_ALLOWED_ORIGINS` entry, panicking on anything that isn't
+/// a bare `scheme://host[:port]` origin.
+fn parse_origin(origin: &str) -> HeaderValue {
+    let valid = (origin.starts_with("http://") || origin.starts_with("https://"))
+        && !origin.ends_with('/');
+    match HeaderValue::from_str(origin) {
+        Ok(value) if valid => value,
+        _ => panic!(
+            "CORS_ALLOWED_ORIGINS has an invalid origin: {origin:?} (expected e.g. https://example.com)"
+        ),
+    }
+}
+
+/// Parse one `CORS_ALLOWED_METHODS` entry, panicking on unknown methods.
+fn parse_method(method: &str) -> Method {
+    CORS_METHODS
+        .into_iter()
+        .find(|m| m.as_str().eq_ignore_ascii_case(method))
+        .unwrap_or_else(|| panic!("CORS_ALLOWED_METHODS has an unknown method: {method:?}"))
+}
+
+/// How to treat a suspected accidental resubmit of a match.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// No check (default).
+    Off,
+    /// Record the match but attach a warning to the response.
+    Warn,
+    /// Refuse the match with `409 Conflict`.
+    Reject,
+}
+
+impl FromStr for DuplicatePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "warn" => Ok(Self::Warn),
+            "reject" => Ok(Self::Reject),
+            other => Err(format!("unknown policy '{other}' (expected off, warn or reject)")),
+        }
+    }
+}
+
+/// A daily local-time window, e.g. `00:00-06:00`. The start is inclusive and
+/// the end exclusive; a window whose end is before its start wraps past
+/// midnight (`22:00-06:00`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    /// Whether `time` falls inside the window.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
         }
     }
 }
 
+impl FromStr for QuietHours {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once('-')
+            .ok_or_else(|| format!("'{s}' is not a HH:MM-HH:MM range"))?;
+        let parse = |t: &str| {
+            NaiveTime::parse_from_str(t.trim(), "%H:%M")
+                .map_err(|_| format!("'{}' is not a HH:MM time", t.trim()))
+        };
+        Ok(Self {
+            start: parse(start)?,
+            end: parse(end)?,
+        })
+    }
+}
+
 /// Helper: read a required env var or panic with a helpful message.
 fn required(name: &str) -> String {
     env::var(name).unwrap_or_else(|_| {
@@ -64,3 +513,73 @@ fn required(name: &str) -> String {
         )
     })
 }
+
+/// Whether session cookies get `Secure`: `COOKIE_SECURE` when set, otherwise
+/// whether the app is served over https.
+pub(crate) fn cookie_secure(app_url: &str, cookie_secure_var: Option<bool>) -> bool {
+    cookie_secure_var.unwrap_or_else(|| app_url.starts_with("https://"))
+}
+
+/// Helper: read an optional env var, treating an empty value as unset.
+fn optional(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+/// Helper: read an optional env var parsed into `T`, or `default` if unset.
+///
+/// Panics if the variable is set but malformed — same fail-fast reasoning as
+/// `required`.
+fn parsed<T: std::str::FromStr>(name: &str, default: T) -> T {
+    match env::var(name) {
+        Ok(v) => v
+            .trim()
+            .parse()
+            .unwrap_or_else(|_| panic!("{name} has an invalid value: {v:?}")),
+        Err(_) => default,
+    }
+}
+
+/// Helper: read a boolean feature flag. Only "true" or "1" turn it on;
+/// anything else (including unset) means off.
+fn flag(name: &str) -> bool {
+    env::var(name)
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "true" | "1"))
+        .unwrap_or(false)
+}
+
+/// Helper: read a comma-separated list, falling back to `default` when the
+/// variable is unset or contains no non-empty items.
+fn list(name: &str, default: &[&str]) -> Vec<String> {
+    let items: Vec<String> = env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    if items.is_empty() {
+        default.iter().map(|s| s.to_string()).collect()
+    } else {
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_ttl_may_be_up_to_thirty_days() {
+        let mut config = AppConfig::for_tests();
+        config.session_ttl_hours = MAX_SESSION_TTL_HOURS;
+        config.validate();
+    }
+
+    #[test]
+    #[should_panic(expected = "SESSION_TTL_HOURS must be between 1 and 720")]
+    fn longer_sessions_are_refused() {
+        let mut config = AppConfig::for_tests();
+        config.session_ttl_hours = MAX_SESSION_TTL_HOURS + 1;
+        config.validate();
+    }
+}
@@ -0,0 +1,93 @@
+// csv.rs — Minimal CSV helpers.
+//
+// Our CSV exports are small and flat, so we write them by hand (RFC 4180:
+// comma-separated, CRLF line endings, fields quoted only when needed) rather
+// than pulling in a CSV crate. `parse` reads the same format back for imports.
+
+use axum::body::Body;
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+
+/// Escape one field: wrap it in double quotes (doubling any inside) if it
+/// contains a comma, quote or line break; otherwise return it unchanged.
+pub fn field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One CSV line: every field escaped, comma-separated, CRLF-terminated.
+pub fn row<S: AsRef<str>>(fields: &[S]) -> String {
+    let fields: Vec<String> = fields.iter().map(|f| field(f.as_ref())).collect();
+    format!("{}\r\n", fields.join(","))
+}
+
+/// Parse CSV text into records of fields.
+///
+/// Accepts what spreadsheets produce: LF or CRLF line endings, quoted fields
+/// containing commas, doubled quotes and line breaks, and a leading UTF-8
+/// BOM. Blank lines are skipped. Only an unterminated quote is an error.
+pub fn parse(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                push_record(&mut records, std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("unterminated quoted field".to_string());
+    }
+    record.push(field);
+    push_record(&mut records, record);
+
+    Ok(records)
+}
+
+/// Keep `record` unless it came from a blank line.
+fn push_record(records: &mut Vec<Vec<String>>, record: Vec<String>) {
+    if !(record.len() == 1 && record[0].is_empty()) {
+        records.push(record);
+    }
+}
+
+/// A `200` response carrying `body` (a string or a stream of chunks) as a
+/// downloadable CSV file.
+pub fn response(filename: &str, body: impl Into<Body>) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        body.into(),
+    )
+        .into_response()
+}
@@ -0,0 +1,42 @@
+// extract.rs — Request extractors shared by the route handlers.
+//
+// Axum's own `Json` extractor rejects a bad body with a plain-text message,
+// while everything else in the API answers `{"error": "..."}`. `ApiJson` is
+// `Json` with a rejection in the API's shape, so clients can handle a typo in
+// a request body like any other validation error.
+
+use axum::{
+    Json,
+    extract::{FromRequest, rejection::JsonRejection},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+/// `Json<T>` for request bodies, rejecting with `{"error": "..."}`.
+#[derive(FromRequest)]
+#[from_request(via(Json), rejection(ApiJsonRejection))]
+pub struct ApiJson<T>(pub T);
+
+/// Why a request body couldn't be read as JSON.
+pub struct ApiJsonRejection(JsonRejection);
+
+impl From<JsonRejection> for ApiJsonRejection {
+    fn from(rejection: JsonRejection) -> Self {
+        Self(rejection)
+    }
+}
+
+impl IntoResponse for ApiJsonRejection {
+    /// Malformed JSON and JSON of the wrong shape are both `400`, with serde's
+    /// message, e.g. "missing field `winner1_id` at line 1 column 42". Other
+    /// failures (wrong content type, oversized body) keep their status.
+    fn into_response(self) -> Response {
+        let status = match &self.0 {
+            JsonRejection::JsonDataError(_) | JsonRejection::JsonSyntaxError(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            other => other.status(),
+        };
+        (status, Json(serde_json::json!({ "error": self.0.body_text() }))).into_response()
+    }
+}
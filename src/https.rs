@@ -0,0 +1,140 @@
+// https.rs — Optional HTTP → HTTPS redirect for deployments behind a proxy.
+//
+// TLS is terminated by whatever sits in front of us (Azure Container Apps,
+// a reverse proxy, ...), so every request reaches us over plain HTTP and the
+// original scheme is only known from `X-Forwarded-Proto`. When `FORCE_HTTPS`
+// is on, requests the proxy marks as `http` get a `301` to the same path and
+// query on the configured app URL matching their `Host` (`APP_URL` or one of
+// `APP_URLS`, as for the OIDC redirect). Unknown hosts, and hosts whose
+// configured URL isn't https, go to `APP_URL`, which must be https.
+//
+// We trust `X-Forwarded-Proto` as-is: the app is only ever reachable through
+// the proxy. Requests without the header (e.g. a probe hitting the container
+// directly) pass through, as does the health check path.
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, StatusCode, Uri, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::auth::oidc::base_url_for_host;
+use crate::config::AppConfig;
+
+/// Paths never redirected, so platform health probes over plain HTTP work.
+const EXEMPT_PATHS: &[&str] = &["/api/health"];
+
+/// Middleware: `301` to the https URL of the same page when the proxy saw
+/// plain HTTP.
+pub async fn redirect_to_https(
+    State(config): State<AppConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if forwarded_as_http(request.headers()) && !EXEMPT_PATHS.contains(&request.uri().path()) {
+        let host = request.headers().get(header::HOST).and_then(|v| v.to_str().ok());
+        let location = https_location(https_base_url(&config, host), request.uri());
+        // Not `Redirect::permanent`, which is a 308.
+        return (StatusCode::MOVED_PERMANENTLY, [(header::LOCATION, location)]).into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Whether the proxy reports the original request as plain HTTP. Only the
+/// first value counts when proxies are chained (`http, https`).
+fn forwarded_as_http(headers: &HeaderMap) -> bool {
+    headers
+        .get("x-forwarded-proto")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .is_some_and(|proto| proto.trim().eq_ignore_ascii_case("http"))
+}
+
+/// The configured app URL for `host` if it's https, otherwise `APP_URL`.
+fn https_base_url<'a>(config: &'a AppConfig, host: Option<&str>) -> &'a str {
+    let base_url = base_url_for_host(config, host);
+    if base_url.starts_with("https://") {
+        base_url
+    } else {
+        &config.app_url
+    }
+}
+
+fn https_location(base_url: &str, uri: &Uri) -> String {
+    let path_and_query = uri.path_and_query().map_or("/", |pq| pq.as_str());
+    format!("{}{path_and_query}", base_url.trim_end_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, middleware, routing::get};
+    use tower::ServiceExt;
+
+    fn config() -> AppConfig {
+        let mut config = AppConfig::for_tests();
+        config.app_url = "https://scores.example.com".to_string();
+        config.app_urls = vec![
+            "https://pickle.example.org/".to_string(),
+            "http://staging.example.com".to_string(),
+        ];
+        config.force_https = true;
+        config
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/leaderboard", get(|| async { "ok" }))
+            .route("/api/health", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(config(), redirect_to_https))
+    }
+
+    async fn send(host: &str, proto: &str, path: &str) -> Response {
+        let request = Request::builder()
+            .uri(path)
+            .header(header::HOST, host)
+            .header("x-forwarded-proto", proto)
+            .body(Body::empty())
+            .unwrap();
+        app().oneshot(request).await.unwrap()
+    }
+
+    fn location(response: &Response) -> &str {
+        response.headers()[header::LOCATION].to_str().unwrap()
+    }
+
+    #[tokio::test]
+    async fn http_requests_are_redirected_with_path_and_query() {
+        let response = send("scores.example.com", "http", "/leaderboard?league_id=l1").await;
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(location(&response), "https://scores.example.com/leaderboard?league_id=l1");
+    }
+
+    #[tokio::test]
+    async fn https_requests_pass_through() {
+        let response = send("scores.example.com", "https", "/leaderboard").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn secondary_hosts_stay_on_their_own_url() {
+        let response = send("pickle.example.org", "http", "/leaderboard").await;
+        assert_eq!(location(&response), "https://pickle.example.org/leaderboard");
+    }
+
+    #[tokio::test]
+    async fn unknown_and_non_https_hosts_go_to_app_url() {
+        let response = send("evil.example.net", "http", "/leaderboard").await;
+        assert_eq!(location(&response), "https://scores.example.com/leaderboard");
+        let response = send("staging.example.com", "http", "/leaderboard").await;
+        assert_eq!(location(&response), "https://scores.example.com/leaderboard");
+    }
+
+    #[tokio::test]
+    async fn health_checks_are_never_redirected() {
+        let response = send("scores.example.com", "http", "/api/health").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,210 @@
+// idempotency.rs — Remembering `Idempotency-Key`s for match creation.
+//
+// Flaky mobile connections retry `POST /api/matches`, and because every match
+// ID carries a random suffix the retry would be stored as a second match.
+// Clients can send an `Idempotency-Key` header instead; we remember which
+// match each key produced and answer a repeat with that match.
+//
+// Keys live in memory only, for `IDEMPOTENCY_KEY_TTL_SECS` after the match was
+// created, so they don't survive a restart or span several instances. The
+// table is bounded: expired keys are dropped first, then the least recently
+// used one. That's plenty for the retry window this is meant to cover.
+//
+// A key whose first request is still running is "in flight": a concurrent
+// repeat is turned away rather than racing it to create a duplicate. If the
+// first request fails, the key is released so a retry can go through.
+
+use axum::{
+    Json,
+    extract::FromRequestParts,
+    http::{StatusCode, request::Parts},
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Header clients use to mark retries of the same request.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Longest key we accept.
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// Extractor for the request's `Idempotency-Key`, if it sent one (1–255
+/// visible ASCII characters; anything else is rejected with `400`), together
+/// with the shared `IdempotencyCache` extension to claim it in.
+pub struct IdempotencyKey {
+    cache: IdempotencyCache,
+    key: Option<String>,
+}
+
+impl IdempotencyKey {
+    /// Claim the key within `scope` (e.g. the user, so one person's key can't
+    /// replay another's match). `None` if the request sent no key.
+    pub fn claim(&self, scope: &str) -> Option<Claim> {
+        let key = self.key.as_ref()?;
+        Some(self.cache.claim(format!("{scope}:{key}")))
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for IdempotencyKey {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(cache) = parts.extensions.get::<IdempotencyCache>().cloned() else {
+            tracing::error!("IdempotencyCache not found in request extensions");
+            return Err(error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Server configuration error".to_string(),
+            ));
+        };
+        let key = match parts.headers.get(IDEMPOTENCY_KEY_HEADER).map(|v| v.to_str()) {
+            None => None,
+            Some(Ok(key)) if !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN => {
+                Some(key.to_string())
+            }
+            Some(_) => {
+                return Err(error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "Idempotency-Key must be 1–{MAX_IDEMPOTENCY_KEY_LEN} visible ASCII \
+                         characters"
+                    ),
+                ));
+            }
+        };
+        Ok(Self { cache, key })
+    }
+}
+
+fn error_response(status: StatusCode, message: String) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+/// What a key resolved to.
+enum Slot {
+    /// The first request with this key hasn't finished yet.
+    InFlight,
+    /// The match created by the first request.
+    Done(String),
+}
+
+struct Entry {
+    slot: Slot,
+    /// When the key was claimed, or when its match was created once done.
+    since: Instant,
+    last_used: Instant,
+}
+
+/// Shared table of recently seen idempotency keys. Cheap to clone.
+#[derive(Clone)]
+pub struct IdempotencyCache {
+    /// Maximum number of keys. 0 disables idempotency keys entirely.
+    capacity: usize,
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+}
+
+/// Outcome of `IdempotencyCache::claim`.
+pub enum Claim {
+    /// First time we see this key: go ahead, then `complete` the guard.
+    New(IdempotencyGuard),
+    /// Already used; the ID of the match it created.
+    Done(String),
+    /// Another request with this key is still being processed.
+    InFlight,
+}
+
+impl IdempotencyCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Look up `key`, claiming it for the caller if it's unknown or expired.
+    pub fn claim(&self, key: String) -> Claim {
+        let guard = IdempotencyGuard {
+            cache: self.clone(),
+            key: key.clone(),
+            completed: false,
+        };
+        if self.capacity == 0 {
+            return Claim::New(guard);
+        }
+
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.get_mut(&key)
+            && now.duration_since(entry.since) < self.ttl
+        {
+            entry.last_used = now;
+            return match &entry.slot {
+                Slot::InFlight => Claim::InFlight,
+                Slot::Done(match_id) => Claim::Done(match_id.clone()),
+            };
+        }
+
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, e| now.duration_since(e.since) < self.ttl);
+            if entries.len() >= self.capacity
+                && let Some(oldest) = entries
+                    .iter()
+                    .min_by_key(|(_, e)| e.last_used)
+                    .map(|(k, _)| k.clone())
+            {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            Entry {
+                slot: Slot::InFlight,
+                since: now,
+                last_used: now,
+            },
+        );
+        Claim::New(guard)
+    }
+}
+
+/// A claimed key. Record the outcome with `complete`; dropping the guard
+/// without completing it (the request failed) releases the key.
+pub struct IdempotencyGuard {
+    cache: IdempotencyCache,
+    key: String,
+    completed: bool,
+}
+
+impl IdempotencyGuard {
+    /// Remember that the key created `match_id`. Its lifetime starts now.
+    pub fn complete(mut self, match_id: String) {
+        self.completed = true;
+        if self.cache.capacity == 0 {
+            return;
+        }
+        let now = Instant::now();
+        self.cache.entries.lock().unwrap().insert(
+            std::mem::take(&mut self.key),
+            Entry {
+                slot: Slot::Done(match_id),
+                since: now,
+                last_used: now,
+            },
+        );
+    }
+}
+
+impl Drop for IdempotencyGuard {
+    fn drop(&mut self) {
+        if self.completed || self.cache.capacity == 0 {
+            return;
+        }
+        let mut entries = self.cache.entries.lock().unwrap();
+        if matches!(entries.get(&self.key), Some(e) if matches!(e.slot, Slot::InFlight)) {
+            entries.remove(&self.key);
+        }
+    }
+}
@@ -20,13 +20,28 @@
 //   - Axum serves both the API and the static files from a single binary.
 
 mod auth;
+mod cache;
 mod config;
+mod csv;
+mod extract;
+mod https;
+mod idempotency;
+mod metrics;
 mod models;
 mod routes;
+mod stats;
 mod storage;
+mod webhook;
 
-use axum::Router;
+use axum::body::Body;
+use axum::http::{HeaderName, Request, StatusCode, header};
+use axum::response::Html;
+use axum::{Router, middleware};
 use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+use tower_http::cors::CorsLayer;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 use tower_http::services::{ServeDir, ServeFile};
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -53,6 +68,7 @@ async fn main() {
     dotenvy::dotenv().ok(); // Silently ignore if no .env file exists.
     let config = AppConfig::from_env();
     let port = config.port;
+    let static_dir = config.static_dir.clone();
 
     // ── Initialize Azure Table Storage client ───────────────────────────
     let storage = StorageClient::new(&config);
@@ -63,6 +79,19 @@ async fn main() {
         tracing::warn!("Continuing anyway — tables may already exist or Azure may be unreachable");
     }
 
+    // Optionally backfill properties missing from older matches. Idempotent,
+    // so leaving this on across restarts only costs a table scan.
+    if config.migrate_on_startup {
+        match storage::matches::migrate_matches(&storage).await {
+            Ok(report) => tracing::info!(
+                "Match migration: scanned {}, updated {}",
+                report.scanned,
+                report.updated
+            ),
+            Err(e) => tracing::error!("Match migration failed: {e}"),
+        }
+    }
+
     // ── Build the application router ────────────────────────────────────
     //
     // The router is layered:
@@ -70,13 +99,40 @@ async fn main() {
     //   /*      → Static files (SvelteKit build output)
     //
     // The `ServeDir` fallback serves the SPA's index.html for all unmatched
-    // routes, so client-side routing works correctly.
-    let app = Router::new()
-        .nest("/api", routes::api_router(storage, config))
-        .fallback_service(
-            ServeDir::new("static").fallback(ServeFile::new("static/index.html")),
-        )
-        .layer(TraceLayer::new_for_http());
+    // routes, so client-side routing works correctly. If the frontend hasn't
+    // been built (typical for a fresh `cargo run`), every non-API path gets a
+    // short page saying so instead of a bare 404.
+    let cors = cors_layer(&config);
+    let https_config = config.force_https.then(|| config.clone());
+    let mut app = Router::new().nest("/api", routes::api_router(storage, config));
+    app = with_frontend(app, &static_dir);
+    if let Some(cors) = cors {
+        app = app.layer(cors);
+    }
+    if let Some(https_config) = https_config {
+        app = app.layer(middleware::from_fn_with_state(https_config, https::redirect_to_https));
+    }
+    // Every request gets an ID (the caller's `X-Request-Id`, or a fresh
+    // UUID) that's recorded on its tracing span, so all log lines it causes,
+    // storage errors included, can be tied together. It's echoed back in the
+    // response header for bug reports.
+    let request_id_header = HeaderName::from_static(REQUEST_ID_HEADER);
+    let app = app
+        .layer(TraceLayer::new_for_http().make_span_with(|request: &Request<Body>| {
+            let request_id = request
+                .headers()
+                .get(REQUEST_ID_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default();
+            tracing::info_span!(
+                "request",
+                method = %request.method(),
+                uri = %request.uri(),
+                request_id,
+            )
+        }))
+        .layer(PropagateRequestIdLayer::new(request_id_header.clone()))
+        .layer(SetRequestIdLayer::new(request_id_header, MakeRequestUuid));
 
     // ── Start the server ────────────────────────────────────────────────
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
@@ -86,7 +142,162 @@ async fn main() {
         .await
         .expect("Failed to bind to address");
 
+    // On SIGTERM/Ctrl-C, stop accepting connections but let in-flight
+    // requests (and their storage writes) finish before exiting.
     axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
         .await
         .expect("Server error");
 }
+
+/// Resolve when the process is asked to stop: Ctrl-C, or SIGTERM on Unix
+/// (what Docker and container orchestrators send during a restart).
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl-C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("Shutdown signal received, shutting down gracefully");
+}
+
+/// Header carrying the per-request correlation ID.
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Build the CORS layer from config, or `None` when no origins are allowed
+/// (the default — the SPA is normally served from the same origin).
+///
+/// Credentials are allowed so the session cookie works cross-origin, which
+/// is why origins, methods and headers are listed explicitly (the CORS spec
+/// forbids wildcards together with credentials). Our custom response headers
+/// are exposed so cross-origin clients can page, see warnings and quote request
+/// IDs, and they may send `Idempotency-Key` when recording matches.
+fn cors_layer(config: &AppConfig) -> Option<CorsLayer> {
+    if config.cors_allowed_origins.is_empty() {
+        return None;
+    }
+
+    Some(
+        CorsLayer::new()
+            .allow_origin(config.cors_allowed_origins.clone())
+            .allow_methods(config.cors_allowed_methods.clone())
+            .allow_headers([
+                header::CONTENT_TYPE,
+                HeaderName::from_static(idempotency::IDEMPOTENCY_KEY_HEADER),
+            ])
+            .expose_headers([
+                HeaderName::from_static(routes::matches::CONTINUATION_HEADER),
+                HeaderName::from_static(routes::matches::WARNING_HEADER),
+                HeaderName::from_static(REQUEST_ID_HEADER),
+            ])
+            .allow_credentials(true)
+            .max_age(Duration::from_secs(config.cors_max_age_secs)),
+    )
+}
+
+/// Serve the SPA from `static_dir` for every path `app` doesn't route, or the
+/// "not built" placeholder if the directory doesn't exist.
+fn with_frontend(app: Router, static_dir: &str) -> Router {
+    if Path::new(static_dir).is_dir() {
+        let index = Path::new(static_dir).join("index.html");
+        app.fallback_service(ServeDir::new(static_dir).fallback(ServeFile::new(index)))
+    } else {
+        tracing::warn!(
+            "Static directory '{static_dir}' not found — the frontend is not built. \
+             Serving a placeholder page; run `npm run build` in frontend/ or set STATIC_DIR."
+        );
+        app.fallback(frontend_not_built)
+    }
+}
+
+/// Placeholder served for non-API paths when `STATIC_DIR` doesn't exist.
+const FRONTEND_NOT_BUILT_HTML: &str = r#"<!doctype html>
+<html lang="en">
+<head><meta charset="utf-8"><title>Scoreboard — frontend not built</title></head>
+<body style="font-family: sans-serif; max-width: 40em; margin: 4em auto;">
+<h1>Frontend not built</h1>
+<p>The API is running, but there are no frontend files to serve.</p>
+<p>Run <code>npm run build</code> in <code>frontend/</code> and copy the output to the
+directory named by <code>STATIC_DIR</code> (default <code>static</code>), or use the Vite dev
+server with <code>npm run dev</code> during development.</p>
+</body>
+</html>
+"#;
+
+async fn frontend_not_built() -> (StatusCode, Html<&'static str>) {
+    (StatusCode::NOT_FOUND, Html(FRONTEND_NOT_BUILT_HTML))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{HeaderValue, Method};
+    use axum::routing::get;
+    use tower::ServiceExt;
+
+    async fn preflight(config: &AppConfig) -> axum::response::Response {
+        let app = Router::new()
+            .route("/api/matches", get(|| async { "ok" }))
+            .layer(cors_layer(config).unwrap());
+        let request = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/api/matches")
+            .header(header::ORIGIN, "http://localhost:5173")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn preflight_headers_reflect_the_config() {
+        let mut config = AppConfig::for_tests();
+        config.cors_allowed_origins = vec![HeaderValue::from_static("http://localhost:5173")];
+        config.cors_allowed_methods = vec![Method::GET, Method::POST];
+        config.cors_max_age_secs = 3600;
+
+        let response = preflight(&config).await;
+
+        let headers = response.headers();
+        assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "3600");
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], "GET,POST");
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "http://localhost:5173");
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+    }
+
+    #[test]
+    fn no_origins_means_no_cors_layer() {
+        assert!(cors_layer(&AppConfig::for_tests()).is_none());
+    }
+
+    #[tokio::test]
+    async fn missing_static_dir_serves_the_placeholder_page() {
+        let app = Router::new().route("/api/health", get(|| async { "ok" }));
+        let app = with_frontend(app, "/definitely/not/a/static/dir");
+
+        let request = Request::builder().uri("/leaderboard").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, FRONTEND_NOT_BUILT_HTML);
+
+        let request = Request::builder().uri("/api/health").body(Body::empty()).unwrap();
+        assert_eq!(app.oneshot(request).await.unwrap().status(), StatusCode::OK);
+    }
+}
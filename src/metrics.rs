@@ -0,0 +1,157 @@
+// metrics.rs — In-process counters exposed in Prometheus text format.
+//
+// Two kinds of numbers:
+//   - Business counters (matches created, players created, logins), bumped
+//     by the handlers that do those things.
+//   - Per-route request totals and a latency histogram, recorded by the
+//     `track_requests` middleware for every API route. Routes are labelled by
+//     their pattern (`/api/players/{id}`), not the raw path, so the number of
+//     series stays bounded.
+//
+// Everything lives in memory and resets on restart, which Prometheus handles
+// fine for counters. We render the text format by hand rather than pulling
+// in a metrics crate; it's a few lines of `format!`.
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Upper bounds (seconds) of the latency histogram buckets — Prometheus'
+/// usual defaults, which suit a small JSON API.
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Shared metrics registry. Cheap to clone.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    matches_created: AtomicU64,
+    players_created: AtomicU64,
+    logins: AtomicU64,
+    /// Keyed by (method, route pattern).
+    routes: Mutex<BTreeMap<(String, String), RouteStats>>,
+}
+
+#[derive(Default)]
+struct RouteStats {
+    /// Requests by response status code.
+    by_status: BTreeMap<u16, u64>,
+    /// Non-cumulative counts per `LATENCY_BUCKETS` entry; slower requests
+    /// only show up in `count`.
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum_secs: f64,
+    count: u64,
+}
+
+impl Metrics {
+    pub fn matches_created(&self, n: u64) {
+        self.inner.matches_created.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn player_created(&self) {
+        self.inner.players_created.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn login(&self) {
+        self.inner.logins.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn observe_request(&self, method: String, route: String, status: u16, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        let mut routes = self.inner.routes.lock().unwrap();
+        let stats = routes.entry((method, route)).or_default();
+        *stats.by_status.entry(status).or_default() += 1;
+        if let Some(i) = LATENCY_BUCKETS.iter().position(|&le| secs <= le) {
+            stats.buckets[i] += 1;
+        }
+        stats.sum_secs += secs;
+        stats.count += 1;
+    }
+
+    /// Everything, in the Prometheus text exposition format (version 0.0.4).
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (name, help, value) in [
+            ("matches_created", "Matches recorded.", &self.inner.matches_created),
+            ("players_created", "Players created.", &self.inner.players_created),
+            ("logins", "Successful sign-ins.", &self.inner.logins),
+        ] {
+            let value = value.load(Ordering::Relaxed);
+            let _ = writeln!(out, "# HELP scoreboard_{name}_total {help}");
+            let _ = writeln!(out, "# TYPE scoreboard_{name}_total counter");
+            let _ = writeln!(out, "scoreboard_{name}_total {value}");
+        }
+
+        let routes = self.inner.routes.lock().unwrap();
+        out.push_str("# HELP scoreboard_http_requests_total API requests handled.\n");
+        out.push_str("# TYPE scoreboard_http_requests_total counter\n");
+        for ((method, route), stats) in routes.iter() {
+            let labels = format!("method=\"{method}\",route=\"{}\"", escape(route));
+            for (status, n) in &stats.by_status {
+                let _ = writeln!(
+                    out,
+                    "scoreboard_http_requests_total{{{labels},status=\"{status}\"}} {n}"
+                );
+            }
+        }
+
+        let name = "scoreboard_http_request_duration_seconds";
+        let _ = writeln!(out, "# HELP {name} API request latency.");
+        let _ = writeln!(out, "# TYPE {name} histogram");
+        for ((method, route), stats) in routes.iter() {
+            let labels = format!("method=\"{method}\",route=\"{}\"", escape(route));
+            // Prometheus buckets are cumulative.
+            let mut cumulative = 0;
+            for (le, n) in LATENCY_BUCKETS.iter().zip(stats.buckets) {
+                cumulative += n;
+                let _ = writeln!(out, "{name}_bucket{{{labels},le=\"{le}\"}} {cumulative}");
+            }
+            let _ = writeln!(out, "{name}_bucket{{{labels},le=\"+Inf\"}} {}", stats.count);
+            let _ = writeln!(out, "{name}_sum{{{labels}}} {}", stats.sum_secs);
+            let _ = writeln!(out, "{name}_count{{{labels}}} {}", stats.count);
+        }
+
+        out
+    }
+}
+
+/// Escape a label value: backslash, double quote and newline.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Middleware recording the count, status and latency of each request under
+/// its route pattern. Must be added with `route_layer` so the route has been
+/// matched (and `MatchedPath` set) by the time it runs.
+pub async fn track_requests(
+    State(metrics): State<Metrics>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    metrics.observe_request(method, route, response.status().as_u16(), start.elapsed());
+    response
+}
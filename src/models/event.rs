@@ -0,0 +1,130 @@
+// models/event.rs — Append-only audit events.
+//
+// When `EVENT_LOG` is enabled, every match creation and deletion is also
+// written to the "events" table as an immutable event carrying the full
+// match payload and the acting user. The matches table can be edited or
+// pruned later; the event log is never updated, so it preserves what
+// actually happened and could be replayed to rebuild the data.
+//
+// Unlike matches, event RowKeys use a *forward* timestamp, so the partition
+// reads back oldest-first — the natural order for replaying a log.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::match_record::MatchRecord;
+
+/// The PartitionKey used for all events.
+pub const EVENT_PARTITION_KEY: &str = "event";
+
+/// What happened to a match.
+#[derive(Debug, Clone, Copy)]
+pub enum MatchEventKind {
+    Created,
+    Deleted,
+    Restored,
+}
+
+impl MatchEventKind {
+    /// The stored event type, e.g. `match.created`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MatchEventKind::Created => "match.created",
+            MatchEventKind::Deleted => "match.deleted",
+            MatchEventKind::Restored => "match.restored",
+        }
+    }
+}
+
+/// Azure Table Storage entity for an event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EventEntity {
+    /// Always "event".
+    pub partition_key: String,
+
+    /// `{unix_millis:020}_{uuid}` — chronological and unique.
+    pub row_key: String,
+
+    /// e.g. "match.created" or "match.deleted".
+    #[serde(rename = "event_type")]
+    pub event_type: String,
+
+    /// ID of the match the event is about.
+    #[serde(rename = "match_id")]
+    pub match_id: String,
+
+    /// OID of the user who performed the action.
+    #[serde(rename = "actor")]
+    pub actor: String,
+
+    /// The full match as JSON, as it was at the time of the event.
+    #[serde(rename = "payload")]
+    pub payload: String,
+
+    #[serde(rename = "occurred_at")]
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl EventEntity {
+    /// Build an event for `record`, stamped with the current time.
+    pub fn for_match(kind: MatchEventKind, actor: &str, record: &MatchRecord) -> Self {
+        let occurred_at = Utc::now();
+        Self {
+            partition_key: EVENT_PARTITION_KEY.to_string(),
+            row_key: format!("{:020}_{}", occurred_at.timestamp_millis(), Uuid::new_v4()),
+            event_type: kind.as_str().to_string(),
+            match_id: record.id.clone(),
+            actor: actor.to_string(),
+            // Serializing a plain struct of strings/numbers can't fail.
+            payload: serde_json::to_string(record).unwrap_or_default(),
+            occurred_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::match_record::NewMatch;
+
+    fn record() -> MatchRecord {
+        MatchRecord::new(NewMatch {
+            winner1_id: "alice".to_string(),
+            loser1_id: "bob".to_string(),
+            winner_score: Some(11),
+            loser_score: Some(4),
+            recorded_by: "user-1".to_string(),
+            played_at: Utc::now(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn a_created_match_becomes_an_event_with_the_full_payload() {
+        let record = record();
+
+        let event = EventEntity::for_match(MatchEventKind::Created, "user-1", &record);
+
+        assert_eq!(event.partition_key, EVENT_PARTITION_KEY);
+        assert_eq!(event.event_type, "match.created");
+        assert_eq!(event.match_id, record.id);
+        assert_eq!(event.actor, "user-1");
+        let payload: MatchRecord = serde_json::from_str(&event.payload).unwrap();
+        assert_eq!(
+            serde_json::to_value(payload).unwrap(),
+            serde_json::to_value(&record).unwrap()
+        );
+    }
+
+    #[test]
+    fn later_events_sort_after_earlier_ones() {
+        let first = EventEntity::for_match(MatchEventKind::Created, "user-1", &record());
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = EventEntity::for_match(MatchEventKind::Deleted, "user-1", &record());
+        assert!(first.row_key < second.row_key);
+        assert_eq!(second.event_type, "match.deleted");
+    }
+}
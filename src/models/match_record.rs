@@ -1,6 +1,6 @@
 // models/match_record.rs — Match struct and Azure Table Storage entity mapping.
 //
-// Each match records a doubles (2v2) pickleball game result. We store them in
+// Each match records a doubles (2v2) or singles (1v1) pickleball game result. We store them in
 // Azure Table Storage with a reverse-timestamp RowKey so that querying the
 // partition returns matches in newest-first order by default.
 //
@@ -15,28 +15,62 @@
 //   Matches without a league_id are "unaffiliated" and always show up in
 //   all-time stats. The `#[serde(default)]` attribute ensures backward
 //   compatibility with existing matches that don't have this field.
+//
+// Singles:
+//   `match_type` tells singles from doubles. Singles have no second winner or
+//   loser: `winner2_id`/`loser2_id` are `None` in the domain type and empty
+//   strings in storage. Rows stored before singles existed have no
+//   `match_type` property and deserialize as doubles. Stats code should walk
+//   `winner_ids()`/`loser_ids()` rather than the slots directly, so singles
+//   never credit a phantom partner.
+//
+// Forfeits:
+//   A match can be marked `forfeit` when one team conceded without playing.
+//   Forfeits count as a normal win/loss for standings, but any scores on them
+//   are ignored by score-based stats (use `MatchRecord::scores`).
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
 use uuid::Uuid;
 
 /// Maximum timestamp value for the reverse-timestamp trick.
 /// We use the year 9999 in milliseconds as our ceiling.
 const MAX_TIMESTAMP_MS: i64 = 253_402_300_799_999;
 
-/// A recorded pickleball match (doubles: 2v2).
+/// Returned when a `played_at` falls outside the range the reverse-timestamp
+/// RowKey can represent (i.e. after the end of year 9999).
+#[derive(Debug, thiserror::Error)]
+#[error("played_at {0} is beyond the supported range (must be before year 10000)")]
+pub struct PlayedAtOutOfRange(pub DateTime<Utc>);
+
+/// Whether a match was 1v1 or 2v2.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchType {
+    Singles,
+    /// The default, and what every match stored before singles existed is.
+    #[default]
+    Doubles,
+}
+
+/// A recorded pickleball match (doubles: 2v2, or singles: 1v1).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MatchRecord {
     /// Unique match ID (the RowKey from Azure, which includes the reverse timestamp).
     pub id: String,
 
-    /// Player IDs of the winning team.
+    #[serde(default)]
+    pub match_type: MatchType,
+
+    /// Player IDs of the winning team. `winner2_id` is `None` for singles.
     pub winner1_id: String,
-    pub winner2_id: String,
+    pub winner2_id: Option<String>,
 
-    /// Player IDs of the losing team.
+    /// Player IDs of the losing team. `loser2_id` is `None` for singles.
     pub loser1_id: String,
-    pub loser2_id: String,
+    pub loser2_id: Option<String>,
 
     /// Optional scores.
     pub winner_score: Option<i32>,
@@ -58,6 +92,14 @@ pub struct MatchRecord {
     /// the league feature was added — they'll deserialize with `None`.
     #[serde(default)]
     pub league_id: Option<String>,
+
+    /// Whether the losing team forfeited instead of playing.
+    #[serde(default)]
+    pub forfeit: bool,
+
+    /// Shared by all games entered together via `POST /api/matches/session`.
+    #[serde(default)]
+    pub session_id: Option<String>,
 }
 
 /// Azure Table Storage entity for a match.
@@ -67,13 +109,18 @@ pub struct MatchEntity {
     pub partition_key: String,
     pub row_key: String,
 
+    /// Missing on rows stored before singles existed → doubles.
+    #[serde(rename = "match_type", default)]
+    pub match_type: MatchType,
     #[serde(rename = "winner1_id")]
     pub winner1_id: String,
-    #[serde(rename = "winner2_id")]
+    /// Empty for singles.
+    #[serde(rename = "winner2_id", default)]
     pub winner2_id: String,
     #[serde(rename = "loser1_id")]
     pub loser1_id: String,
-    #[serde(rename = "loser2_id")]
+    /// Empty for singles.
+    #[serde(rename = "loser2_id", default)]
     pub loser2_id: String,
     #[serde(rename = "winner_score")]
     pub winner_score: Option<i32>,
@@ -89,11 +136,103 @@ pub struct MatchEntity {
     /// in storage that don't have this field yet (they deserialize as `None`).
     #[serde(rename = "league_id", default)]
     pub league_id: Option<String>,
+    #[serde(rename = "forfeit", default)]
+    pub forfeit: bool,
+    #[serde(rename = "session_id", default)]
+    pub session_id: Option<String>,
+    /// Soft-deleted: hidden from every listing and stat until restored.
+    /// Never set on the domain `MatchRecord` — deleted rows don't leave storage.
+    #[serde(rename = "deleted", default)]
+    pub deleted: bool,
+}
+
+/// A match entity as it may exist in older data, with every property that
+/// was added after launch optional. Used by the backfill migration to tell
+/// "missing" apart from "set to the default".
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LegacyMatchEntity {
+    pub partition_key: String,
+    pub row_key: String,
+    #[serde(rename = "match_type", default)]
+    pub match_type: MatchType,
+    #[serde(rename = "winner1_id")]
+    pub winner1_id: String,
+    #[serde(rename = "winner2_id", default)]
+    pub winner2_id: String,
+    #[serde(rename = "loser1_id")]
+    pub loser1_id: String,
+    #[serde(rename = "loser2_id", default)]
+    pub loser2_id: String,
+    #[serde(rename = "winner_score")]
+    pub winner_score: Option<i32>,
+    #[serde(rename = "loser_score")]
+    pub loser_score: Option<i32>,
+    #[serde(rename = "comment")]
+    pub comment: Option<String>,
+    #[serde(rename = "recorded_by")]
+    pub recorded_by: Option<String>,
+    #[serde(rename = "played_at")]
+    pub played_at: String,
+    #[serde(rename = "league_id", default)]
+    pub league_id: Option<String>,
+    #[serde(rename = "forfeit", default)]
+    pub forfeit: bool,
+    #[serde(rename = "session_id", default)]
+    pub session_id: Option<String>,
+    #[serde(rename = "deleted", default)]
+    pub deleted: bool,
+}
+
+/// Value written to `recorded_by` for matches stored before it was tracked
+/// (the old hardcoded submitter).
+pub const LEGACY_RECORDED_BY: &str = "anonymous";
+
+impl LegacyMatchEntity {
+    /// Whether any backfilled property is missing from this entity.
+    pub fn needs_backfill(&self) -> bool {
+        self.comment.is_none() || self.recorded_by.is_none()
+    }
+}
+
+/// Fill defaults for missing properties, producing a current-schema entity.
+impl From<LegacyMatchEntity> for MatchEntity {
+    fn from(e: LegacyMatchEntity) -> Self {
+        Self {
+            partition_key: e.partition_key,
+            row_key: e.row_key,
+            match_type: e.match_type,
+            winner1_id: e.winner1_id,
+            winner2_id: e.winner2_id,
+            loser1_id: e.loser1_id,
+            loser2_id: e.loser2_id,
+            winner_score: e.winner_score,
+            loser_score: e.loser_score,
+            comment: e.comment.unwrap_or_default(),
+            recorded_by: e
+                .recorded_by
+                .unwrap_or_else(|| LEGACY_RECORDED_BY.to_string()),
+            played_at: e.played_at,
+            league_id: e.league_id,
+            forfeit: e.forfeit,
+            session_id: e.session_id,
+            deleted: e.deleted,
+        }
+    }
 }
 
 /// The constant partition key for all matches.
 pub const MATCH_PARTITION_KEY: &str = "match";
 
+/// Placeholder player ID shown in place of losers when anonymizing public views.
+///
+/// The underscore keeps it outside what `validate_player_id` (and `slugify`)
+/// allow, so no real player can ever have this ID.
+pub const ANONYMOUS_PLAYER_ID: &str = "_hidden";
+
+/// Display name for `ANONYMOUS_PLAYER_ID`.
+pub const ANONYMOUS_PLAYER_NAME: &str = "Anonymous";
+
 /// Generate a RowKey that sorts newest-first in Azure Table Storage.
 ///
 /// Format: `{reverse_timestamp}_{uuid}`
@@ -101,33 +240,92 @@ pub const MATCH_PARTITION_KEY: &str = "match";
 /// The reverse timestamp ensures that newer matches have lexicographically
 /// smaller RowKeys, so Azure's default ascending sort returns newest first.
 /// The UUID suffix guarantees uniqueness even for matches at the same millisecond.
-pub fn generate_match_row_key(played_at: &DateTime<Utc>) -> String {
+///
+/// Dates past `MAX_TIMESTAMP_MS` (typically garbage from a client) would make
+/// the reverse value negative, producing a key with a leading `-` that sorts
+/// out of order. Those are rejected rather than silently stored.
+pub fn generate_match_row_key(played_at: &DateTime<Utc>) -> Result<String, PlayedAtOutOfRange> {
     let ms = played_at.timestamp_millis();
-    let reverse = MAX_TIMESTAMP_MS - ms;
+    if ms > MAX_TIMESTAMP_MS {
+        return Err(PlayedAtOutOfRange(*played_at));
+    }
     let uuid = Uuid::new_v4();
+    Ok(format!("{}_{uuid}", row_key_prefix(played_at)))
+}
+
+/// Whether `key` has the shape `generate_match_row_key` produces: 20
+/// reverse-timestamp digits, `_`, then a UUID. Used to vet page cursors,
+/// which are RowKeys handed back by clients, before they reach a query.
+pub fn is_match_row_key(key: &str) -> bool {
+    let Some((reverse, uuid)) = key.split_once('_') else {
+        return false;
+    };
+    reverse.len() == 20
+        && reverse.bytes().all(|b| b.is_ascii_digit())
+        && Uuid::parse_str(uuid).is_ok()
+}
+
+/// The reverse-timestamp part of a match RowKey for `played_at`.
+///
+/// Every match played at that millisecond has a RowKey starting with this
+/// prefix, so it doubles as a range bound for date-filtered queries. Dates
+/// past the supported range clamp to all zeros (the newest possible key).
+pub fn row_key_prefix(played_at: &DateTime<Utc>) -> String {
+    let reverse = (MAX_TIMESTAMP_MS - played_at.timestamp_millis()).max(0);
     // Zero-pad the reverse timestamp to 20 digits so lexicographic sort works correctly.
-    format!("{reverse:020}_{uuid}")
+    format!("{reverse:020}")
+}
+
+/// The fields of a match about to be recorded — everything `MatchRecord`
+/// holds except what `MatchRecord::new` derives (ID, match type) or a caller
+/// sets afterwards (session ID).
+///
+/// `Default` is a blank singles match played at the Unix epoch, for filling
+/// in with struct update syntax.
+#[derive(Debug, Clone, Default)]
+pub struct NewMatch {
+    pub winner1_id: String,
+    pub winner2_id: Option<String>,
+    pub loser1_id: String,
+    pub loser2_id: Option<String>,
+    pub winner_score: Option<i32>,
+    pub loser_score: Option<i32>,
+    pub comment: String,
+    pub recorded_by: String,
+    pub played_at: DateTime<Utc>,
+    pub league_id: Option<String>,
+    pub forfeit: bool,
 }
 
 impl MatchRecord {
     /// Create a new MatchRecord, generating the reverse-timestamp ID.
     ///
-    /// The `league_id` parameter is optional — pass `None` for unaffiliated matches.
-    pub fn new(
-        winner1_id: String,
-        winner2_id: String,
-        loser1_id: String,
-        loser2_id: String,
-        winner_score: Option<i32>,
-        loser_score: Option<i32>,
-        comment: String,
-        recorded_by: String,
-        played_at: DateTime<Utc>,
-        league_id: Option<String>,
-    ) -> Self {
-        let id = generate_match_row_key(&played_at);
-        Self {
+    /// Leave `league_id` as `None` for unaffiliated matches, and both second
+    /// players as `None` to record singles (see `second_players`). Fails if
+    /// `played_at` is too far in the future to encode in the RowKey.
+    pub fn new(fields: NewMatch) -> Result<Self, PlayedAtOutOfRange> {
+        let NewMatch {
+            winner1_id,
+            winner2_id,
+            loser1_id,
+            loser2_id,
+            winner_score,
+            loser_score,
+            comment,
+            recorded_by,
+            played_at,
+            league_id,
+            forfeit,
+        } = fields;
+        let id = generate_match_row_key(&played_at)?;
+        let match_type = if winner2_id.is_some() {
+            MatchType::Doubles
+        } else {
+            MatchType::Singles
+        };
+        Ok(Self {
             id,
+            match_type,
             winner1_id,
             winner2_id,
             loser1_id,
@@ -138,8 +336,173 @@ impl MatchRecord {
             recorded_by,
             played_at,
             league_id,
+            forfeit,
+            session_id: None,
+        })
+    }
+}
+
+impl MatchRecord {
+    /// The `(winner, loser)` score if it should count toward score-based stats:
+    /// both scores recorded and the match wasn't a forfeit.
+    pub fn scores(&self) -> Option<(i32, i32)> {
+        if self.forfeit {
+            return None;
         }
+        self.winner_score.zip(self.loser_score)
     }
+
+    /// Check the record describes a possible game: non-negative scores, the
+    /// winner scoring more than the loser (when both scores are given), and
+    /// no player appearing in more than one slot.
+    pub fn validate(&self) -> Result<(), String> {
+        let scores = [
+            ("winner_score", self.winner_score),
+            ("loser_score", self.loser_score),
+        ];
+        for (side, score) in scores {
+            if let Some(score) = score
+                && score < 0
+            {
+                return Err(format!("{side} can't be negative (got {score})"));
+            }
+        }
+        if let (Some(w), Some(l)) = (self.winner_score, self.loser_score)
+            && w <= l
+        {
+            return Err(format!("winner_score must be higher than loser_score (got {w}-{l})"));
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        if let Some(id) = self.player_ids().find(|id| !seen.insert(*id)) {
+            return Err(format!("player '{id}' appears more than once"));
+        }
+        Ok(())
+    }
+
+    /// The winning player(s): one for singles, two for doubles.
+    pub fn winner_ids(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.winner1_id.as_str()).chain(self.winner2_id.as_deref())
+    }
+
+    /// The losing player(s): one for singles, two for doubles.
+    pub fn loser_ids(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.loser1_id.as_str()).chain(self.loser2_id.as_deref())
+    }
+
+    /// Everyone who played, winners first.
+    pub fn player_ids(&self) -> impl Iterator<Item = &str> {
+        self.winner_ids().chain(self.loser_ids())
+    }
+
+    /// The winning and losing pairs, or `None` for singles.
+    pub fn pairs(&self) -> Option<[(&str, &str); 2]> {
+        let (winner2, loser2) = self.winner2_id.as_deref().zip(self.loser2_id.as_deref())?;
+        Some([(&self.winner1_id, winner2), (&self.loser1_id, loser2)])
+    }
+
+    /// Whether the given player took part in the match.
+    pub fn has_player(&self, player_id: &str) -> bool {
+        self.player_ids().any(|id| id == player_id)
+    }
+
+    /// Whether the given player was on the winning side.
+    pub fn is_winner(&self, player_id: &str) -> bool {
+        self.winner_ids().any(|id| id == player_id)
+    }
+
+    /// Whether both matches involve the same players, in any team
+    /// arrangement.
+    pub fn same_lineup(&self, other: &MatchRecord) -> bool {
+        fn lineup(m: &MatchRecord) -> Vec<&str> {
+            let mut ids: Vec<&str> = m.player_ids().collect();
+            ids.sort_unstable();
+            ids
+        }
+        lineup(self) == lineup(other)
+    }
+
+    /// Replace the losing players with `ANONYMOUS_PLAYER_ID`, keeping winners
+    /// visible. Used for public views where we don't want to shame anyone.
+    /// `recorded_by` is blanked too: the loser is often who entered it.
+    pub fn anonymize_losers(&mut self) {
+        self.loser1_id = ANONYMOUS_PLAYER_ID.to_string();
+        if self.loser2_id.is_some() {
+            self.loser2_id = Some(ANONYMOUS_PLAYER_ID.to_string());
+        }
+        self.recorded_by.clear();
+    }
+}
+
+/// Validate the optional second-slot IDs of a create/update request.
+///
+/// Both present means doubles; both absent (or empty) means singles. Naming
+/// only one second player is ambiguous and rejected.
+pub fn second_players(
+    winner2_id: Option<String>,
+    loser2_id: Option<String>,
+) -> Result<(Option<String>, Option<String>), String> {
+    let present = |id: Option<String>| id.filter(|id| !id.is_empty());
+    match (present(winner2_id), present(loser2_id)) {
+        (Some(w), Some(l)) => Ok((Some(w), Some(l))),
+        (None, None) => Ok((None, None)),
+        _ => Err(
+            "name both second players (doubles) or neither (singles), not just one".to_string(),
+        ),
+    }
+}
+
+/// Parse a client-supplied `played_at` into UTC.
+///
+/// Values with an explicit offset (RFC 3339, e.g. `2024-03-01T18:30:00Z`) are
+/// taken as-is. With a `tz`, a naive datetime (`2024-03-01T18:30:00`) is read
+/// as wall-clock time in that zone. During a DST fall-back the earlier of the
+/// two instants wins; times skipped by a spring-forward are rejected.
+pub fn parse_played_at(raw: &str, tz: Option<Tz>) -> Result<DateTime<Utc>, String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+
+    let Some(tz) = tz else {
+        return Err(format!(
+            "played_at '{raw}' must be RFC 3339 with an offset (or pass ?tz= for local times)"
+        ));
+    };
+    let naive = NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M"))
+        .map_err(|_| format!("played_at '{raw}' is not a valid datetime"))?;
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok_or_else(|| format!("played_at '{raw}' does not exist in {tz} (DST gap)"))
+}
+
+/// Trim a match comment and enforce the configured length limits.
+///
+/// Length is measured in grapheme clusters so emoji-heavy trash talk isn't
+/// penalized for multi-byte encodings, while the byte ceiling still stops a
+/// few hundred "characters" of stacked combining marks from blowing up a row.
+pub fn sanitize_comment(
+    comment: &str,
+    max_graphemes: usize,
+    max_bytes: usize,
+) -> Result<String, String> {
+    let comment = comment.trim();
+
+    let graphemes = comment.graphemes(true).count();
+    if graphemes > max_graphemes {
+        return Err(format!(
+            "comment is too long ({graphemes} characters, max {max_graphemes})"
+        ));
+    }
+    if comment.len() > max_bytes {
+        return Err(format!(
+            "comment is too large ({} bytes, max {max_bytes})",
+            comment.len()
+        ));
+    }
+
+    Ok(comment.to_string())
 }
 
 /// Convert a domain MatchRecord into an Azure Table Storage entity.
@@ -148,16 +511,20 @@ impl From<MatchRecord> for MatchEntity {
         Self {
             partition_key: MATCH_PARTITION_KEY.to_string(),
             row_key: m.id,
+            match_type: m.match_type,
             winner1_id: m.winner1_id,
-            winner2_id: m.winner2_id,
+            winner2_id: m.winner2_id.unwrap_or_default(),
             loser1_id: m.loser1_id,
-            loser2_id: m.loser2_id,
+            loser2_id: m.loser2_id.unwrap_or_default(),
             winner_score: m.winner_score,
             loser_score: m.loser_score,
             comment: m.comment,
             recorded_by: m.recorded_by,
             played_at: m.played_at.to_rfc3339(),
             league_id: m.league_id,
+            forfeit: m.forfeit,
+            session_id: m.session_id,
+            deleted: false,
         }
     }
 }
@@ -165,24 +532,32 @@ impl From<MatchRecord> for MatchEntity {
 /// Convert an Azure Table Storage entity back into a domain MatchRecord.
 ///
 /// Uses `TryFrom` because the `played_at` date string could be malformed.
+/// Singles rows get their (empty) second slots back as `None`.
 impl TryFrom<MatchEntity> for MatchRecord {
     type Error = chrono::ParseError;
 
     fn try_from(entity: MatchEntity) -> Result<Self, Self::Error> {
         let played_at = DateTime::parse_from_rfc3339(&entity.played_at)?
             .with_timezone(&Utc);
+        let (winner2_id, loser2_id) = match entity.match_type {
+            MatchType::Singles => (None, None),
+            MatchType::Doubles => (Some(entity.winner2_id), Some(entity.loser2_id)),
+        };
         Ok(Self {
             id: entity.row_key,
+            match_type: entity.match_type,
             winner1_id: entity.winner1_id,
-            winner2_id: entity.winner2_id,
+            winner2_id,
             loser1_id: entity.loser1_id,
-            loser2_id: entity.loser2_id,
+            loser2_id,
             winner_score: entity.winner_score,
             loser_score: entity.loser_score,
             comment: entity.comment,
             recorded_by: entity.recorded_by,
             played_at,
             league_id: entity.league_id,
+            forfeit: entity.forfeit,
+            session_id: entity.session_id,
         })
     }
 }
@@ -192,18 +567,52 @@ impl TryFrom<MatchEntity> for MatchRecord {
 #[derive(Debug, Deserialize)]
 pub struct CreateMatchRequest {
     pub winner1_id: String,
-    pub winner2_id: String,
+    /// Omit both second players to record singles.
+    #[serde(default)]
+    pub winner2_id: Option<String>,
     pub loser1_id: String,
-    pub loser2_id: String,
+    #[serde(default)]
+    pub loser2_id: Option<String>,
     pub winner_score: Option<i32>,
     pub loser_score: Option<i32>,
     #[serde(default)]
     pub comment: String,
     /// Optional: when the match was played. Defaults to now if omitted.
-    pub played_at: Option<DateTime<Utc>>,
+    /// RFC 3339 with an offset, or — when the request carries `?tz=` — a
+    /// naive local datetime (see `parse_played_at`).
+    pub played_at: Option<String>,
     /// Optional: which league this match belongs to.
     #[serde(default)]
     pub league_id: Option<String>,
+    /// Optional: the losing team forfeited. Defaults to false.
+    #[serde(default)]
+    pub forfeit: bool,
+}
+
+/// One game in a `CreateSessionRequest`.
+#[derive(Debug, Deserialize)]
+pub struct SessionGame {
+    /// One player for singles, two for doubles — same count on both sides.
+    pub winners: Vec<String>,
+    pub losers: Vec<String>,
+    pub winner_score: Option<i32>,
+    pub loser_score: Option<i32>,
+    #[serde(default)]
+    pub comment: String,
+    #[serde(default)]
+    pub forfeit: bool,
+}
+
+/// Request body for recording several games from one sitting at once.
+#[derive(Debug, Deserialize)]
+pub struct CreateSessionRequest {
+    /// When the first game was played; later games follow in list order.
+    /// Same formats as `CreateMatchRequest::played_at`.
+    pub played_at_start: String,
+    /// League for every game in the session.
+    #[serde(default)]
+    pub league_id: Option<String>,
+    pub games: Vec<SessionGame>,
 }
 
 /// Request body for updating an existing match (full record replace pattern).
@@ -215,9 +624,12 @@ pub struct CreateMatchRequest {
 #[derive(Debug, Deserialize)]
 pub struct UpdateMatchRequest {
     pub winner1_id: String,
-    pub winner2_id: String,
+    /// Omit both second players for singles.
+    #[serde(default)]
+    pub winner2_id: Option<String>,
     pub loser1_id: String,
-    pub loser2_id: String,
+    #[serde(default)]
+    pub loser2_id: Option<String>,
     pub winner_score: Option<i32>,
     pub loser_score: Option<i32>,
     #[serde(default)]
@@ -225,4 +637,177 @@ pub struct UpdateMatchRequest {
     /// Which league this match belongs to (can be changed during edit).
     #[serde(default)]
     pub league_id: Option<String>,
+    #[serde(default)]
+    pub forfeit: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_row_keys_are_valid_cursors() {
+        let key = generate_match_row_key(&Utc::now()).unwrap();
+        assert!(is_match_row_key(&key), "{key}");
+    }
+
+    #[test]
+    fn absurd_future_dates_are_rejected() {
+        let year_20000 = Utc.with_ymd_and_hms(20000, 1, 1, 0, 0, 0).unwrap();
+        assert!(generate_match_row_key(&year_20000).is_err());
+        let fields = NewMatch {
+            played_at: year_20000,
+            ..Default::default()
+        };
+        assert!(MatchRecord::new(fields).is_err());
+    }
+
+    #[test]
+    fn the_last_supported_millisecond_still_gets_a_key() {
+        let last = DateTime::from_timestamp_millis(MAX_TIMESTAMP_MS).unwrap();
+        let key = generate_match_row_key(&last).unwrap();
+        assert!(key.starts_with("00000000000000000000_"), "{key}");
+    }
+
+    #[test]
+    fn newer_matches_sort_first() {
+        let older = generate_match_row_key(&Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+        let newer = generate_match_row_key(&Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap());
+        assert!(newer.unwrap() < older.unwrap());
+    }
+
+    #[test]
+    fn malformed_cursors_are_rejected() {
+        let uuid = Uuid::new_v4();
+        for cursor in [
+            String::new(),
+            "00000000000000000000".to_string(),
+            format!("1234_{uuid}"),
+            format!("0000000000000000000x_{uuid}"),
+            "00000000000000000000_not-a-uuid".to_string(),
+            format!("00000000000000000000_{uuid}' or PartitionKey ne '"),
+        ] {
+            assert!(!is_match_row_key(&cursor), "{cursor:?}");
+        }
+    }
+
+    // One grapheme, 18 bytes: a byte limit alone would allow only a handful.
+    const FAMILY: &str = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+
+    #[test]
+    fn emoji_count_as_one_character_each() {
+        let comment = FAMILY.repeat(10);
+        assert_eq!(sanitize_comment(&comment, 10, 4096).unwrap(), comment);
+        let err = sanitize_comment(&FAMILY.repeat(11), 10, 4096).unwrap_err();
+        assert!(err.contains("11 characters, max 10"), "{err}");
+    }
+
+    #[test]
+    fn emoji_heavy_comments_still_hit_the_byte_ceiling() {
+        let comment = FAMILY.repeat(10);
+        assert_eq!(comment.len(), 180);
+        assert!(sanitize_comment(&comment, 280, 180).is_ok());
+        let err = sanitize_comment(&comment, 280, 179).unwrap_err();
+        assert!(err.contains("180 bytes, max 179"), "{err}");
+    }
+
+    #[test]
+    fn comments_are_trimmed_before_counting() {
+        let comment = format!("  {}  ", FAMILY.repeat(3));
+        assert_eq!(sanitize_comment(&comment, 3, 54).unwrap(), FAMILY.repeat(3));
+    }
+
+    /// An entity as stored before `comment` and `recorded_by` existed.
+    fn launch_era_entity() -> serde_json::Value {
+        serde_json::json!({
+            "PartitionKey": MATCH_PARTITION_KEY,
+            "RowKey": generate_match_row_key(&Utc::now()).unwrap(),
+            "winner1_id": "alice",
+            "winner2_id": "bob",
+            "loser1_id": "carol",
+            "loser2_id": "dave",
+            "winner_score": 11,
+            "loser_score": 7,
+            "played_at": "2023-05-01T12:00:00+00:00",
+        })
+    }
+
+    #[test]
+    fn entities_missing_new_fields_are_backfilled_with_defaults() {
+        let legacy: LegacyMatchEntity = serde_json::from_value(launch_era_entity()).unwrap();
+        assert!(legacy.needs_backfill());
+
+        let migrated = MatchEntity::from(legacy);
+        assert_eq!(migrated.comment, "");
+        assert_eq!(migrated.recorded_by, LEGACY_RECORDED_BY);
+        assert_eq!(migrated.match_type, MatchType::Doubles);
+        assert!(!migrated.forfeit && !migrated.deleted);
+        assert_eq!((migrated.winner_score, migrated.loser_score), (Some(11), Some(7)));
+    }
+
+    #[test]
+    fn migrating_twice_is_a_no_op() {
+        let legacy: LegacyMatchEntity = serde_json::from_value(launch_era_entity()).unwrap();
+        let rewritten = serde_json::to_value(MatchEntity::from(legacy)).unwrap();
+        let reread: LegacyMatchEntity = serde_json::from_value(rewritten).unwrap();
+        assert!(!reread.needs_backfill());
+    }
+
+    #[test]
+    fn an_empty_comment_is_not_missing() {
+        let mut entity = launch_era_entity();
+        entity["comment"] = "".into();
+        entity["recorded_by"] = "user-1".into();
+        let legacy: LegacyMatchEntity = serde_json::from_value(entity).unwrap();
+        assert!(!legacy.needs_backfill());
+    }
+
+    fn scored(winner_score: Option<i32>, loser_score: Option<i32>) -> MatchRecord {
+        MatchRecord::new(NewMatch {
+            winner1_id: "alice".to_string(),
+            winner2_id: Some("bob".to_string()),
+            loser1_id: "carol".to_string(),
+            loser2_id: Some("dave".to_string()),
+            winner_score,
+            loser_score,
+            played_at: Utc::now(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn possible_results_are_valid() {
+        assert_eq!(scored(Some(11), Some(9)).validate(), Ok(()));
+        assert_eq!(scored(Some(11), Some(0)).validate(), Ok(()));
+        assert_eq!(scored(None, None).validate(), Ok(()));
+        assert_eq!(scored(Some(11), None).validate(), Ok(()));
+    }
+
+    #[test]
+    fn negative_scores_are_rejected() {
+        let err = scored(Some(-1), None).validate().unwrap_err();
+        assert!(err.contains("winner_score can't be negative"), "{err}");
+        let err = scored(Some(11), Some(-3)).validate().unwrap_err();
+        assert!(err.contains("loser_score can't be negative"), "{err}");
+    }
+
+    #[test]
+    fn the_winner_must_outscore_the_loser() {
+        let err = scored(Some(9), Some(11)).validate().unwrap_err();
+        assert!(err.contains("(got 9-11)"), "{err}");
+        assert!(scored(Some(11), Some(11)).validate().is_err(), "a tie is not a win");
+    }
+
+    #[test]
+    fn nobody_can_play_two_slots() {
+        let mut partner = scored(Some(11), Some(9));
+        partner.winner2_id = Some("alice".to_string());
+        let err = partner.validate().unwrap_err();
+        assert_eq!(err, "player 'alice' appears more than once");
+
+        let mut opponent = scored(Some(11), Some(9));
+        opponent.loser2_id = Some("bob".to_string());
+        assert!(opponent.validate().is_err());
+    }
 }
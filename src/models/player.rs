@@ -8,6 +8,7 @@
 // ("player") so we can list them all with a single partition query.
 
 use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
 
 /// A pickleball player in the system.
 ///
@@ -29,12 +30,213 @@ pub struct Player {
     /// Emoji used as the player's avatar (e.g., "🏓", "🔥", "💀").
     #[serde(default = "default_avatar")]
     pub avatar_emoji: String,
+
+    /// Accent color for match cards, as `#rrggbb`. `None` lets the UI pick.
+    #[serde(default)]
+    pub color: Option<String>,
+
+    /// False once the player has been archived: hidden from the player list
+    /// but still resolvable by ID, so their matches and stats stay intact.
+    #[serde(default = "default_active")]
+    pub active: bool,
 }
 
-fn default_avatar() -> String {
+impl Player {
+    /// Names of the editable fields whose values differ between `self`
+    /// (before) and `after`, in declaration order.
+    pub fn changed_fields(&self, after: &Player) -> Vec<&'static str> {
+        [
+            ("name", self.name != after.name),
+            ("nickname", self.nickname != after.nickname),
+            ("avatar_emoji", self.avatar_emoji != after.avatar_emoji),
+            ("color", self.color != after.color),
+        ]
+        .into_iter()
+        .filter_map(|(field, changed)| changed.then_some(field))
+        .collect()
+    }
+
+    /// Overwrite the editable fields that are `Some`, leaving the rest as is.
+    /// `color` is doubly optional so an update can clear it (`Some(None)`).
+    pub fn apply_update(
+        &mut self,
+        name: Option<String>,
+        nickname: Option<String>,
+        avatar_emoji: Option<String>,
+        color: Option<Option<String>>,
+    ) {
+        if let Some(n) = name {
+            self.name = n;
+        }
+        if let Some(n) = nickname {
+            self.nickname = n;
+        }
+        if let Some(a) = avatar_emoji {
+            self.avatar_emoji = a;
+        }
+        if let Some(c) = color {
+            self.color = c;
+        }
+    }
+}
+
+/// The avatar given to players who don't pick one.
+pub fn default_avatar() -> String {
     "🏓".to_string()
 }
 
+/// Byte ceiling for an avatar. The longest real emoji (ZWJ families, tag
+/// flags) are around 30 bytes; this stops one grapheme padded with hundreds
+/// of combining marks.
+const MAX_AVATAR_BYTES: usize = 64;
+
+/// Check that an avatar is a single emoji, returning the value to store.
+///
+/// Blank falls back to `default_avatar`. Otherwise the value (trimmed) must be
+/// exactly one grapheme cluster, so skin tones, ZWJ sequences and flags are
+/// fine, containing at least one character from the common emoji blocks.
+/// That range check is deliberately rough: it keeps out text like "ab" or
+/// "x", not every oddity Unicode allows.
+pub fn validate_avatar_emoji(avatar: &str) -> Result<String, String> {
+    let avatar = avatar.trim();
+    if avatar.is_empty() {
+        return Ok(default_avatar());
+    }
+    if avatar.graphemes(true).count() != 1 || avatar.len() > MAX_AVATAR_BYTES {
+        return Err("avatar_emoji must be a single emoji".to_string());
+    }
+    if !avatar.chars().any(is_emoji_char) {
+        return Err(format!("avatar_emoji {avatar:?} is not an emoji"));
+    }
+    Ok(avatar.to_string())
+}
+
+/// Whether `c` falls in one of the Unicode blocks emoji come from.
+fn is_emoji_char(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF // Pictographs, emoticons, transport, flags, …
+            | 0x2600..=0x27BF // Miscellaneous symbols and dingbats
+            | 0x2300..=0x23FF // Miscellaneous technical (⌚, ⏰)
+            | 0x2B00..=0x2BFF // Arrows and shapes (⭐, ⬛)
+            | 0x2190..=0x21FF // Arrows (↩)
+            | 0x20E3 // Combining keycap (1️⃣)
+            | 0x3030 | 0x303D | 0x3297 | 0x3299
+            | 0x00A9 | 0x00AE | 0x203C | 0x2049 | 0x2122 | 0x2139
+    )
+}
+
+/// Check that a color is a `#rrggbb` hex code, returning the value to store.
+///
+/// Blank means no color. Letters are lowercased so equal colors compare equal.
+pub fn validate_color(color: &str) -> Result<Option<String>, String> {
+    let color = color.trim();
+    if color.is_empty() {
+        return Ok(None);
+    }
+    match color.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Ok(Some(color.to_ascii_lowercase()))
+        }
+        _ => Err(format!("color {color:?} must be a hex code like #1e90ff")),
+    }
+}
+
+/// Players are active unless archived, including those stored before
+/// archiving existed.
+pub fn default_active() -> bool {
+    true
+}
+
+/// Maximum length of a player ID.
+pub const MAX_PLAYER_ID_LEN: usize = 50;
+
+/// Check that a player ID is a URL- and RowKey-safe slug.
+///
+/// IDs become Azure RowKeys and URL path segments, so we only allow lowercase
+/// ASCII letters, digits and hyphens, 1–50 characters. Characters Azure
+/// explicitly forbids in keys (`/`, `\`, `#`, `?`, control characters) get a
+/// specific message since they're the most confusing failures.
+pub fn validate_player_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || id.len() > MAX_PLAYER_ID_LEN {
+        return Err(format!(
+            "player id must be 1–{MAX_PLAYER_ID_LEN} characters"
+        ));
+    }
+
+    if let Some(c) = id
+        .chars()
+        .find(|c| matches!(c, '/' | '\\' | '#' | '?') || c.is_control())
+    {
+        return Err(format!(
+            "player id cannot contain {c:?} (not allowed in Azure Table keys)"
+        ));
+    }
+
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err(
+            "player id may only contain lowercase letters, digits and hyphens".to_string(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Derive a valid player ID from an email address's local part.
+///
+/// `Jane.Doe+pong@company.com` becomes `jane-doe-pong` (see `slugify`).
+pub fn player_id_from_email(email: &str) -> String {
+    slugify(email.split('@').next().unwrap_or_default())
+}
+
+/// Turn free text, like a display name, into a valid player ID.
+///
+/// Lowercased, anything outside `[a-z0-9]` turned into a hyphen, runs of
+/// hyphens collapsed and trimmed: `Jane O'Neil` becomes `jane-o-neil`. The
+/// result is short enough to take a `-N` suffix within `MAX_PLAYER_ID_LEN`.
+/// Falls back to `player` when nothing usable is left.
+pub fn slugify(text: &str) -> String {
+    let mut id = String::new();
+    for c in text.chars().map(|c| c.to_ascii_lowercase()) {
+        if c.is_ascii_lowercase() || c.is_ascii_digit() {
+            id.push(c);
+        } else if !id.is_empty() && !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+
+    // Leave room for a collision suffix like "-99".
+    id.truncate(MAX_PLAYER_ID_LEN - 4);
+    let id = id.trim_end_matches('-');
+    if id.is_empty() { "player".to_string() } else { id.to_string() }
+}
+
+/// Generate a fun nickname like "Turbo Pickle" for a player without one.
+///
+/// The choice is seeded by the player ID (FNV-1a hash) so the same player
+/// always gets the same nickname — we deliberately avoid `DefaultHasher`
+/// because its output isn't guaranteed to be stable across Rust releases.
+pub fn generate_nickname(player_id: &str, adjectives: &[String], nouns: &[String]) -> String {
+    if adjectives.is_empty() || nouns.is_empty() {
+        return String::new();
+    }
+
+    let hash = player_id
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+
+    // Use the low and high halves of the hash so adjective and noun vary
+    // independently.
+    let adjective = &adjectives[(hash % adjectives.len() as u64) as usize];
+    let noun = &nouns[((hash >> 32) % nouns.len() as u64) as usize];
+    format!("{adjective} {noun}")
+}
+
 /// The shape we store in Azure Table Storage.
 ///
 /// Azure Table Storage requires PartitionKey and RowKey as the first two fields.
@@ -63,6 +265,14 @@ pub struct PlayerEntity {
     /// Emoji avatar.
     #[serde(rename = "avatar_emoji", default = "default_avatar")]
     pub avatar_emoji: String,
+
+    /// Accent color, if one was picked.
+    #[serde(rename = "color", default)]
+    pub color: Option<String>,
+
+    /// False for archived players.
+    #[serde(rename = "active", default = "default_active")]
+    pub active: bool,
 }
 
 /// The constant partition key we use for all players.
@@ -77,6 +287,8 @@ impl From<Player> for PlayerEntity {
             name: player.name,
             nickname: player.nickname,
             avatar_emoji: player.avatar_emoji,
+            color: player.color,
+            active: player.active,
         }
     }
 }
@@ -89,6 +301,8 @@ impl From<PlayerEntity> for Player {
             name: entity.name,
             nickname: entity.nickname,
             avatar_emoji: entity.avatar_emoji,
+            color: entity.color,
+            active: entity.active,
         }
     }
 }
@@ -97,12 +311,16 @@ impl From<PlayerEntity> for Player {
 /// Separate from Player so we control exactly what the API accepts.
 #[derive(Debug, Deserialize)]
 pub struct CreatePlayerRequest {
+    /// Omit (or leave empty) to derive one from `name`.
+    #[serde(default)]
     pub id: String,
     pub name: String,
     #[serde(default)]
     pub nickname: String,
     #[serde(default = "default_avatar")]
     pub avatar_emoji: String,
+    #[serde(default)]
+    pub color: String,
 }
 
 /// Request body for updating an existing player (all fields optional).
@@ -111,4 +329,166 @@ pub struct UpdatePlayerRequest {
     pub name: Option<String>,
     pub nickname: Option<String>,
     pub avatar_emoji: Option<String>,
+    /// An empty string clears the color.
+    pub color: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn generated_nicknames_are_deterministic_per_id() {
+        let adjectives = words(&["Spicy", "Sneaky", "Mighty", "Wobbly"]);
+        let nouns = words(&["Dinker", "Pickle", "Smasher", "Lobster"]);
+        let first = generate_nickname("martin", &adjectives, &nouns);
+        assert_eq!(first, generate_nickname("martin", &adjectives, &nouns));
+
+        let (adjective, noun) = first.split_once(' ').unwrap();
+        assert!(adjectives.iter().any(|a| a == adjective));
+        assert!(nouns.iter().any(|n| n == noun));
+    }
+
+    #[test]
+    fn generated_nicknames_are_stable_across_releases() {
+        // FNV-1a is fixed, so this exact pairing must never change.
+        let nickname = generate_nickname("martin", &words(&["A", "B", "C"]), &words(&["X", "Y"]));
+        assert_eq!(nickname, "B Y");
+    }
+
+    #[test]
+    fn no_words_means_no_nickname() {
+        assert_eq!(generate_nickname("martin", &[], &words(&["Pickle"])), "");
+        assert_eq!(generate_nickname("martin", &words(&["Spicy"]), &[]), "");
+    }
+
+    #[test]
+    fn slug_ids_are_valid() {
+        for id in ["a", "martin", "jane-doe-2", "7", &"x".repeat(MAX_PLAYER_ID_LEN)] {
+            assert_eq!(validate_player_id(id), Ok(()), "{id:?}");
+        }
+    }
+
+    #[test]
+    fn empty_and_overlong_ids_are_rejected() {
+        assert!(validate_player_id("").is_err());
+        assert!(validate_player_id(&"x".repeat(MAX_PLAYER_ID_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn azure_forbidden_characters_get_a_specific_message() {
+        for id in ["a/b", "a\\b", "a#b", "a?b", "a\tb", "a\u{7f}b"] {
+            let err = validate_player_id(id).unwrap_err();
+            assert!(err.contains("Azure Table keys"), "{id:?}: {err}");
+        }
+    }
+
+    #[test]
+    fn other_non_slug_characters_are_rejected() {
+        for id in ["Martin", "jane doe", "jane_doe", "jane.doe", "josé"] {
+            let err = validate_player_id(id).unwrap_err();
+            assert!(err.contains("lowercase letters, digits and hyphens"), "{id:?}: {err}");
+        }
+    }
+
+    #[test]
+    fn changed_fields_lists_only_what_differs() {
+        let before = Player {
+            id: "martin".to_string(),
+            name: "Martin".to_string(),
+            nickname: String::new(),
+            avatar_emoji: default_avatar(),
+            color: None,
+            active: true,
+        };
+        let mut after = before.clone();
+        after.apply_update(
+            Some("Martin".to_string()),
+            Some("The Dinkmaster".to_string()),
+            None,
+            None,
+        );
+
+        assert_eq!(before.changed_fields(&after), ["nickname"]);
+        assert!(before.changed_fields(&before).is_empty());
+    }
+
+    #[test]
+    fn single_emoji_avatars_are_accepted() {
+        for avatar in ["🏓", " 🦊 ", "👍🏽", "👨‍👩‍👧", "🇳🇿", "⭐", "1️⃣"] {
+            assert_eq!(validate_avatar_emoji(avatar).as_deref(), Ok(avatar.trim()), "{avatar:?}");
+        }
+        assert_eq!(validate_avatar_emoji("  "), Ok(default_avatar()));
+    }
+
+    #[test]
+    fn several_characters_or_plain_text_are_rejected() {
+        for avatar in ["🏓🏓", "🏓x", "ab", &"🏓".repeat(50)] {
+            let err = validate_avatar_emoji(avatar).unwrap_err();
+            assert!(err.contains("single emoji"), "{avatar:?}: {err}");
+        }
+        for avatar in ["x", "7", "é"] {
+            let err = validate_avatar_emoji(avatar).unwrap_err();
+            assert!(err.contains("is not an emoji"), "{avatar:?}: {err}");
+        }
+    }
+
+    #[test]
+    fn slugify_makes_valid_ids_from_names() {
+        assert_eq!(slugify("Jane O'Neil"), "jane-o-neil");
+        assert_eq!(slugify("  --Martin__2--  "), "martin-2");
+        assert_eq!(slugify("José"), "jos");
+        assert_eq!(slugify("🏓"), "player");
+        assert_eq!(player_id_from_email("Jane.Doe+pong@company.com"), "jane-doe-pong");
+
+        // Long names leave room for a collision suffix.
+        let long = slugify(&"a".repeat(200));
+        assert_eq!(long.len(), MAX_PLAYER_ID_LEN - 4);
+        for id in [long.clone(), format!("{long}-99"), slugify("a/b#c?d\\e")] {
+            assert_eq!(validate_player_id(&id), Ok(()), "{id:?}");
+        }
+    }
+
+    #[test]
+    fn the_hidden_loser_placeholder_is_never_a_player_id() {
+        use crate::models::match_record::{ANONYMOUS_PLAYER_ID, LEGACY_RECORDED_BY};
+
+        assert!(validate_player_id(ANONYMOUS_PLAYER_ID).is_err());
+        assert_ne!(ANONYMOUS_PLAYER_ID, LEGACY_RECORDED_BY);
+        for name in [ANONYMOUS_PLAYER_ID, "Hidden", "_hidden_", "Anonymous"] {
+            assert_ne!(slugify(name), ANONYMOUS_PLAYER_ID, "{name:?}");
+        }
+    }
+
+    #[test]
+    fn colors_are_hex_codes() {
+        assert_eq!(validate_color(" #1E90ff "), Ok(Some("#1e90ff".to_string())));
+        assert_eq!(validate_color(""), Ok(None));
+        for color in ["1e90ff", "#1e90f", "#1e90ffaa", "#ggg000", "blue"] {
+            assert!(validate_color(color).is_err(), "{color:?}");
+        }
+    }
+
+    #[test]
+    fn an_update_can_clear_the_color() {
+        let mut player = Player {
+            id: "martin".to_string(),
+            name: "Martin".to_string(),
+            nickname: String::new(),
+            avatar_emoji: default_avatar(),
+            color: Some("#1e90ff".to_string()),
+            active: true,
+        };
+        let before = player.clone();
+
+        player.apply_update(None, None, None, None);
+        assert_eq!(player.color.as_deref(), Some("#1e90ff"));
+        player.apply_update(None, None, None, Some(None));
+        assert_eq!(player.color, None);
+        assert_eq!(before.changed_fields(&player), ["color"]);
+    }
 }
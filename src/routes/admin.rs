@@ -0,0 +1,128 @@
+// routes/admin.rs — Admin-only maintenance endpoints.
+//
+// Endpoints:
+//   POST /api/admin/migrate — Backfill properties missing from older matches
+//   GET  /api/debug/suspicious-matches — Matches too close together to be real
+//   POST /api/admin/display-url — Mint a signed URL for the office TV
+//
+// Like the user management endpoints, authorization is checked inside each
+// handler against the session's role.
+
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::display_token::sign_display_token;
+use crate::auth::oidc::SessionClaims;
+use crate::config::AppConfig;
+use crate::stats::suspicious::{self, SuspiciousPair};
+use crate::storage::client::StorageClient;
+use crate::storage::matches::{self, MatchStorageError, MigrationReport};
+
+/// POST /api/admin/migrate — Run the match backfill migration.
+///
+/// Safe to call repeatedly: matches that already have every property are
+/// left untouched, so a re-run reports zero updates.
+pub async fn migrate(
+    State(storage): State<StorageClient>,
+    Extension(claims): Extension<SessionClaims>,
+) -> Result<Json<MigrationReport>, MatchStorageError> {
+    if !claims.is_admin {
+        return Err(MatchStorageError::Forbidden(
+            "Only admins can run migrations".to_string(),
+        ));
+    }
+
+    let report = matches::migrate_matches(&storage).await?;
+    tracing::info!(
+        "Match migration by {}: scanned {}, updated {}",
+        claims.email,
+        report.scanned,
+        report.updated
+    );
+    Ok(Json(report))
+}
+
+/// GET /api/debug/suspicious-matches — Likely duplicate or mis-imported matches.
+///
+/// Flags pairs of matches where a player appears in both less than
+/// `SUSPICIOUS_MATCH_INTERVAL_SECS` apart. Read-only; cleaning up is left to
+/// the regular edit/delete endpoints.
+pub async fn get_suspicious_matches(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    Extension(claims): Extension<SessionClaims>,
+) -> Result<Json<Vec<SuspiciousPair>>, MatchStorageError> {
+    if !claims.is_admin {
+        return Err(MatchStorageError::Forbidden(
+            "Only admins can view suspicious matches".to_string(),
+        ));
+    }
+
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let min_interval = Duration::seconds(config.suspicious_match_interval_secs);
+    Ok(Json(suspicious::find_suspicious_matches(
+        &all_matches,
+        min_interval,
+    )))
+}
+
+/// Default and maximum lifetime of a display token, in days.
+const DISPLAY_TOKEN_DEFAULT_DAYS: u32 = 30;
+const DISPLAY_TOKEN_MAX_DAYS: u32 = 365;
+
+/// Query parameters for minting a display URL.
+#[derive(Deserialize)]
+pub struct DisplayUrlQuery {
+    /// Days until the token expires (1–365, default 30).
+    pub ttl_days: Option<u32>,
+}
+
+/// A freshly minted display token and ready-to-use URLs.
+#[derive(Debug, Serialize)]
+pub struct DisplayUrl {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    pub leaderboard_url: String,
+    pub dashboard_url: String,
+}
+
+/// POST /api/admin/display-url — Signed, read-only URLs for an unattended screen.
+///
+/// The token grants `GET /api/display/leaderboard` and
+/// `GET /api/display/dashboard` until it expires (see
+/// auth/display_token.rs). Other query parameters such as `league_id` can be
+/// appended to the returned URLs.
+pub async fn create_display_url(
+    Extension(config): Extension<AppConfig>,
+    Extension(claims): Extension<SessionClaims>,
+    Query(query): Query<DisplayUrlQuery>,
+) -> Result<Json<DisplayUrl>, MatchStorageError> {
+    if !claims.is_admin {
+        return Err(MatchStorageError::Forbidden(
+            "Only admins can create display URLs".to_string(),
+        ));
+    }
+
+    let ttl_days = query.ttl_days.unwrap_or(DISPLAY_TOKEN_DEFAULT_DAYS);
+    if !(1..=DISPLAY_TOKEN_MAX_DAYS).contains(&ttl_days) {
+        return Err(MatchStorageError::Invalid(format!(
+            "ttl_days must be between 1 and {DISPLAY_TOKEN_MAX_DAYS}"
+        )));
+    }
+
+    let expires_at = Utc::now() + Duration::days(i64::from(ttl_days));
+    let token = sign_display_token(&config, expires_at.timestamp());
+    let base = config.app_url.trim_end_matches('/');
+    tracing::info!("Display URL created by {}, expires {expires_at}", claims.email);
+
+    Ok(Json(DisplayUrl {
+        leaderboard_url: format!("{base}/api/display/leaderboard?token={token}"),
+        dashboard_url: format!("{base}/api/display/dashboard?token={token}"),
+        token,
+        expires_at,
+    }))
+}
@@ -4,6 +4,7 @@
 //   GET  /api/auth/login    → Redirect to Microsoft login
 //   GET  /api/auth/callback → Handle the redirect back from Microsoft
 //   GET  /api/auth/me       → Return current user info (from session cookie)
+//   POST /api/auth/refresh  → Extend a still-valid session
 //   POST /api/auth/logout   → Clear the session cookie
 //
 // The callback handler is the most complex — after validating the Microsoft
@@ -16,43 +17,100 @@
 use axum::{
     Extension, Json,
     extract::Query,
-    http::{StatusCode, header},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
     response::{IntoResponse, Redirect, Response},
 };
 use chrono::Utc;
 use serde::Deserialize;
 
-use crate::auth::middleware::SESSION_COOKIE_NAME;
+use crate::auth::middleware::{SESSION_COOKIE_NAME, cookie_value};
 use crate::auth::oidc::{
-    SessionClaims, authorize_url, create_session_token, decode_id_token_claims, exchange_code,
+    AuthError, SessionClaims, authorize_url, base_url_for_host, create_session_token,
+    exchange_code, new_session_claims, sign_session_claims, validate_ms_id_token,
+    validate_session_token,
 };
 use crate::config::AppConfig;
+use crate::metrics::Metrics;
 use crate::models::user::User;
 use crate::storage::client::StorageClient;
 use crate::storage::users;
 
+/// Cookie carrying the OIDC `state` nonce between login and callback.
+const STATE_COOKIE_NAME: &str = "oidc_state";
+
+/// How long (seconds) a login attempt may take before its state expires.
+const STATE_COOKIE_MAX_AGE: u32 = 600;
+
+/// Where a callback for an expired or already-used login lands: the SPA,
+/// with an error code it can turn into a "please sign in again" message.
+const STALE_LOGIN_REDIRECT: &str = "/?auth_error=code_expired";
+
 /// GET /api/auth/login — Redirect to Microsoft's login page.
+///
+/// Generates a random `state` nonce, remembers it in a short-lived HttpOnly
+/// cookie scoped to the auth routes, and sends it along to Microsoft. The
+/// callback only accepts a response carrying the same value, so an attacker
+/// can't complete a login flow they started in someone else's browser.
+///
+/// The redirect URI follows the request's `Host` when it's one of the
+/// configured app URLs, so each hostname returns to itself after login.
+///
+/// If the provider's discovery document can't be fetched, the user lands
+/// back on the SPA with `?auth_error=discovery`.
 pub async fn login(
     Extension(config): Extension<AppConfig>,
-) -> Redirect {
-    let url = authorize_url(&config);
-    Redirect::temporary(&url)
+    headers: HeaderMap,
+) -> Response {
+    let state = uuid::Uuid::new_v4().simple().to_string();
+    let base_url = base_url_for_host(&config, request_host(&headers));
+    let url = match authorize_url(&config, base_url, &state).await {
+        Ok(url) => url,
+        Err(e) => {
+            tracing::error!("OIDC discovery failed: {e}");
+            return Redirect::to("/?auth_error=discovery").into_response();
+        }
+    };
+    let cookie_value = format!(
+        "{STATE_COOKIE_NAME}={state}; Path=/api/auth; HttpOnly; SameSite=Lax; \
+         Max-Age={STATE_COOKIE_MAX_AGE}"
+    );
+
+    (
+        [(header::SET_COOKIE, cookie_value)],
+        Redirect::temporary(&url),
+    )
+        .into_response()
 }
 
 /// Query parameters on the callback URL from Microsoft.
 #[derive(Deserialize)]
 pub struct CallbackQuery {
     pub code: Option<String>,
+    /// The nonce we sent in `login`, echoed back by Microsoft.
+    pub state: Option<String>,
     pub error: Option<String>,
     pub error_description: Option<String>,
 }
 
+/// The request's `Host` header, if present and valid UTF-8.
+fn request_host(headers: &HeaderMap) -> Option<&str> {
+    headers.get(header::HOST).and_then(|v| v.to_str().ok())
+}
+
+/// `Set-Cookie` value that deletes the state cookie.
+fn clear_state_cookie() -> String {
+    format!("{STATE_COOKIE_NAME}=; Path=/api/auth; HttpOnly; SameSite=Lax; Max-Age=0")
+}
+
 /// GET /api/auth/callback — Handle the redirect back from Microsoft.
 ///
 /// This is the heart of the login flow. After Microsoft authenticates the user
 /// and redirects back here with an authorization code, we:
+///   0. Check the `state` parameter matches the state cookie set by `login`
+///      (400 otherwise — possible CSRF), and clear that cookie. A missing
+///      cookie means a stale callback and redirects back to sign in.
 ///   1. Exchange the code for tokens (ID token + access token).
-///   2. Decode the ID token to get the user's identity (OID, name, email).
+///   2. Validate the ID token and read the user's identity (OID, name, email).
 ///   3. Upsert the user record in Azure Table Storage.
 ///   4. If this is the first user ever, assign them the "admin" role.
 ///   5. Create a signed session JWT cookie with role and player_id embedded.
@@ -64,8 +122,47 @@ pub struct CallbackQuery {
 pub async fn callback(
     Extension(config): Extension<AppConfig>,
     Extension(storage): Extension<StorageClient>,
+    Extension(metrics): Extension<Metrics>,
+    headers: HeaderMap,
     Query(query): Query<CallbackQuery>,
 ) -> Response {
+    // The state cookie is single-use: clear it on every response below.
+    let mut response = handle_callback(config, storage, &metrics, &headers, query).await;
+    if let Ok(value) = HeaderValue::from_str(&clear_state_cookie()) {
+        response.headers_mut().append(header::SET_COOKIE, value);
+    }
+    response
+}
+
+async fn handle_callback(
+    config: AppConfig,
+    storage: StorageClient,
+    metrics: &Metrics,
+    headers: &HeaderMap,
+    query: CallbackQuery,
+) -> Response {
+    // Reject responses to a login this browser didn't start.
+    let expected_state = cookie_value(headers, STATE_COOKIE_NAME);
+    if expected_state.is_none() && query.code.is_some() {
+        // The single-use state cookie is gone: almost always a bookmarked or
+        // reloaded callback URL (or a login left open past its expiry). The
+        // code is spent either way, so just ask them to sign in again.
+        tracing::info!("OIDC callback without a state cookie; treating as stale");
+        return Redirect::to(STALE_LOGIN_REDIRECT).into_response();
+    }
+    let state_matches = matches!(
+        (&expected_state, &query.state),
+        (Some(expected), Some(got)) if !expected.is_empty() && expected == got
+    );
+    if !state_matches {
+        tracing::warn!("Rejecting OIDC callback: missing or mismatched state");
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "Invalid login state"})),
+        )
+            .into_response();
+    }
+
     // Check for errors from Microsoft.
     if let Some(error) = &query.error {
         let desc = query.error_description.as_deref().unwrap_or("Unknown error");
@@ -92,19 +189,17 @@ pub async fn callback(
     };
 
     // Exchange the authorization code for tokens.
-    let token_response = match exchange_code(&config, code).await {
+    // Failures are logged in full (status + body) but the user just lands
+    // back on the SPA with a short error code, not a raw JSON 500.
+    // Microsoft sent the user back to the same host `login` chose, so the
+    // Host header picks the same redirect URI again.
+    let base_url = base_url_for_host(&config, request_host(headers));
+    let token_response = match exchange_code(&config, base_url, code).await {
         Ok(t) => t,
-        Err(e) => {
-            tracing::error!("Token exchange failed: {e}");
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": "Token exchange failed"})),
-            )
-                .into_response();
-        }
+        Err(e) => return exchange_failure_redirect(e).into_response(),
     };
 
-    // Decode the ID token to get user info.
+    // Validate the ID token and get user info.
     let id_token = match &token_response.id_token {
         Some(t) => t,
         None => {
@@ -116,26 +211,31 @@ pub async fn callback(
         }
     };
 
-    let ms_claims = match decode_id_token_claims(id_token) {
-        Some(c) => c,
-        None => {
+    let ms_claims = match validate_ms_id_token(&config, id_token).await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!("Rejecting ID token: {e}");
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": "Failed to decode ID token"})),
+                Json(serde_json::json!({"error": "Failed to validate ID token"})),
             )
                 .into_response();
         }
     };
 
     // Extract user identity from Microsoft claims.
+    let email = ms_claims.email_address(config.require_email_claim);
     let user_id = ms_claims
         .oid
         .or(ms_claims.sub)
         .unwrap_or_else(|| "unknown".to_string());
     let name = ms_claims.name.unwrap_or_else(|| "Unknown User".to_string());
-    let email = ms_claims
-        .preferred_username
-        .unwrap_or_else(|| "unknown@unknown.com".to_string());
+    // Inventing an address pollutes sessions and any email-based linking, so
+    // deployments can opt into rejecting the login instead.
+    let Some(email) = email else {
+        tracing::warn!("Rejecting login for '{user_id}': ID token has no email claim");
+        return Redirect::to("/?auth_error=missing_email").into_response();
+    };
 
     // ── Upsert user in the users table ───────────────────────────────────
     //
@@ -213,24 +313,130 @@ pub async fn callback(
             }
         };
 
-    // Set the session cookie and redirect to the home page.
-    // HttpOnly: prevents JavaScript from reading the cookie (XSS protection).
-    // SameSite=Lax: cookie sent on top-level navigations (needed for OIDC redirect).
-    // Secure: only sent over HTTPS (omitted in development).
-    let cookie_value = format!(
-        "{SESSION_COOKIE_NAME}={session_token}; Path=/; HttpOnly; SameSite=Lax; Max-Age=86400"
-    );
+    metrics.login();
 
+    // Set the session cookie and redirect to the home page.
     (
         StatusCode::SEE_OTHER,
         [
-            (header::SET_COOKIE, cookie_value),
+            (header::SET_COOKIE, build_session_cookie(&config, &session_token)),
             (header::LOCATION, "/".to_string()),
         ],
     )
         .into_response()
 }
 
+/// Where to send the user when the code exchange fails, logging why.
+fn exchange_failure_redirect(e: AuthError) -> Redirect {
+    match e {
+        AuthError::StaleCode(_) => {
+            // A revisited callback URL, not a server problem: send them back
+            // to sign in again.
+            tracing::info!("{e}");
+            Redirect::to(STALE_LOGIN_REDIRECT)
+        }
+        e => {
+            tracing::error!("{e}");
+            Redirect::to("/?auth_error=token_exchange")
+        }
+    }
+}
+
+/// `Set-Cookie` value that stores a session token.
+///
+/// HttpOnly: prevents JavaScript from reading the cookie (XSS protection).
+/// SameSite=Lax: cookie sent on top-level navigations (needed for OIDC redirect).
+/// Secure: only sent over HTTPS, per `config.cookie_secure` (off for local
+/// development over plain http).
+/// Max-Age: the same `SESSION_TTL_HOURS` the token itself expires after.
+fn build_session_cookie(config: &AppConfig, token: &str) -> String {
+    session_cookie_with_max_age(config, token, config.session_ttl_hours * 3600)
+}
+
+/// `Set-Cookie` value that deletes the session cookie.
+fn clear_session_cookie(config: &AppConfig) -> String {
+    session_cookie_with_max_age(config, "", 0)
+}
+
+fn session_cookie_with_max_age(config: &AppConfig, value: &str, max_age: i64) -> String {
+    let secure = if config.cookie_secure { "; Secure" } else { "" };
+    format!(
+        "{SESSION_COOKIE_NAME}={value}; Path=/; HttpOnly; SameSite=Lax; Max-Age={max_age}{secure}"
+    )
+}
+
+/// POST /api/auth/refresh — Extend the current session without signing in again.
+///
+/// If the session cookie is still valid, a new token with a fresh expiry
+/// replaces it, so an active SPA can keep its user signed in indefinitely
+/// by refreshing now and then. An expired (or missing) session is a 401:
+/// idle users still have to go through Microsoft again.
+///
+/// Role and linked player are re-read from the users table, so changes to
+/// them take effect at the next refresh rather than only at the next login.
+/// Returns the same body as `/api/auth/me`.
+pub async fn refresh(
+    Extension(config): Extension<AppConfig>,
+    Extension(storage): Extension<StorageClient>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(mut claims) = cookie_value(&headers, SESSION_COOKIE_NAME)
+        .and_then(|token| validate_session_token(&config, &token))
+    else {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({"error": "Invalid or expired session"})),
+        )
+            .into_response();
+    };
+
+    match users::get_user(&storage, &claims.sub).await {
+        Ok(user) => {
+            claims.role = user.role;
+            claims.player_id = user.player_id;
+        }
+        // Keep what the old session said, as the login callback does when
+        // the user record can't be written.
+        Err(e) => tracing::warn!("Failed to load user on session refresh: {e}"),
+    }
+
+    let (session_token, claims) = match renew_session(&config, &claims) {
+        Ok(renewed) => renewed,
+        Err(e) => {
+            tracing::error!("Failed to create session token: {e}");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Failed to create session"})),
+            )
+                .into_response();
+        }
+    };
+
+    (
+        [(header::SET_COOKIE, build_session_cookie(&config, &session_token))],
+        me(Some(Extension(claims))).await,
+    )
+        .into_response()
+}
+
+/// Sign a fresh session token (full `SESSION_TTL_HOURS` from now) for the
+/// user in `claims`, returning it with its claims.
+fn renew_session(
+    config: &AppConfig,
+    claims: &SessionClaims,
+) -> Result<(String, SessionClaims), jsonwebtoken::errors::Error> {
+    let claims = new_session_claims(
+        config,
+        &claims.sub,
+        &claims.name,
+        &claims.email,
+        &claims.role,
+        claims.player_id.clone(),
+    );
+    let token = sign_session_claims(config, &claims)?;
+    Ok((token, claims))
+}
+
 /// GET /api/auth/me — Return the current user's info.
 ///
 /// This endpoint is called by the frontend to check if the user is logged in
@@ -246,6 +452,7 @@ pub async fn me(
             "name": claims.name,
             "email": claims.email,
             "role": claims.role,
+            "is_admin": claims.is_admin,
             "player_id": claims.player_id,
         }))
         .into_response(),
@@ -257,15 +464,106 @@ pub async fn me(
 }
 
 /// POST /api/auth/logout — Clear the session cookie.
-pub async fn logout() -> Response {
-    // Set the cookie with an expired Max-Age to delete it.
-    let cookie_value =
-        format!("{SESSION_COOKIE_NAME}=; Path=/; HttpOnly; SameSite=Lax; Max-Age=0");
-
+pub async fn logout(Extension(config): Extension<AppConfig>) -> Response {
+    // Set the cookie with an expired Max-Age to delete it. The attributes
+    // (including Secure) must match the ones it was set with.
     (
         StatusCode::OK,
-        [(header::SET_COOKIE, cookie_value)],
+        [(header::SET_COOKIE, clear_session_cookie(&config))],
         Json(serde_json::json!({"message": "Logged out"})),
     )
         .into_response()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_codes_redirect_back_to_sign_in() {
+        let location = |e| {
+            let response = exchange_failure_redirect(e).into_response();
+            response.headers()[header::LOCATION].to_str().unwrap().to_string()
+        };
+
+        assert_eq!(
+            location(AuthError::StaleCode("AADSTS70008: expired".to_string())),
+            "/?auth_error=code_expired"
+        );
+        let other = AuthError::TokenExchange {
+            status: 502,
+            body: "<html>Bad Gateway</html>".to_string(),
+        };
+        assert_eq!(location(other), "/?auth_error=token_exchange");
+    }
+
+    /// A session token for alice that expires `expires_in_secs` from now.
+    fn token_expiring_in(config: &AppConfig, expires_in_secs: i64) -> String {
+        let now = Utc::now().timestamp();
+        let claims = SessionClaims {
+            sub: "oid-1".to_string(),
+            name: "Alice".to_string(),
+            email: "alice@example.com".to_string(),
+            role: "admin".to_string(),
+            is_admin: true,
+            player_id: Some("alice".to_string()),
+            exp: now + expires_in_secs,
+            iat: now - 86_000,
+        };
+        let key = jsonwebtoken::EncodingKey::from_secret(config.session_secret.as_bytes());
+        jsonwebtoken::encode(&jsonwebtoken::Header::default(), &claims, &key).unwrap()
+    }
+
+    #[test]
+    fn near_expiry_sessions_are_extended() {
+        let config = AppConfig::for_tests();
+        let old = validate_session_token(&config, &token_expiring_in(&config, 30)).unwrap();
+
+        let (token, renewed) = renew_session(&config, &old).unwrap();
+
+        let full_ttl = Utc::now().timestamp() + config.session_ttl_hours * 3600;
+        assert!((renewed.exp - full_ttl).abs() <= 5, "{} vs {full_ttl}", renewed.exp);
+        assert!(renewed.exp > old.exp);
+        assert_eq!((renewed.sub.as_str(), renewed.role.as_str()), ("oid-1", "admin"));
+        assert_eq!(renewed.player_id.as_deref(), Some("alice"));
+        let signed = validate_session_token(&config, &token).unwrap();
+        assert_eq!((signed.exp, signed.is_admin), (renewed.exp, renewed.is_admin));
+    }
+
+    #[tokio::test]
+    async fn expired_sessions_cannot_be_refreshed() {
+        let config = AppConfig::for_tests();
+        let mut headers = HeaderMap::new();
+        let cookie = format!("session={}", token_expiring_in(&config, -3600));
+        headers.insert(header::COOKIE, HeaderValue::from_str(&cookie).unwrap());
+        // Rejected before storage is touched.
+        let storage = StorageClient::new(&config);
+
+        let response = refresh(Extension(config), Extension(storage), headers).await;
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn session_cookies_are_secure_on_https_only() {
+        let cookie_for = |app_url: &str, cookie_secure_var| {
+            let mut config = AppConfig::for_tests();
+            config.app_url = app_url.to_string();
+            config.cookie_secure = crate::config::cookie_secure(app_url, cookie_secure_var);
+            (build_session_cookie(&config, "tok"), clear_session_cookie(&config))
+        };
+
+        let (set, clear) = cookie_for("https://scores.example.com", None);
+        assert!(set.starts_with("session=tok; Path=/; HttpOnly; SameSite=Lax;"), "{set}");
+        assert!(set.ends_with("; Secure"), "{set}");
+        assert!(clear.ends_with("Max-Age=0; Secure"), "{clear}");
+
+        let (set, clear) = cookie_for("http://localhost:3000", None);
+        assert!(!set.contains("Secure"), "{set}");
+        assert!(!clear.contains("Secure"), "{clear}");
+
+        // An explicit `COOKIE_SECURE` wins either way.
+        assert!(cookie_for("http://localhost:3000", Some(true)).0.ends_with("; Secure"));
+        assert!(!cookie_for("https://scores.example.com", Some(false)).0.contains("Secure"));
+    }
+}
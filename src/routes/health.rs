@@ -0,0 +1,57 @@
+// routes/health.rs — Liveness/readiness probe for load balancers and uptime
+// monitors.
+//
+//   GET /api/health → 200 {"status":"ok","storage":"reachable"}
+//                     503 {"status":"degraded","storage":"unreachable"}
+//
+// Public and uncached: it answers "can this instance serve requests right
+// now", so every call makes one tiny storage query (at most one row).
+
+use axum::{Json, extract::State, http::StatusCode};
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::storage::client::StorageClient;
+
+/// How long the storage check may take before the probe reports it
+/// unreachable — well under typical probe timeouts.
+const STORAGE_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Serialize)]
+pub struct HealthResponse {
+    pub status: &'static str,
+    pub storage: &'static str,
+}
+
+/// GET /api/health — Report whether Azure Table Storage is reachable.
+pub async fn health(State(storage): State<StorageClient>) -> (StatusCode, Json<HealthResponse>) {
+    let reachable = match tokio::time::timeout(STORAGE_CHECK_TIMEOUT, storage.ping()).await {
+        Ok(Ok(())) => true,
+        Ok(Err(e)) => {
+            tracing::warn!("Health check: storage query failed: {e}");
+            false
+        }
+        Err(_) => {
+            tracing::warn!("Health check: storage query timed out");
+            false
+        }
+    };
+
+    if reachable {
+        (
+            StatusCode::OK,
+            Json(HealthResponse {
+                status: "ok",
+                storage: "reachable",
+            }),
+        )
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(HealthResponse {
+                status: "degraded",
+                storage: "unreachable",
+            }),
+        )
+    }
+}
@@ -1,7 +1,9 @@
 // routes/leaderboard.rs — Leaderboard and stats API handlers.
 //
 // Stats are computed on-the-fly from match data. With <10 players and a few
-// hundred matches at most, this is fast enough without caching.
+// hundred matches at most, that's fast enough for most endpoints. The two hot
+// or heavy ones — the leaderboard (every dashboard load) and the full player
+// export — cache their serialized bodies until the next write (see cache.rs).
 //
 // The leaderboard ranks players by win rate (with a minimum number of games
 // to avoid someone being #1 with 1 win and 0 losses).
@@ -11,23 +13,49 @@
 //   only matches belonging to that league are used for stat computation. When
 //   omitted, all matches are included (all-time view). This filtering happens
 //   in-memory after fetching all matches — fine for our small dataset.
+//
+// Empty data:
+//   Every endpoint must cope with no players and/or no matches (a fresh
+//   deployment, or a league nobody has played in yet): lists come back empty,
+//   counts and rates as zero, and "best/worst" style picks as `null`. Only a
+//   missing *path* entity (unknown player or league) is an error. Guard every
+//   division and use `Option`-returning `max_by`/`first` instead of indexing.
 
 use axum::{
-    Json,
+    Extension, Json,
+    body::Bytes,
     extract::{Path, Query, State},
-    response::IntoResponse,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
 };
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use crate::auth::oidc::SessionClaims;
+use crate::cache::ResponseCache;
+use crate::config::AppConfig;
+use crate::csv;
+use crate::metrics::Metrics;
 use crate::models::match_record::MatchRecord;
+use crate::models::player::Player;
+use crate::models::user::User;
+use crate::routes::matches::hide_losers;
+use crate::routes::players::auto_create_player;
+use crate::stats::{attendance, elo, glicko, scheduler};
 use crate::storage::client::StorageClient;
+use crate::storage::leagues::{self, LeagueStorageError};
 use crate::storage::matches::{self, MatchStorageError};
 use crate::storage::players::{self, PlayerStorageError};
+use crate::storage::users::{self, UserStorageError};
 
 /// A player's entry on the leaderboard.
 #[derive(Debug, Serialize)]
 pub struct LeaderboardEntry {
+    /// 1-based position. With `SHARED_RANKS`, tied entries share one and the
+    /// next rank skips accordingly (1, 2, 2, 4).
+    pub rank: u32,
     pub player_id: String,
     pub player_name: String,
     pub avatar_emoji: String,
@@ -38,6 +66,29 @@ pub struct LeaderboardEntry {
     pub win_rate: f64,
     /// Current streak: positive = winning, negative = losing.
     pub streak: i32,
+    /// True when the winning streak has reached `HOT_STREAK_THRESHOLD`, so the
+    /// SPA can show a 🔥 badge without duplicating the threshold.
+    pub on_fire: bool,
+    /// Longest winning streak the player has ever had over the matches this
+    /// leaderboard covers.
+    pub longest_streak_all_time: u32,
+    /// When the player's most recent match was played; `None` without games.
+    pub last_played: Option<DateTime<Utc>>,
+    /// Games in the last `RECENT_ACTIVITY_DAYS` calendar days (today
+    /// included, in `TIMEZONE`).
+    pub games_last_7_days: u32,
+    /// Glicko-2 rating and deviation, only present with `?rank_by=glicko`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating_deviation: Option<f64>,
+    /// Share of match days (0.0–1.0) the player showed up for, only present
+    /// with `?include_participation=true`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub participation_rate: Option<f64>,
+    /// Scoring totals, only present with `?include_points=true`.
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub points: Option<PointTotals>,
 }
 
 /// Detailed stats for a single player.
@@ -52,12 +103,113 @@ pub struct PlayerStats {
     pub total_games: u32,
     pub win_rate: f64,
     pub streak: i32,
+    /// Like `streak`, but surviving up to `STREAK_TOLERANCE` results that went
+    /// the other way (one bad game doesn't end a 10-game run).
+    pub resilient_streak: i32,
+    /// Longest run of consecutive wins / losses in the (filtered) history.
+    pub longest_win_streak: u32,
+    pub longest_loss_streak: u32,
     /// Best partner: (partner_id, partner_name, wins_together, losses_together)
     pub best_partner: Option<PartnerStats>,
     /// Nemesis: the player they lose to most.
     pub nemesis: Option<RivalryStats>,
-    /// Recent matches (last 10).
-    pub recent_matches: Vec<MatchRecord>,
+    /// Recent matches (last 10), full records or summaries per `?recent_detail`.
+    pub recent_matches: Vec<RecentMatch>,
+    /// Whole days since the player's most recent win. `None` if they've never won.
+    pub days_since_last_win: Option<i64>,
+    /// Whole days since the player's most recent match. `None` if they've never played.
+    pub days_since_last_played: Option<i64>,
+    /// Highest and lowest win-rate months (in `TIMEZONE`) among those with at
+    /// least `MONTH_HIGHLIGHT_MIN_GAMES` games. `None` if no month qualifies;
+    /// the same month when only one does.
+    pub best_month: Option<MonthlyRecord>,
+    pub worst_month: Option<MonthlyRecord>,
+    /// Win rate over competitive games only: a game is left out when the
+    /// opponents' average win rate is more than `ADJUSTED_WIN_RATE_GAP` below
+    /// the player's own (all rates over the same filtered match set). `None`
+    /// when no game qualifies.
+    pub adjusted_win_rate: Option<f64>,
+    /// Points scored and conceded over matches with recorded scores.
+    #[serde(flatten)]
+    pub points: PointTotals,
+}
+
+/// A player's points scored and conceded, summed over the matches they
+/// played that have both scores recorded (forfeits never count).
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct PointTotals {
+    pub points_for: u32,
+    pub points_against: u32,
+    /// `(points_for - points_against)` per scored match. `None` without any.
+    pub avg_point_differential: Option<f64>,
+    #[serde(skip)]
+    scored_games: u32,
+}
+
+impl PointTotals {
+    fn add(&mut self, scored: i32, conceded: i32) {
+        // `MatchRecord::validate` rejects negative scores.
+        self.points_for += scored.max(0) as u32;
+        self.points_against += conceded.max(0) as u32;
+        self.scored_games += 1;
+        self.avg_point_differential = Some(
+            (f64::from(self.points_for) - f64::from(self.points_against))
+                / f64::from(self.scored_games),
+        );
+    }
+}
+
+/// Every player's `PointTotals` across `matches`: winners score the winner
+/// score and concede the loser score, losers the other way round.
+fn point_totals(matches: &[MatchRecord]) -> HashMap<&str, PointTotals> {
+    let mut totals: HashMap<&str, PointTotals> = HashMap::new();
+    for m in matches {
+        let Some((winner_score, loser_score)) = m.scores() else {
+            continue;
+        };
+        for id in m.winner_ids() {
+            totals.entry(id).or_default().add(winner_score, loser_score);
+        }
+        for id in m.loser_ids() {
+            totals.entry(id).or_default().add(loser_score, winner_score);
+        }
+    }
+    totals
+}
+
+/// One entry in `PlayerStats::recent_matches`.
+///
+/// Untagged so `full` mode serializes exactly like a `MatchRecord`, keeping
+/// the default response backward compatible.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum RecentMatch {
+    Full(MatchRecord),
+    Summary(RecentMatchSummary),
+}
+
+/// A compact view of a recent match from the player's perspective.
+#[derive(Debug, Serialize)]
+pub struct RecentMatchSummary {
+    pub id: String,
+    pub played_at: DateTime<Utc>,
+    /// "win" or "loss".
+    pub result: &'static str,
+    /// Score from the player's side first, e.g. "7-11" for a loss. `None`
+    /// when scores weren't recorded or the match was a forfeit.
+    pub score: Option<String>,
+    pub forfeit: bool,
+}
+
+/// How much detail to include for recent matches in player stats.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecentDetail {
+    /// Full `MatchRecord` objects (default, backward compatible).
+    #[default]
+    Full,
+    /// Only `{id, played_at, result, score}` per match.
+    Summary,
 }
 
 #[derive(Debug, Serialize)]
@@ -87,6 +239,49 @@ pub struct RivalryEntry {
     pub player1_wins: u32,
     /// Games where player2 was on the winning team and player1 was on the losing team.
     pub player2_wins: u32,
+    /// How much of a real rivalry this is — see `rivalry_intensity`.
+    pub intensity: f64,
+    /// Who won their most recent meetings, and how many in a row.
+    pub current_streak: Option<RivalryStreak>,
+}
+
+/// Consecutive head-to-head wins by one player, up to the latest meeting
+/// ("X has won the last 4 against Y"). In doubles a meeting goes to whichever
+/// of the two players' teams won, whoever their partners were. Alternating
+/// results give a streak of 1.
+#[derive(Debug, Clone, Serialize)]
+pub struct RivalryStreak {
+    pub player_id: String,
+    pub wins: u32,
+}
+
+/// Extend `streak` with the next meeting (in chronological order) won by
+/// `winner`.
+fn extend_streak(streak: &mut Option<RivalryStreak>, winner: &str) {
+    match streak {
+        Some(s) if s.player_id == winner => s.wins += 1,
+        _ => {
+            *streak = Some(RivalryStreak {
+                player_id: winner.to_string(),
+                wins: 1,
+            })
+        }
+    }
+}
+
+/// Rivalry intensity: `total_games * (1 - |player1_wins - player2_wins| / total_games)`.
+///
+/// Grows with the number of games and shrinks as the record gets lopsided:
+/// 10 games at 5–5 scores 10, 10 games at 9–1 scores 2, and a clean sweep
+/// scores 0 however long it runs. (Algebraically this is `total - |diff|`,
+/// i.e. twice the underdog's wins.)
+fn rivalry_intensity(player1_wins: u32, player2_wins: u32) -> f64 {
+    let total = (player1_wins + player2_wins) as f64;
+    if total == 0.0 {
+        return 0.0;
+    }
+    let diff = (player1_wins as f64 - player2_wins as f64).abs();
+    total * (1.0 - diff / total)
 }
 
 /// Unified error type for leaderboard endpoints.
@@ -100,6 +295,10 @@ pub enum StatsError {
     Player(#[from] PlayerStorageError),
     #[error("{0}")]
     Match(#[from] MatchStorageError),
+    #[error("{0}")]
+    League(#[from] LeagueStorageError),
+    #[error("{0}")]
+    User(#[from] UserStorageError),
 }
 
 impl IntoResponse for StatsError {
@@ -107,6 +306,8 @@ impl IntoResponse for StatsError {
         match self {
             StatsError::Player(e) => e.into_response(),
             StatsError::Match(e) => e.into_response(),
+            StatsError::League(e) => e.into_response(),
+            StatsError::User(e) => e.into_response(),
         }
     }
 }
@@ -116,9 +317,23 @@ impl IntoResponse for StatsError {
 /// All leaderboard/stats endpoints accept this optional filter.
 /// When `league_id` is `Some`, only matches in that league are counted.
 /// When `None`, all matches are included (all-time stats).
+/// `exclude_forfeits=true` additionally drops forfeited matches.
 #[derive(Deserialize)]
 pub struct StatsQuery {
     pub league_id: Option<String>,
+    #[serde(default)]
+    pub exclude_forfeits: bool,
+}
+
+impl StatsQuery {
+    /// Response cache key for `prefix`, covering every query parameter.
+    fn cache_key(&self, prefix: &str) -> String {
+        format!(
+            "{prefix}:{}:{}",
+            self.league_id.as_deref().unwrap_or(""),
+            self.exclude_forfeits,
+        )
+    }
 }
 
 /// Filter matches by league ID (if provided).
@@ -135,135 +350,812 @@ fn filter_by_league(matches: Vec<MatchRecord>, league_id: &Option<String>) -> Ve
     }
 }
 
+/// Apply the league filter and, if requested, drop forfeits.
+fn filter_matches(
+    matches: Vec<MatchRecord>,
+    league_id: &Option<String>,
+    exclude_forfeits: bool,
+) -> Vec<MatchRecord> {
+    let mut matches = filter_by_league(matches, league_id);
+    if exclude_forfeits {
+        matches.retain(|m| !m.forfeit);
+    }
+    matches
+}
+
+/// How to order the leaderboard.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RankBy {
+    /// Win rate, then games played (default).
+    #[default]
+    WinRate,
+    /// Glicko-2 rating (see `stats::glicko`).
+    Glicko,
+}
+
+/// Query parameters for the leaderboard.
+#[derive(Deserialize)]
+pub struct LeaderboardQuery {
+    pub league_id: Option<String>,
+    #[serde(default)]
+    pub exclude_forfeits: bool,
+    /// `winrate` (default) or `glicko`.
+    #[serde(default)]
+    pub rank_by: RankBy,
+    /// Add each player's `participation_rate`.
+    #[serde(default)]
+    pub include_participation: bool,
+    /// Add each player's `points_for`, `points_against` and
+    /// `avg_point_differential`.
+    #[serde(default)]
+    pub include_points: bool,
+    /// `false` drops players who have no games (in the filtered match set)
+    /// instead of listing them with a 0.0 win rate. Defaults to `true`.
+    #[serde(default = "include_inactive_default")]
+    pub include_inactive: bool,
+    /// Only count matches played at or after this instant (RFC 3339).
+    pub since: Option<DateTime<Utc>>,
+    /// Only count matches from the last N days. Combined with `since`, the
+    /// later of the two bounds wins.
+    pub days: Option<u32>,
+    /// Ignore any cached copy and recompute (debugging aid).
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+impl LeaderboardQuery {
+    /// Start of the requested time window, or `None` for all-time.
+    fn window_start(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let from_days = self.days.map(|d| now - chrono::Duration::days(i64::from(d)));
+        self.since.max(from_days)
+    }
+
+    /// Response cache key covering every parameter that changes the result,
+    /// or `None` when the result depends on the current time (`days`).
+    /// `today` (in `TIMEZONE`) is part of the key because
+    /// `games_last_7_days` moves with the date.
+    fn cache_key(&self, today: NaiveDate) -> Option<String> {
+        if self.days.is_some() {
+            return None;
+        }
+        Some(format!(
+            "leaderboard:{today}:{}:{}:{:?}:{}:{}:{}:{}",
+            self.league_id.as_deref().unwrap_or(""),
+            self.exclude_forfeits,
+            self.rank_by,
+            self.include_participation,
+            self.include_points,
+            self.include_inactive,
+            self.since.map(|s| s.to_rfc3339()).unwrap_or_default(),
+        ))
+    }
+}
+
+fn include_inactive_default() -> bool {
+    true
+}
+
 /// GET /api/leaderboard — Ranked player list with stats.
 ///
 /// Accepts optional `?league_id=xxx` to filter stats to a specific league.
+/// With `?rank_by=glicko`, entries carry a Glicko-2 `rating` and
+/// `rating_deviation` and are ordered by rating instead of win rate.
+/// `?include_participation=true` adds the share of match days (in
+/// `TIMEZONE`) each player attended. `?include_inactive=false` omits players
+/// without any games.
+///
+/// `?since=<rfc3339>` or `?days=30` restrict the standings to a time window;
+/// streaks, ratings and participation are then computed within it too.
+/// Players who didn't play in the window are listed with zeroed stats unless
+/// `include_inactive=false`.
+///
+/// The serialized result is cached per parameter combination until the next
+/// data write; `?refresh=true` bypasses the cached copy. `?days=` results are
+/// never cached, since the window moves with the clock.
 pub async fn get_leaderboard(
     State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    Extension(cache): Extension<ResponseCache>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Response, StatsError> {
+    // Read the version *before* fetching, as in `export_player`.
+    let version = storage.data_version();
+    let key = query.cache_key(Utc::now().with_timezone(&config.timezone).date_naive());
+    if let Some(key) = &key
+        && !query.refresh
+        && let Some(body) = cache.get(key, version)
+    {
+        return Ok(json_bytes(body));
+    }
+
+    let entries = build_leaderboard(&storage, &config, &query).await?;
+    let body = Bytes::from(
+        serde_json::to_vec(&entries).expect("LeaderboardEntry serialization cannot fail"),
+    );
+    if let Some(key) = key {
+        cache.insert(key, version, body.clone());
+    }
+
+    Ok(json_bytes(body))
+}
+
+/// Compute the leaderboard for `query` (uncached; see `get_leaderboard`).
+async fn build_leaderboard(
+    storage: &StorageClient,
+    config: &AppConfig,
+    query: &LeaderboardQuery,
+) -> Result<Vec<LeaderboardEntry>, StatsError> {
+    let all_players = players::list_players(storage).await?;
+    let all_matches = matches::list_matches(storage, None).await?;
+    let mut all_matches =
+        filter_matches(all_matches, &query.league_id, query.exclude_forfeits);
+    if let Some(start) = query.window_start(Utc::now()) {
+        all_matches.retain(|m| m.played_at >= start);
+    }
+    Ok(rank_leaderboard(&all_players, &all_matches, config, query))
+}
+
+/// The leaderboard for `query` over already filtered matches (newest-first).
+fn rank_leaderboard(
+    all_players: &[Player],
+    all_matches: &[MatchRecord],
+    config: &AppConfig,
+    query: &LeaderboardQuery,
+) -> Vec<LeaderboardEntry> {
+    let mut entries = compute_leaderboard(all_players, all_matches, config);
+    if !query.include_inactive {
+        entries.retain(|e| e.total_games > 0);
+    }
+
+    if query.include_participation {
+        let rates = attendance::participation_rates(all_matches, config.timezone);
+        for entry in &mut entries {
+            entry.participation_rate =
+                Some(rates.get(entry.player_id.as_str()).copied().unwrap_or(0.0));
+        }
+    }
+
+    if query.include_points {
+        let totals = point_totals(all_matches);
+        for entry in &mut entries {
+            entry.points = Some(totals.get(entry.player_id.as_str()).copied().unwrap_or_default());
+        }
+    }
+
+    if let RankBy::Glicko = query.rank_by {
+        let ratings = glicko::compute_glicko(all_players, all_matches, config.glicko_period_days);
+        for entry in &mut entries {
+            let rating = ratings.get(&entry.player_id).copied().unwrap_or_default();
+            entry.rating = Some(rating.rating);
+            entry.rating_deviation = Some(rating.deviation);
+        }
+        entries.sort_by(|a, b| {
+            b.rating
+                .partial_cmp(&a.rating)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        assign_ranks(&mut entries, config.shared_ranks);
+    }
+
+    entries
+}
+
+/// Calendar days, today included, counted by `games_last_7_days`.
+const RECENT_ACTIVITY_DAYS: u64 = 7;
+
+/// Aggregate a match list into sorted leaderboard entries.
+///
+/// `all_matches` must be newest-first (storage order) for streaks to be
+/// correct. Every player in `all_players` gets an entry, even with zero games.
+fn compute_leaderboard(
+    all_players: &[Player],
+    all_matches: &[MatchRecord],
+    config: &AppConfig,
+) -> Vec<LeaderboardEntry> {
+    let mut standings = Standings::new(config);
+    for m in all_matches.iter().rev() {
+        standings.record(m);
+    }
+    standings.entries(all_players, config)
+}
+
+/// Leaderboard state built up one match at a time, oldest first, so the
+/// standings after any prefix of the history are cheap to read off.
+struct Standings<'a> {
+    records: HashMap<&'a str, StandingsRecord>,
+    tz: Tz,
+    /// First local day counted by `games_last_7_days`.
+    recent_start: NaiveDate,
+}
+
+/// One player's running totals in `Standings`.
+#[derive(Default)]
+struct StandingsRecord {
+    wins: u32,
+    losses: u32,
+    /// Positive = winning, negative = losing, as `calculate_streak`.
+    streak: i32,
+    longest_win_streak: u32,
+    last_played: Option<DateTime<Utc>>,
+    recent_games: u32,
+}
+
+impl<'a> Standings<'a> {
+    fn new(config: &AppConfig) -> Self {
+        let tz = config.timezone;
+        let recent_start = Utc::now().with_timezone(&tz).date_naive()
+            - chrono::Days::new(RECENT_ACTIVITY_DAYS - 1);
+        Self {
+            records: HashMap::new(),
+            tz,
+            recent_start,
+        }
+    }
+
+    /// Add `m`, which must be newer than every match recorded so far.
+    fn record(&mut self, m: &'a MatchRecord) {
+        let recent = m.played_at.with_timezone(&self.tz).date_naive() >= self.recent_start;
+        let results = m.winner_ids().map(|id| (id, true));
+        for (id, won) in results.chain(m.loser_ids().map(|id| (id, false))) {
+            let record = self.records.entry(id).or_default();
+            if won {
+                record.wins += 1;
+                record.streak = record.streak.max(0) + 1;
+                record.longest_win_streak = record.longest_win_streak.max(record.streak as u32);
+            } else {
+                record.losses += 1;
+                record.streak = record.streak.min(0) - 1;
+            }
+            record.last_played = record.last_played.max(Some(m.played_at));
+            if recent {
+                record.recent_games += 1;
+            }
+        }
+    }
+
+    /// Sorted, ranked entries for every player in `all_players` as of the
+    /// matches recorded so far.
+    fn entries(&self, all_players: &[Player], config: &AppConfig) -> Vec<LeaderboardEntry> {
+        let no_games = StandingsRecord::default();
+        let mut entries: Vec<LeaderboardEntry> = all_players
+            .iter()
+            .map(|p| {
+                let record = self.records.get(p.id.as_str()).unwrap_or(&no_games);
+                let total = record.wins + record.losses;
+                let win_rate = if total > 0 {
+                    record.wins as f64 / total as f64
+                } else {
+                    0.0
+                };
+
+                LeaderboardEntry {
+                    rank: 0,
+                    player_id: p.id.clone(),
+                    player_name: p.name.clone(),
+                    avatar_emoji: p.avatar_emoji.clone(),
+                    nickname: p.nickname.clone(),
+                    wins: record.wins,
+                    losses: record.losses,
+                    total_games: total,
+                    win_rate,
+                    streak: record.streak,
+                    on_fire: record.streak >= config.hot_streak_threshold,
+                    longest_streak_all_time: record.longest_win_streak,
+                    last_played: record.last_played,
+                    games_last_7_days: record.recent_games,
+                    rating: None,
+                    rating_deviation: None,
+                    participation_rate: None,
+                    points: None,
+                }
+            })
+            .collect();
+
+        // Sort by win rate descending, then by total games descending as tiebreaker.
+        entries.sort_by(|a, b| {
+            b.win_rate
+                .partial_cmp(&a.win_rate)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(b.total_games.cmp(&a.total_games))
+        });
+        assign_ranks(&mut entries, config.shared_ranks);
+
+        entries
+    }
+}
+
+/// Number sorted `entries` 1, 2, 3, … or, with `shared`, give tied entries
+/// the same rank and skip the ranks they used up ("1224" ranking).
+///
+/// Entries tie when they'd sort the same: equal Glicko ratings when ranked by
+/// rating, otherwise equal win rate and total games.
+fn assign_ranks(entries: &mut [LeaderboardEntry], shared: bool) {
+    let tied = |a: &LeaderboardEntry, b: &LeaderboardEntry| match (a.rating, b.rating) {
+        (Some(x), Some(y)) => x == y,
+        _ => a.win_rate == b.win_rate && a.total_games == b.total_games,
+    };
+
+    for i in 0..entries.len() {
+        entries[i].rank = if shared && i > 0 && tied(&entries[i - 1], &entries[i]) {
+            entries[i - 1].rank
+        } else {
+            i as u32 + 1
+        };
+    }
+}
+
+/// The leaderboard as it stood right after one particular match.
+#[derive(Debug, Serialize)]
+pub struct LeaderboardSnapshot {
+    /// How many matches (oldest-first) had been played at this point, 1-based.
+    pub match_number: usize,
+    pub match_id: String,
+    pub played_at: DateTime<Utc>,
+    pub standings: Vec<LeaderboardEntry>,
+}
+
+/// GET /api/leaderboard/timeline — Leaderboard snapshots over time.
+///
+/// Powers the animated "race" chart. Returns one snapshot per match in
+/// chronological order, or an evenly spaced sample when there are more
+/// matches than `TIMELINE_MAX_SNAPSHOTS`. The last snapshot always reflects
+/// every match, so it matches `GET /api/leaderboard`.
+///
+/// The history is replayed once, oldest first, so the cost is
+/// O(matches + snapshots × players).
+pub async fn get_leaderboard_timeline(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
     Query(query): Query<StatsQuery>,
-) -> Result<Json<Vec<LeaderboardEntry>>, StatsError> {
+) -> Result<Json<Vec<LeaderboardSnapshot>>, StatsError> {
     let all_players = players::list_players(&storage).await?;
     let all_matches = matches::list_matches(&storage, None).await?;
-    let all_matches = filter_by_league(all_matches, &query.league_id);
+    let all_matches = filter_matches(all_matches, &query.league_id, query.exclude_forfeits);
 
-    // Count wins/losses per player and track streaks.
-    let mut wins: HashMap<&str, u32> = HashMap::new();
-    let mut losses: HashMap<&str, u32> = HashMap::new();
+    Ok(Json(compute_timeline(&all_players, &all_matches, &config)))
+}
 
-    // For streak calculation, we need matches in chronological order per player.
-    // all_matches is already sorted newest-first (reverse timestamp RowKey).
-    let mut last_results: HashMap<&str, Vec<bool>> = HashMap::new(); // true=win, false=loss
+/// Leaderboard snapshots after each match (or an evenly spaced sample of
+/// them), oldest first. `all_matches` must be newest-first.
+fn compute_timeline(
+    all_players: &[Player],
+    all_matches: &[MatchRecord],
+    config: &AppConfig,
+) -> Vec<LeaderboardSnapshot> {
+    let total = all_matches.len();
+    let max = config.timeline_max_snapshots.max(1);
+    let sample_points: Vec<usize> = if total <= max {
+        (1..=total).collect()
+    } else {
+        // Evenly spaced and always ending on `total`.
+        (1..=max).map(|k| k * total / max).collect()
+    };
 
-    for m in &all_matches {
-        for winner_id in [&m.winner1_id, &m.winner2_id] {
-            *wins.entry(winner_id.as_str()).or_default() += 1;
-            last_results
-                .entry(winner_id.as_str())
-                .or_default()
-                .push(true);
-        }
-        for loser_id in [&m.loser1_id, &m.loser2_id] {
-            *losses.entry(loser_id.as_str()).or_default() += 1;
-            last_results
-                .entry(loser_id.as_str())
-                .or_default()
-                .push(false);
+    let mut sample_points = sample_points.into_iter().peekable();
+    let mut standings = Standings::new(config);
+    let mut snapshots = Vec::with_capacity(total.min(max));
+    // all_matches is newest-first, so replay it from the back.
+    for (i, m) in all_matches.iter().rev().enumerate() {
+        standings.record(m);
+        if sample_points.next_if_eq(&(i + 1)).is_some() {
+            snapshots.push(LeaderboardSnapshot {
+                match_number: i + 1,
+                match_id: m.id.clone(),
+                played_at: m.played_at,
+                standings: standings.entries(all_players, config),
+            });
         }
     }
+    snapshots
+}
 
-    let mut entries: Vec<LeaderboardEntry> = all_players
-        .iter()
-        .map(|p| {
-            let w = wins.get(p.id.as_str()).copied().unwrap_or(0);
-            let l = losses.get(p.id.as_str()).copied().unwrap_or(0);
-            let total = w + l;
-            let win_rate = if total > 0 {
-                w as f64 / total as f64
-            } else {
-                0.0
-            };
+/// Everything the SPA home page needs, computed over a single fetch of
+/// players and matches.
+#[derive(Debug, Serialize)]
+pub struct Dashboard {
+    pub leaderboard: Vec<LeaderboardEntry>,
+    pub summary: DashboardSummary,
+    /// The most recent matches, newest first.
+    pub recent_matches: Vec<MatchRecord>,
+    /// The longest active winning streak, if anyone has one.
+    pub hot_streak: Option<HotStreak>,
+}
 
-            // Calculate current streak from most recent matches.
-            // last_results are in newest-first order (from all_matches order).
-            let streak = calculate_streak(
-                last_results.get(p.id.as_str()).map(|v| v.as_slice()).unwrap_or(&[]),
-            );
+/// Headline numbers for the dashboard.
+#[derive(Debug, Serialize)]
+pub struct DashboardSummary {
+    pub total_matches: usize,
+    pub total_players: usize,
+    /// Players with at least one game in the (filtered) match set.
+    pub active_players: usize,
+    pub latest_match_at: Option<DateTime<Utc>>,
+}
 
-            LeaderboardEntry {
-                player_id: p.id.clone(),
-                player_name: p.name.clone(),
-                avatar_emoji: p.avatar_emoji.clone(),
-                nickname: p.nickname.clone(),
-                wins: w,
-                losses: l,
-                total_games: total,
-                win_rate,
-                streak,
+/// The player currently on the longest winning streak.
+#[derive(Debug, Serialize)]
+pub struct HotStreak {
+    pub player_id: String,
+    pub player_name: String,
+    pub avatar_emoji: String,
+    pub streak: i32,
+}
+
+/// How many recent matches the dashboard includes.
+const DASHBOARD_RECENT_MATCHES: usize = 10;
+
+/// GET /api/dashboard — Home page bundle in one call.
+///
+/// Replaces separate leaderboard / summary / recent matches / hot streak
+/// requests with one response built from a single shared fetch.
+/// Accepts optional `?league_id=xxx` to filter stats to a specific league.
+/// Losers in `recent_matches` are anonymized for display screens, as in
+/// `GET /api/matches`.
+pub async fn get_dashboard(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    claims: Option<Extension<SessionClaims>>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<Dashboard>, StatsError> {
+    let all_players = players::list_players(&storage).await?;
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let all_matches = filter_matches(all_matches, &query.league_id, query.exclude_forfeits);
+
+    let anonymize = hide_losers(&config, claims.as_deref());
+    Ok(Json(build_dashboard(&config, &all_players, all_matches, anonymize)))
+}
+
+/// Assemble the dashboard from one fetch. `all_matches` must be newest-first.
+fn build_dashboard(
+    config: &AppConfig,
+    all_players: &[Player],
+    all_matches: Vec<MatchRecord>,
+    anonymize: bool,
+) -> Dashboard {
+    let leaderboard = compute_leaderboard(all_players, &all_matches, config);
+
+    let summary = DashboardSummary {
+        total_matches: all_matches.len(),
+        total_players: all_players.len(),
+        active_players: leaderboard.iter().filter(|e| e.total_games > 0).count(),
+        latest_match_at: all_matches.first().map(|m| m.played_at),
+    };
+
+    let hot_streak = leaderboard
+        .iter()
+        .filter(|e| e.streak > 0)
+        .max_by_key(|e| e.streak)
+        .map(|e| HotStreak {
+            player_id: e.player_id.clone(),
+            player_name: e.player_name.clone(),
+            avatar_emoji: e.avatar_emoji.clone(),
+            streak: e.streak,
+        });
+
+    let recent_matches = all_matches
+        .into_iter()
+        .take(DASHBOARD_RECENT_MATCHES)
+        .map(|mut m| {
+            if anonymize {
+                m.anonymize_losers();
             }
+            m
         })
         .collect();
 
-    // Sort by win rate descending, then by total games descending as tiebreaker.
-    entries.sort_by(|a, b| {
-        b.win_rate
-            .partial_cmp(&a.win_rate)
-            .unwrap_or(std::cmp::Ordering::Equal)
-            .then(b.total_games.cmp(&a.total_games))
-    });
+    Dashboard {
+        leaderboard,
+        summary,
+        recent_matches,
+        hot_streak,
+    }
+}
 
-    Ok(Json(entries))
+/// Query parameters for the player stats endpoint.
+#[derive(Deserialize)]
+pub struct PlayerStatsQuery {
+    pub league_id: Option<String>,
+    #[serde(default)]
+    pub exclude_forfeits: bool,
+    /// `full` (default) or `summary` — see `RecentDetail`.
+    #[serde(default)]
+    pub recent_detail: RecentDetail,
+    /// `false` skips building `recent_matches` (returned empty), for callers
+    /// that only need the headline numbers. Defaults to `true`.
+    #[serde(default = "include_recent_default")]
+    pub include_recent: bool,
+}
+
+fn include_recent_default() -> bool {
+    true
 }
 
 /// GET /api/players/{id}/stats — Detailed stats for one player.
 ///
-/// Accepts optional `?league_id=xxx` to filter stats to a specific league.
+/// Accepts optional `?league_id=xxx` to filter stats to a specific league,
+/// `?recent_detail=summary` to shrink the recent matches payload, and
+/// `?include_recent=false` to omit it entirely.
 pub async fn get_player_stats(
     State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
     Path(player_id): Path<String>,
-    Query(query): Query<StatsQuery>,
+    Query(query): Query<PlayerStatsQuery>,
 ) -> Result<Json<PlayerStats>, StatsError> {
     let player = players::get_player(&storage, &player_id).await?;
     let all_matches = matches::list_matches(&storage, None).await?;
-    let all_matches = filter_by_league(all_matches, &query.league_id);
+    let all_matches = filter_matches(all_matches, &query.league_id, query.exclude_forfeits);
+    let all_players = players::list_players(&storage).await?;
+
+    Ok(Json(compute_player_stats(
+        &config,
+        player,
+        &all_matches,
+        &all_players,
+        query.include_recent.then_some(query.recent_detail),
+    )))
+}
+
+/// Whose stats `/api/me/stats` shows for a stored user.
+#[derive(Debug, PartialEq, Eq)]
+enum MyPlayer<'a> {
+    /// The user's linked player.
+    Linked(&'a str),
+    /// Nobody linked, but `AUTO_CREATE_PLAYERS` is on.
+    AutoCreate,
+    /// Nobody linked: `404`.
+    Unlinked,
+}
+
+fn my_player<'a>(config: &AppConfig, user: &'a User) -> MyPlayer<'a> {
+    match user.player_id.as_deref() {
+        Some(player_id) => MyPlayer::Linked(player_id),
+        None if config.auto_create_players => MyPlayer::AutoCreate,
+        None => MyPlayer::Unlinked,
+    }
+}
+
+/// GET /api/me/stats — Detailed stats for the caller's linked player.
+///
+/// Takes the same query parameters as `/api/players/{id}/stats`. The link is
+/// read from the stored user rather than the session, which only picks up
+/// changes at the next login. Without a linked player this is a 404, unless
+/// `AUTO_CREATE_PLAYERS` is on: then a player is created from the caller's
+/// profile (see `auto_create_player`) and linked first.
+pub async fn get_my_stats(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    Extension(claims): Extension<SessionClaims>,
+    Extension(metrics): Extension<Metrics>,
+    query: Query<PlayerStatsQuery>,
+) -> Result<Response, StatsError> {
+    let user = users::get_user(&storage, &claims.sub).await?;
+    let player_id = match my_player(&config, &user) {
+        MyPlayer::Linked(player_id) => player_id.to_string(),
+        MyPlayer::AutoCreate => {
+            let player = auto_create_player(&storage, &config, &claims).await?;
+            users::link_player(&storage, &claims.sub, Some(player.id.clone())).await?;
+            metrics.player_created();
+            tracing::info!("Auto-created player '{}' for user {}", player.id, claims.sub);
+            player.id
+        }
+        MyPlayer::Unlinked => {
+            return Ok((
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({"error": "No player is linked to your account"})),
+            )
+                .into_response());
+        }
+    };
+
+    let stats = get_player_stats(State(storage), Extension(config), Path(player_id), query).await?;
+    Ok(stats.into_response())
+}
+
+/// A self-contained export of one player's full record.
+///
+/// Unlike `PlayerStats` (which only carries the last 10 matches), this includes
+/// every match the player took part in, so it can be shared as a "stat card"
+/// or archived without further API calls.
+#[derive(Debug, Serialize)]
+pub struct PlayerExport {
+    pub player: Player,
+    pub stats: PlayerStats,
+    /// Every match the player participated in, newest first.
+    pub matches: Vec<MatchRecord>,
+    pub exported_at: DateTime<Utc>,
+}
+
+/// Assemble `player`'s export from the (already filtered) match set.
+fn build_player_export(
+    config: &AppConfig,
+    player: Player,
+    all_matches: Vec<MatchRecord>,
+    all_players: &[Player],
+) -> PlayerExport {
+    let stats = compute_player_stats(
+        config,
+        player.clone(),
+        &all_matches,
+        all_players,
+        Some(RecentDetail::Full),
+    );
+    let player_matches = all_matches
+        .into_iter()
+        .filter(|m| m.has_player(&player.id))
+        .collect();
+
+    PlayerExport {
+        player,
+        stats,
+        matches: player_matches,
+        exported_at: Utc::now(),
+    }
+}
+
+/// GET /api/players/{id}/export — Full player record as a single JSON document.
+///
+/// Heavier than the stats endpoint by design: it returns the complete match
+/// history. Accepts optional `?league_id=xxx` like the other stats endpoints.
+///
+/// The serialized body is cached until the next data write, so repeated
+/// exports in between are served as-is (including `exported_at`, which then
+/// reflects when the snapshot was built).
+pub async fn export_player(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    Extension(cache): Extension<ResponseCache>,
+    Path(player_id): Path<String>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Response, StatsError> {
+    // Read the version *before* fetching, so a write that lands mid-build
+    // leaves this entry already stale rather than cached as current.
+    let version = storage.data_version();
+    let key = query.cache_key(&format!("player-export:{player_id}"));
+    if let Some(body) = cache.get(&key, version) {
+        return Ok(json_bytes(body));
+    }
+
+    let player = players::get_player(&storage, &player_id).await?;
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let all_matches = filter_matches(all_matches, &query.league_id, query.exclude_forfeits);
+    let all_players = players::list_players(&storage).await?;
+
+    let export = build_player_export(&config, player, all_matches, &all_players);
+    let body = Bytes::from(
+        serde_json::to_vec(&export).expect("PlayerExport serialization cannot fail"),
+    );
+    cache.insert(key, version, body.clone());
+
+    Ok(json_bytes(body))
+}
+
+/// Version of the `PlayerCard` shape. Bump when fields change so the image
+/// renderer can tell which template to use.
+pub const PLAYER_CARD_VERSION: u32 = 1;
+
+/// Exactly what the Open Graph stat-card template needs — kept separate from
+/// `PlayerStats` so the template doesn't break when stats grow new fields.
+#[derive(Debug, Serialize)]
+pub struct PlayerCard {
+    pub version: u32,
+    pub name: String,
+    pub emoji: String,
+    pub wins: u32,
+    pub losses: u32,
+    /// Win rate as a whole percentage (0–100), ready to print.
+    pub win_rate_pct: u32,
+    /// Current streak: positive = winning, negative = losing.
+    pub streak: i32,
+    /// Leaderboard rank (shared with tied players under `SHARED_RANKS`).
+    pub rank: u32,
+}
+
+/// GET /api/players/{id}/card — Compact stat payload for a share image.
+///
+/// Accepts optional `?league_id=xxx` like the other stats endpoints.
+pub async fn get_player_card(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    Path(player_id): Path<String>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<PlayerCard>, StatsError> {
+    let player = players::get_player(&storage, &player_id).await?;
+    let all_players = players::list_players(&storage).await?;
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let all_matches = filter_matches(all_matches, &query.league_id, query.exclude_forfeits);
+
+    let player_id = player.id.clone();
+    let card = build_player_card(&config, player, &all_players, &all_matches)
+        .ok_or(PlayerStorageError::NotFound(player_id))?;
+    Ok(Json(card))
+}
+
+/// The card for `player`, or `None` if they aren't on the leaderboard.
+fn build_player_card(
+    config: &AppConfig,
+    player: Player,
+    all_players: &[Player],
+    all_matches: &[MatchRecord],
+) -> Option<PlayerCard> {
+    let entry = compute_leaderboard(all_players, all_matches, config)
+        .into_iter()
+        .find(|e| e.player_id == player.id)?;
+
+    Some(PlayerCard {
+        version: PLAYER_CARD_VERSION,
+        name: player.name,
+        emoji: player.avatar_emoji,
+        wins: entry.wins,
+        losses: entry.losses,
+        win_rate_pct: (entry.win_rate * 100.0).round() as u32,
+        streak: entry.streak,
+        rank: entry.rank,
+    })
+}
+
+/// Wrap an already-serialized JSON body in a response.
+fn json_bytes(body: Bytes) -> Response {
+    ([(header::CONTENT_TYPE, "application/json")], body).into_response()
+}
+
+/// Compute detailed stats for one player from a (pre-filtered) match list.
+///
+/// Shared by the stats and export endpoints. `all_matches` must be in
+/// newest-first order (as returned by storage) for streaks and recent matches
+/// to be correct. `recent_detail: None` leaves `recent_matches` empty.
+fn compute_player_stats(
+    config: &AppConfig,
+    player: Player,
+    all_matches: &[MatchRecord],
+    all_players: &[Player],
+    recent_detail: Option<RecentDetail>,
+) -> PlayerStats {
+    let player_id = player.id.as_str();
 
     let mut wins = 0u32;
     let mut losses = 0u32;
     let mut results: Vec<bool> = Vec::new(); // newest-first
     let mut partner_record: HashMap<String, (u32, u32)> = HashMap::new(); // (wins, losses)
     let mut opponent_record: HashMap<String, (u32, u32)> = HashMap::new(); // (wins_against, losses_against)
-    let mut recent: Vec<MatchRecord> = Vec::new();
-
-    for m in &all_matches {
-        let is_winner = m.winner1_id == player_id || m.winner2_id == player_id;
-        let is_loser = m.loser1_id == player_id || m.loser2_id == player_id;
+    let mut recent: Vec<RecentMatch> = Vec::new();
+    // Matches are newest-first, so the first hit of each is the most recent.
+    let mut last_played: Option<DateTime<Utc>> = None;
+    let mut last_win: Option<DateTime<Utc>> = None;
 
-        if !is_winner && !is_loser {
+    for m in all_matches {
+        if !m.has_player(player_id) {
             continue;
         }
+        let is_winner = m.is_winner(player_id);
 
-        if recent.len() < 10 {
-            recent.push(m.clone());
+        if let Some(recent_detail) = recent_detail
+            && recent.len() < 10
+        {
+            recent.push(match recent_detail {
+                RecentDetail::Full => RecentMatch::Full(m.clone()),
+                RecentDetail::Summary => RecentMatch::Summary(summarize_match(m, is_winner)),
+            });
         }
 
+        last_played.get_or_insert(m.played_at);
+
         if is_winner {
             wins += 1;
             results.push(true);
+            last_win.get_or_insert(m.played_at);
 
-            // Track partner
-            let partner = if m.winner1_id == player_id {
-                &m.winner2_id
-            } else {
-                &m.winner1_id
-            };
-            partner_record
-                .entry(partner.clone())
-                .or_default()
-                .0 += 1;
+            // Track partner (none in singles)
+            for partner in m.winner_ids().filter(|id| *id != player_id) {
+                partner_record
+                    .entry(partner.to_string())
+                    .or_default()
+                    .0 += 1;
+            }
 
             // Track opponents
-            for opp in [&m.loser1_id, &m.loser2_id] {
+            for opp in m.loser_ids() {
                 opponent_record
-                    .entry(opp.clone())
+                    .entry(opp.to_string())
                     .or_default()
                     .0 += 1;
             }
@@ -271,21 +1163,18 @@ pub async fn get_player_stats(
             losses += 1;
             results.push(false);
 
-            // Track partner
-            let partner = if m.loser1_id == player_id {
-                &m.loser2_id
-            } else {
-                &m.loser1_id
-            };
-            partner_record
-                .entry(partner.clone())
-                .or_default()
-                .1 += 1;
+            // Track partner (none in singles)
+            for partner in m.loser_ids().filter(|id| *id != player_id) {
+                partner_record
+                    .entry(partner.to_string())
+                    .or_default()
+                    .1 += 1;
+            }
 
             // Track opponents
-            for opp in [&m.winner1_id, &m.winner2_id] {
+            for opp in m.winner_ids() {
                 opponent_record
-                    .entry(opp.clone())
+                    .entry(opp.to_string())
                     .or_default()
                     .1 += 1;
             }
@@ -299,9 +1188,13 @@ pub async fn get_player_stats(
         0.0
     };
     let streak = calculate_streak(&results);
+    let resilient_streak = calculate_resilient_streak(&results, config.streak_tolerance);
+    let (longest_win_streak, longest_loss_streak) = longest_streaks(&results);
+
+    let now = Utc::now();
+    let days_since = |t: DateTime<Utc>| (now - t).num_days();
 
     // Find best partner (most wins together, minimum 2 games)
-    let all_players = players::list_players(&storage).await?;
     let player_names: HashMap<&str, &str> = all_players
         .iter()
         .map(|p| (p.id.as_str(), p.name.as_str()))
@@ -336,7 +1229,16 @@ pub async fn get_player_stats(
             losses_against: *l,
         });
 
-    Ok(Json(PlayerStats {
+    let adjusted_win_rate =
+        adjusted_win_rate(player_id, all_matches, config.adjusted_win_rate_gap);
+    let points = point_totals(all_matches).remove(player_id).unwrap_or_default();
+
+    let (best_month, worst_month) = month_extremes(
+        monthly_records(player_id, all_matches, config.timezone, false),
+        config.month_highlight_min_games,
+    );
+
+    PlayerStats {
         player_id: player.id,
         player_name: player.name,
         avatar_emoji: player.avatar_emoji,
@@ -346,56 +1248,288 @@ pub async fn get_player_stats(
         total_games: total,
         win_rate,
         streak,
+        resilient_streak,
+        longest_win_streak,
+        longest_loss_streak,
         best_partner,
         nemesis,
         recent_matches: recent,
-    }))
+        days_since_last_win: last_win.map(days_since),
+        days_since_last_played: last_played.map(days_since),
+        best_month,
+        worst_month,
+        adjusted_win_rate,
+        points,
+    }
+}
+
+/// Each player's overall win rate across `matches`.
+fn win_rates(matches: &[MatchRecord]) -> HashMap<&str, f64> {
+    let mut records: HashMap<&str, (u32, u32)> = HashMap::new(); // (wins, games)
+    for m in matches {
+        for id in m.player_ids() {
+            let record = records.entry(id).or_default();
+            record.0 += u32::from(m.is_winner(id));
+            record.1 += 1;
+        }
+    }
+    records
+        .into_iter()
+        .map(|(id, (wins, games))| (id, wins as f64 / games as f64))
+        .collect()
+}
+
+/// `player_id`'s win rate, skipping games where the opponents' average win
+/// rate is more than `gap` below the player's own. See
+/// `PlayerStats::adjusted_win_rate`.
+fn adjusted_win_rate(player_id: &str, all_matches: &[MatchRecord], gap: f64) -> Option<f64> {
+    let rates = win_rates(all_matches);
+    let own = rates.get(player_id).copied()?;
+
+    let (mut wins, mut games) = (0u32, 0u32);
+    for m in all_matches.iter().filter(|m| m.has_player(player_id)) {
+        let won = m.is_winner(player_id);
+        let opponents: Vec<&str> = if won {
+            m.loser_ids().collect()
+        } else {
+            m.winner_ids().collect()
+        };
+        let opponents_avg = opponents
+            .iter()
+            .map(|id| rates.get(id).copied().unwrap_or(0.0))
+            .sum::<f64>()
+            / opponents.len() as f64;
+        if own - opponents_avg > gap {
+            continue;
+        }
+        wins += u32::from(won);
+        games += 1;
+    }
+
+    (games > 0).then(|| wins as f64 / games as f64)
+}
+
+/// How to order the rivalries list.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RivalrySort {
+    /// Most games played first (default).
+    #[default]
+    Games,
+    /// Highest `intensity` first.
+    Intensity,
+}
+
+/// Query parameters for the rivalries endpoint.
+#[derive(Deserialize)]
+pub struct RivalriesQuery {
+    pub league_id: Option<String>,
+    #[serde(default)]
+    pub exclude_forfeits: bool,
+    /// `games` (default) or `intensity`.
+    #[serde(default)]
+    pub sort: RivalrySort,
 }
 
 /// GET /api/rivalries — Head-to-head records between all player pairs.
 ///
-/// Accepts optional `?league_id=xxx` to filter stats to a specific league.
+/// Accepts optional `?league_id=xxx` to filter stats to a specific league,
+/// and `?sort=intensity` to put close, frequent rivalries ahead of lopsided
+/// ones.
 pub async fn get_rivalries(
     State(storage): State<StorageClient>,
-    Query(query): Query<StatsQuery>,
+    Query(query): Query<RivalriesQuery>,
 ) -> Result<Json<Vec<RivalryEntry>>, StatsError> {
     let all_players = players::list_players(&storage).await?;
     let all_matches = matches::list_matches(&storage, None).await?;
-    let all_matches = filter_by_league(all_matches, &query.league_id);
+    let all_matches = filter_matches(all_matches, &query.league_id, query.exclude_forfeits);
+
+    Ok(Json(compute_rivalries(&all_players, &all_matches, query.sort)))
+}
+
+/// Detailed head-to-head between two specific players.
+#[derive(Debug, Serialize)]
+pub struct HeadToHead {
+    pub player1: Player,
+    pub player2: Player,
+    /// Games where player1 was on the winning team and player2 on the losing team.
+    pub player1_wins: u32,
+    /// Games where player2 was on the winning team and player1 on the losing team.
+    pub player2_wins: u32,
+    /// ID of the player with more wins over the other; `None` when level.
+    pub leader: Option<String>,
+    /// Who won their most recent meetings, and how many in a row; `None`
+    /// when they've never met.
+    pub current_streak: Option<RivalryStreak>,
+    /// The matches they played on opposite teams, newest first.
+    pub matches: Vec<MatchRecord>,
+}
+
+/// GET /api/rivalries/{id1}/{id2} — Head-to-head between two players.
+///
+/// Counts only games where the two were on opposite teams; games as partners
+/// don't count either way. Unlike `GET /api/rivalries` there's no minimum
+/// number of games. `404` if either player doesn't exist, `400` if both IDs
+/// are the same. Accepts the usual `league_id` / `exclude_forfeits`.
+pub async fn get_head_to_head(
+    State(storage): State<StorageClient>,
+    Path((id1, id2)): Path<(String, String)>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Response, StatsError> {
+    if id1 == id2 {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "a player has no head-to-head with themselves"})),
+        )
+            .into_response());
+    }
+    let player1 = players::get_player(&storage, &id1).await?;
+    let player2 = players::get_player(&storage, &id2).await?;
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let all_matches = filter_matches(all_matches, &query.league_id, query.exclude_forfeits);
+
+    let (mut player1_wins, mut player2_wins) = (0, 0);
+    let mut current_streak = None;
+    let mut h2h_matches = Vec::new();
+    for m in all_matches {
+        let mut opposed = false;
+        for (winner, loser) in winner_loser_pairs(&m) {
+            if (winner, loser) == (id1.as_str(), id2.as_str()) {
+                player1_wins += 1;
+                opposed = true;
+            } else if (winner, loser) == (id2.as_str(), id1.as_str()) {
+                player2_wins += 1;
+                opposed = true;
+            }
+        }
+        if opposed {
+            h2h_matches.push(m);
+        }
+    }
+    // Listed newest first; the streak is built oldest first.
+    h2h_matches.sort_by_key(|m| std::cmp::Reverse(m.played_at));
+    for m in h2h_matches.iter().rev() {
+        let winner = if m.winner_ids().any(|id| id == id1) { &id1 } else { &id2 };
+        extend_streak(&mut current_streak, winner);
+    }
+
+    let leader = match player1_wins.cmp(&player2_wins) {
+        std::cmp::Ordering::Greater => Some(id1),
+        std::cmp::Ordering::Less => Some(id2),
+        std::cmp::Ordering::Equal => None,
+    };
+    Ok(Json(HeadToHead {
+        player1,
+        player2,
+        player1_wins,
+        player2_wins,
+        leader,
+        current_streak,
+        matches: h2h_matches,
+    })
+    .into_response())
+}
+
+/// Every (winner, loser) combination across the two teams of a match: one
+/// for singles, four for doubles.
+fn winner_loser_pairs(m: &MatchRecord) -> impl Iterator<Item = (&str, &str)> {
+    m.winner_ids()
+        .flat_map(move |winner| m.loser_ids().map(move |loser| (winner, loser)))
+}
+
+/// GET /api/rivalries/export.csv — Head-to-head records as a CSV matrix.
+///
+/// One row and one column per player that appears in any rivalry (the same
+/// pairs as `GET /api/rivalries`, i.e. at least 2 games), headed by display
+/// name and ordered by name. Each cell holds the row player's wins over the
+/// column player; pairs without a rivalry and the diagonal are left empty.
+/// Accepts the same `league_id` / `exclude_forfeits` filters.
+pub async fn export_rivalries_csv(
+    State(storage): State<StorageClient>,
+    Query(query): Query<RivalriesQuery>,
+) -> Result<Response, StatsError> {
+    let all_players = players::list_players(&storage).await?;
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let all_matches = filter_matches(all_matches, &query.league_id, query.exclude_forfeits);
+    let rivalries = compute_rivalries(&all_players, &all_matches, query.sort);
+
+    Ok(csv::response("rivalries.csv", rivalries_csv(&rivalries)))
+}
+
+/// The `export_rivalries_csv` matrix for `rivalries`.
+fn rivalries_csv(rivalries: &[RivalryEntry]) -> String {
+    // (row, column) → row's wins over column.
+    let mut wins: HashMap<(&str, &str), u32> = HashMap::new();
+    let mut names: BTreeMap<&str, &str> = BTreeMap::new();
+    for r in rivalries {
+        wins.insert((&r.player1_id, &r.player2_id), r.player1_wins);
+        wins.insert((&r.player2_id, &r.player1_id), r.player2_wins);
+        names.insert(&r.player1_id, &r.player1_name);
+        names.insert(&r.player2_id, &r.player2_name);
+    }
+    let mut players: Vec<(&str, &str)> = names.into_iter().collect();
+    players.sort_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)));
+
+    let mut out = String::new();
+    let header: Vec<String> = std::iter::once(String::new())
+        .chain(players.iter().map(|(_, name)| csv::field(name)))
+        .collect();
+    out.push_str(&header.join(","));
+    out.push_str("\r\n");
+    for (row_id, row_name) in &players {
+        let cells: Vec<String> = std::iter::once(csv::field(row_name))
+            .chain(players.iter().map(|(col_id, _)| {
+                wins.get(&(*row_id, *col_id))
+                    .map(u32::to_string)
+                    .unwrap_or_default()
+            }))
+            .collect();
+        out.push_str(&cells.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
 
+/// Head-to-head records for every pair with at least 2 games, sorted by `sort`.
+fn compute_rivalries(
+    all_players: &[Player],
+    all_matches: &[MatchRecord],
+    sort: RivalrySort,
+) -> Vec<RivalryEntry> {
     let player_names: HashMap<&str, &str> = all_players
         .iter()
         .map(|p| (p.id.as_str(), p.name.as_str()))
         .collect();
 
     // Count head-to-head: key is (player_a, player_b) where a < b lexicographically.
-    // Value is (a_wins_over_b, b_wins_over_a).
-    let mut h2h: HashMap<(String, String), (u32, u32)> = HashMap::new();
+    // Value is (a_wins_over_b, b_wins_over_a, current streak).
+    let mut h2h: HashMap<(String, String), (u32, u32, Option<RivalryStreak>)> = HashMap::new();
 
-    for m in &all_matches {
-        // For each winner-loser pair
-        for winner in [&m.winner1_id, &m.winner2_id] {
-            for loser in [&m.loser1_id, &m.loser2_id] {
-                let (a, b, winner_is_a) = if winner < loser {
-                    (winner.clone(), loser.clone(), true)
-                } else {
-                    (loser.clone(), winner.clone(), false)
-                };
+    // Oldest first, so each pair's streak ends at their latest meeting.
+    let mut chronological: Vec<&MatchRecord> = all_matches.iter().collect();
+    chronological.sort_by_key(|m| m.played_at);
+    for m in chronological {
+        for (winner, loser) in winner_loser_pairs(m) {
+            let (a, b, winner_is_a) = if winner < loser {
+                (winner.to_string(), loser.to_string(), true)
+            } else {
+                (loser.to_string(), winner.to_string(), false)
+            };
 
-                let entry = h2h.entry((a, b)).or_default();
-                if winner_is_a {
-                    entry.0 += 1;
-                } else {
-                    entry.1 += 1;
-                }
+            let entry = h2h.entry((a, b)).or_default();
+            if winner_is_a {
+                entry.0 += 1;
+            } else {
+                entry.1 += 1;
             }
+            extend_streak(&mut entry.2, winner);
         }
     }
 
     let mut rivalries: Vec<RivalryEntry> = h2h
         .into_iter()
-        .filter(|(_, (w1, w2))| w1 + w2 >= 2) // Only show pairs with at least 2 games
-        .map(|((p1, p2), (p1_wins, p2_wins))| RivalryEntry {
+        .filter(|(_, (w1, w2, _))| w1 + w2 >= 2) // Only show pairs with at least 2 games
+        .map(|((p1, p2), (p1_wins, p2_wins, current_streak))| RivalryEntry {
             player1_name: player_names
                 .get(p1.as_str())
                 .unwrap_or(&"Unknown")
@@ -408,29 +1542,1676 @@ pub async fn get_rivalries(
             player2_id: p2,
             player1_wins: p1_wins,
             player2_wins: p2_wins,
+            intensity: rivalry_intensity(p1_wins, p2_wins),
+            current_streak,
         })
         .collect();
 
-    // Sort by total games descending for the most active rivalries first.
-    rivalries.sort_by(|a, b| {
-        (b.player1_wins + b.player2_wins).cmp(&(a.player1_wins + a.player2_wins))
-    });
+    match sort {
+        // Sort by total games descending for the most active rivalries first.
+        RivalrySort::Games => rivalries.sort_by(|a, b| {
+            (b.player1_wins + b.player2_wins).cmp(&(a.player1_wins + a.player2_wins))
+        }),
+        RivalrySort::Intensity => rivalries.sort_by(|a, b| {
+            b.intensity
+                .partial_cmp(&a.intensity)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        }),
+    }
 
-    Ok(Json(rivalries))
+    rivalries
 }
 
-/// Calculate the current streak from a list of results (newest first).
+/// Build the compact recent-match view from one player's perspective.
+fn summarize_match(m: &MatchRecord, is_winner: bool) -> RecentMatchSummary {
+    let score = match m.scores() {
+        Some((w, l)) if is_winner => Some(format!("{w}-{l}")),
+        Some((w, l)) => Some(format!("{l}-{w}")),
+        None => None,
+    };
+
+    RecentMatchSummary {
+        id: m.id.clone(),
+        played_at: m.played_at,
+        result: if is_winner { "win" } else { "loss" },
+        score,
+        forfeit: m.forfeit,
+    }
+}
+
+/// A player's record for one calendar month.
+#[derive(Debug, Clone, Serialize)]
+pub struct MonthlyRecord {
+    /// Month label, e.g. "2024-01".
+    pub month: String,
+    pub wins: u32,
+    pub losses: u32,
+    pub win_rate: f64,
+}
+
+/// Query parameters for the monthly series endpoint.
+#[derive(Deserialize)]
+pub struct MonthlyQuery {
+    pub league_id: Option<String>,
+    #[serde(default)]
+    pub exclude_forfeits: bool,
+    /// When true, months without games between the first and last active
+    /// month are included with zero games instead of omitted.
+    #[serde(default)]
+    pub fill: bool,
+}
+
+/// GET /api/players/{id}/monthly — Win/loss record per calendar month.
 ///
-/// Returns positive for a winning streak, negative for a losing streak.
-/// E.g., [true, true, false, ...] → 2 (two wins in a row).
-///       [false, false, false, true, ...] → -3 (three losses in a row).
-fn calculate_streak(results: &[bool]) -> i32 {
-    if results.is_empty() {
-        return 0;
+/// Months are determined in the configured `TIMEZONE`, oldest first. Accepts
+/// optional `?league_id=xxx` and `?fill=true` (zero-fill empty months).
+pub async fn get_player_monthly(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    Path(player_id): Path<String>,
+    Query(query): Query<MonthlyQuery>,
+) -> Result<Json<Vec<MonthlyRecord>>, StatsError> {
+    let player = players::get_player(&storage, &player_id).await?;
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let all_matches = filter_matches(all_matches, &query.league_id, query.exclude_forfeits);
+
+    Ok(Json(monthly_records(
+        &player.id,
+        &all_matches,
+        config.timezone,
+        query.fill,
+    )))
+}
+
+/// Bucket a player's matches by calendar month (in `tz`), oldest first.
+fn monthly_records(
+    player_id: &str,
+    all_matches: &[MatchRecord],
+    tz: Tz,
+    fill: bool,
+) -> Vec<MonthlyRecord> {
+    // (year, month) → (wins, losses). BTreeMap keeps months in order.
+    let mut buckets: BTreeMap<(i32, u32), (u32, u32)> = BTreeMap::new();
+
+    for m in all_matches.iter().filter(|m| m.has_player(player_id)) {
+        let local = m.played_at.with_timezone(&tz);
+        let bucket = buckets.entry((local.year(), local.month())).or_default();
+        if m.is_winner(player_id) {
+            bucket.0 += 1;
+        } else {
+            bucket.1 += 1;
+        }
     }
 
-    let first = results[0];
-    let count = results.iter().take_while(|&&r| r == first).count() as i32;
+    if fill {
+        let bounds = buckets
+            .keys()
+            .next()
+            .copied()
+            .zip(buckets.keys().next_back().copied());
+        if let Some(((mut year, mut month), last)) = bounds {
+            while (year, month) < last {
+                buckets.entry((year, month)).or_default();
+                (year, month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+            }
+        }
+    }
 
-    if first { count } else { -count }
+    buckets
+        .into_iter()
+        .map(|((year, month), (wins, losses))| {
+            let total = wins + losses;
+            MonthlyRecord {
+                month: format!("{year:04}-{month:02}"),
+                wins,
+                losses,
+                win_rate: if total > 0 {
+                    wins as f64 / total as f64
+                } else {
+                    0.0
+                },
+            }
+        })
+        .collect()
+}
+
+/// The best and worst months by win rate among those with at least
+/// `min_games` games. Ties go to the month with more games, then the later
+/// one.
+fn month_extremes(
+    months: Vec<MonthlyRecord>,
+    min_games: u32,
+) -> (Option<MonthlyRecord>, Option<MonthlyRecord>) {
+    let qualifying: Vec<MonthlyRecord> = months
+        .into_iter()
+        .filter(|m| m.wins + m.losses >= min_games.max(1))
+        .collect();
+    let by_games = |m: &MonthlyRecord| m.wins + m.losses;
+
+    let best = qualifying
+        .iter()
+        .max_by(|a, b| {
+            a.win_rate
+                .total_cmp(&b.win_rate)
+                .then(by_games(a).cmp(&by_games(b)))
+        })
+        .cloned();
+    let worst = qualifying
+        .iter()
+        .max_by(|a, b| {
+            b.win_rate
+                .total_cmp(&a.win_rate)
+                .then(by_games(a).cmp(&by_games(b)))
+        })
+        .cloned();
+    (best, worst)
+}
+
+/// The player whose win rate improved the most between two periods.
+#[derive(Debug, Serialize)]
+pub struct ComebackPlayer {
+    pub player_id: String,
+    pub player_name: String,
+    pub avatar_emoji: String,
+    /// First month of the earlier period, e.g. "2024-01".
+    pub previous_period_start: String,
+    /// First month of the current period.
+    pub current_period_start: String,
+    pub previous_win_rate: f64,
+    pub current_win_rate: f64,
+    /// `current_win_rate - previous_win_rate`.
+    pub delta: f64,
+    pub previous_games: u32,
+    pub current_games: u32,
+}
+
+/// GET /api/stats/comeback-player — Biggest win-rate improvement.
+///
+/// Compares the current period (the last `COMEBACK_PERIOD_MONTHS` calendar
+/// months, including this one, in `TIMEZONE`) against the period before it.
+/// Players need `COMEBACK_MIN_GAMES` in both periods to qualify. Returns
+/// `null` when nobody qualifies or nobody improved.
+pub async fn get_comeback_player(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<Option<ComebackPlayer>>, StatsError> {
+    let all_players = players::list_players(&storage).await?;
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let all_matches = filter_matches(all_matches, &query.league_id, query.exclude_forfeits);
+
+    Ok(Json(find_comeback_player(&config, &all_players, &all_matches, Utc::now())))
+}
+
+/// The player whose win rate rose the most from the previous period to the
+/// one containing `now`, if anyone qualifies and improved.
+fn find_comeback_player(
+    config: &AppConfig,
+    all_players: &[Player],
+    all_matches: &[MatchRecord],
+    now: DateTime<Utc>,
+) -> Option<ComebackPlayer> {
+    let tz = config.timezone;
+    let period = config.comeback_period_months as i32;
+    let current_start = month_index(now, tz) - (period - 1);
+    let previous_start = current_start - period;
+
+    // player → [(wins, losses) in previous period, (wins, losses) in current]
+    let mut records: HashMap<&str, [(u32, u32); 2]> = HashMap::new();
+    for m in all_matches {
+        let idx = month_index(m.played_at, tz);
+        let slot = if idx >= current_start {
+            1
+        } else if idx >= previous_start {
+            0
+        } else {
+            continue;
+        };
+        for id in m.winner_ids() {
+            records.entry(id).or_default()[slot].0 += 1;
+        }
+        for id in m.loser_ids() {
+            records.entry(id).or_default()[slot].1 += 1;
+        }
+    }
+
+    // Zero-safe: with COMEBACK_MIN_GAMES=0 a period may have no games.
+    let rate = |(w, l): (u32, u32)| {
+        if w + l > 0 {
+            w as f64 / (w + l) as f64
+        } else {
+            0.0
+        }
+    };
+    all_players
+        .iter()
+        .filter_map(|p| {
+            let [previous, current] = *records.get(p.id.as_str())?;
+            let previous_games = previous.0 + previous.1;
+            let current_games = current.0 + current.1;
+            if previous_games < config.comeback_min_games
+                || current_games < config.comeback_min_games
+            {
+                return None;
+            }
+            let delta = rate(current) - rate(previous);
+            (delta > 0.0).then(|| ComebackPlayer {
+                player_id: p.id.clone(),
+                player_name: p.name.clone(),
+                avatar_emoji: p.avatar_emoji.clone(),
+                previous_period_start: month_label(previous_start),
+                current_period_start: month_label(current_start),
+                previous_win_rate: rate(previous),
+                current_win_rate: rate(current),
+                delta,
+                previous_games,
+                current_games,
+            })
+        })
+        .max_by(|a, b| {
+            a.delta
+                .partial_cmp(&b.delta)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+}
+
+/// How much a loss counts against a win in the MVP score.
+const MVP_LOSS_PENALTY: f64 = 0.5;
+
+/// Query parameters for the MVP endpoint.
+#[derive(Deserialize)]
+pub struct MvpQuery {
+    /// Calendar month as `YYYY-MM`; defaults to the current month.
+    pub month: Option<String>,
+    pub league_id: Option<String>,
+    #[serde(default)]
+    pub exclude_forfeits: bool,
+}
+
+/// A month's most valuable player.
+#[derive(Debug, Serialize)]
+pub struct Mvp {
+    pub month: String,
+    pub player: Player,
+    /// Their record within the month.
+    pub record: MonthlyRecord,
+    /// `wins - MVP_LOSS_PENALTY * losses`.
+    pub score: f64,
+}
+
+/// GET /api/mvp?month=YYYY-MM — Player of the month.
+///
+/// Among players with at least `MVP_MIN_GAMES` in the calendar month (in
+/// `TIMEZONE`), the one with the highest `wins - 0.5 * losses`: winning a lot
+/// matters most, but not at any cost. Ties go to the better win rate, then to
+/// more games. `204 No Content` when nobody qualifies, including months
+/// without matches. Accepts the usual `league_id` / `exclude_forfeits`.
+pub async fn get_mvp(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    Query(query): Query<MvpQuery>,
+) -> Result<Response, StatsError> {
+    let tz = config.timezone;
+    let month = match &query.month {
+        Some(month) => match NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d") {
+            Ok(first) => first.year() * 12 + first.month0() as i32,
+            Err(_) => {
+                return Ok((
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({"error": "month must be formatted as YYYY-MM"})),
+                )
+                    .into_response());
+            }
+        },
+        None => month_index(Utc::now(), tz),
+    };
+
+    let all_players = players::list_players(&storage).await?;
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let mut month_matches =
+        filter_matches(all_matches, &query.league_id, query.exclude_forfeits);
+    month_matches.retain(|m| month_index(m.played_at, tz) == month);
+
+    let label = month_label(month);
+    let score = |r: &MonthlyRecord| r.wins as f64 - MVP_LOSS_PENALTY * r.losses as f64;
+    let mvp = all_players
+        .iter()
+        .filter_map(|p| {
+            // One bucket at most, since every match is in the same month.
+            let record = monthly_records(&p.id, &month_matches, tz, false).pop()?;
+            (record.wins + record.losses >= config.mvp_min_games.max(1)).then_some((p, record))
+        })
+        .max_by(|(_, a), (_, b)| {
+            score(a)
+                .total_cmp(&score(b))
+                .then(a.win_rate.total_cmp(&b.win_rate))
+                .then((a.wins + a.losses).cmp(&(b.wins + b.losses)))
+        });
+
+    let Some((player, record)) = mvp else {
+        return Ok(StatusCode::NO_CONTENT.into_response());
+    };
+    Ok(Json(Mvp {
+        month: label,
+        player: player.clone(),
+        score: score(&record),
+        record,
+    })
+    .into_response())
+}
+
+/// Months since year 0 for `t` in `tz` — makes period arithmetic simple.
+fn month_index(t: DateTime<Utc>, tz: Tz) -> i32 {
+    let local = t.with_timezone(&tz);
+    local.year() * 12 + local.month0() as i32
+}
+
+/// Inverse of `month_index`, formatted like "2024-01".
+fn month_label(index: i32) -> String {
+    format!("{:04}-{:02}", index.div_euclid(12), index.rem_euclid(12) + 1)
+}
+
+/// All-time superlatives for the records page. Each record is `null` until
+/// there's data to set it; ties go to whoever got there first.
+#[derive(Debug, Serialize)]
+pub struct RecordBook {
+    pub longest_win_streak: Option<StreakRecord>,
+    /// Largest winning margin (forfeits excluded).
+    pub biggest_blowout: Option<ScoreRecord>,
+    /// Highest score either team put up in one match (forfeits excluded).
+    pub highest_score: Option<ScoreRecord>,
+    /// Most matches one player played on a single calendar day.
+    pub most_games_in_a_day: Option<DayRecord>,
+    /// The partnership that has played together the most.
+    pub most_frequent_pairing: Option<PairingRecord>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StreakRecord {
+    pub player_id: String,
+    pub player_name: String,
+    pub length: u32,
+    /// The match that brought the streak to its record length.
+    pub match_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoreRecord {
+    pub match_id: String,
+    pub played_at: DateTime<Utc>,
+    /// One ID each for singles, two for doubles.
+    pub winner_ids: Vec<String>,
+    pub loser_ids: Vec<String>,
+    pub winner_score: i32,
+    pub loser_score: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DayRecord {
+    pub player_id: String,
+    pub player_name: String,
+    /// Calendar day in `TIMEZONE`.
+    pub date: NaiveDate,
+    pub games: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PairingRecord {
+    pub player1_id: String,
+    pub player1_name: String,
+    pub player2_id: String,
+    pub player2_name: String,
+    pub games: u32,
+}
+
+/// GET /api/stats/records — The all-time record book.
+///
+/// Accepts optional `?league_id=xxx` to scope records to a league.
+pub async fn get_record_book(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<RecordBook>, StatsError> {
+    let all_players = players::list_players(&storage).await?;
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let all_matches = filter_matches(all_matches, &query.league_id, query.exclude_forfeits);
+
+    Ok(Json(compute_record_book(
+        &all_matches,
+        &all_players,
+        config.timezone,
+    )))
+}
+
+/// Build the record book in a single oldest-first pass over `matches`
+/// (expected newest-first, as returned by storage).
+fn compute_record_book(matches: &[MatchRecord], players: &[Player], tz: Tz) -> RecordBook {
+    let names: HashMap<&str, &str> = players
+        .iter()
+        .map(|p| (p.id.as_str(), p.name.as_str()))
+        .collect();
+    let name_of = |id: &str| names.get(id).copied().unwrap_or(id).to_string();
+
+    let mut streaks: HashMap<&str, u32> = HashMap::new();
+    let mut best_streak: Option<(&str, u32, &str)> = None; // (player, length, match)
+    let mut blowout: Option<&MatchRecord> = None;
+    let mut highest: Option<&MatchRecord> = None;
+    let mut daily: HashMap<(&str, NaiveDate), u32> = HashMap::new();
+    let mut best_day: Option<(&str, NaiveDate, u32)> = None;
+    let mut pairings: HashMap<(String, String), u32> = HashMap::new();
+    let mut best_pairing: Option<((String, String), u32)> = None;
+
+    for m in matches.iter().rev() {
+        for id in m.winner_ids() {
+            let streak = streaks.entry(id).or_default();
+            *streak += 1;
+            if best_streak.is_none_or(|(_, best, _)| *streak > best) {
+                best_streak = Some((id, *streak, m.id.as_str()));
+            }
+        }
+        for id in m.loser_ids() {
+            streaks.insert(id, 0);
+        }
+
+        if let Some((w, l)) = m.scores() {
+            if blowout
+                .and_then(MatchRecord::scores)
+                .is_none_or(|(bw, bl)| w - l > bw - bl)
+            {
+                blowout = Some(m);
+            }
+            if highest
+                .and_then(MatchRecord::scores)
+                .is_none_or(|(hw, hl)| w.max(l) > hw.max(hl))
+            {
+                highest = Some(m);
+            }
+        }
+
+        let date = m.played_at.with_timezone(&tz).date_naive();
+        for id in m.player_ids() {
+            let games = daily.entry((id, date)).or_default();
+            *games += 1;
+            if best_day.is_none_or(|(_, _, best)| *games > best) {
+                best_day = Some((id, date, *games));
+            }
+        }
+
+        for (a, b) in m.pairs().into_iter().flatten() {
+            let key = elo::pair_key(a, b);
+            let games = pairings.entry(key.clone()).or_default();
+            *games += 1;
+            if best_pairing.as_ref().is_none_or(|(_, best)| *games > *best) {
+                best_pairing = Some((key, *games));
+            }
+        }
+    }
+
+    let score_record = |m: &MatchRecord| {
+        let (winner_score, loser_score) = m.scores()?;
+        Some(ScoreRecord {
+            match_id: m.id.clone(),
+            played_at: m.played_at,
+            winner_ids: m.winner_ids().map(str::to_string).collect(),
+            loser_ids: m.loser_ids().map(str::to_string).collect(),
+            winner_score,
+            loser_score,
+        })
+    };
+
+    RecordBook {
+        longest_win_streak: best_streak.map(|(id, length, match_id)| StreakRecord {
+            player_id: id.to_string(),
+            player_name: name_of(id),
+            length,
+            match_id: match_id.to_string(),
+        }),
+        biggest_blowout: blowout.and_then(score_record),
+        highest_score: highest.and_then(score_record),
+        most_games_in_a_day: best_day.map(|(id, date, games)| DayRecord {
+            player_id: id.to_string(),
+            player_name: name_of(id),
+            date,
+            games,
+        }),
+        most_frequent_pairing: best_pairing.map(|((a, b), games)| PairingRecord {
+            player1_name: name_of(&a),
+            player2_name: name_of(&b),
+            player1_id: a,
+            player2_id: b,
+            games,
+        }),
+    }
+}
+
+/// A player's individual ELO rating.
+#[derive(Debug, Serialize)]
+pub struct PlayerRating {
+    pub player_id: String,
+    pub player_name: String,
+    pub avatar_emoji: String,
+    pub rating: f64,
+}
+
+/// GET /api/ratings — Individual ELO ratings, highest first.
+///
+/// Without `?league_id`, ratings are replayed over all matches from the base.
+/// With `?league_id=xxx`, every league up to and including that one is
+/// replayed as a season in `created_at` order, carrying `ELO_CARRYOVER` of
+/// each season's finals into the next (see `stats::elo`). Matches without a
+/// league don't count toward seasonal ratings.
+pub async fn get_ratings(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<Vec<PlayerRating>>, StatsError> {
+    let all_players = players::list_players(&storage).await?;
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let all_matches = filter_matches(all_matches, &None, query.exclude_forfeits);
+
+    let ratings = match &query.league_id {
+        None => elo::compute_elo(&all_matches, &HashMap::new()),
+        Some(league_id) => {
+            let mut all_leagues = leagues::list_leagues(&storage).await?;
+            all_leagues.sort_by_key(|l| l.created_at);
+            let position = all_leagues
+                .iter()
+                .position(|l| &l.id == league_id)
+                .ok_or_else(|| LeagueStorageError::NotFound(league_id.clone()))?;
+
+            let seasons: Vec<Vec<MatchRecord>> = all_leagues[..=position]
+                .iter()
+                .map(|l| filter_by_league(all_matches.clone(), &Some(l.id.clone())))
+                .collect();
+            elo::compute_season_elo(&seasons, config.elo_carryover)
+        }
+    };
+
+    let mut entries: Vec<PlayerRating> = all_players
+        .into_iter()
+        .map(|p| PlayerRating {
+            rating: ratings.get(&p.id).copied().unwrap_or(elo::BASE_RATING),
+            player_id: p.id,
+            player_name: p.name,
+            avatar_emoji: p.avatar_emoji,
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        b.rating
+            .partial_cmp(&a.rating)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    Ok(Json(entries))
+}
+
+/// One of a player's matches with the rating change it caused.
+#[derive(Debug, Serialize)]
+pub struct EloHistoryEntry {
+    #[serde(rename = "match")]
+    pub match_record: MatchRecord,
+    #[serde(flatten)]
+    pub change: elo::EloChange,
+}
+
+/// Query parameters for the ELO history endpoint.
+#[derive(Deserialize)]
+pub struct EloHistoryQuery {
+    #[serde(default)]
+    pub exclude_forfeits: bool,
+}
+
+/// GET /api/players/{id}/elo-history — Per-match ELO changes, newest first.
+///
+/// Replays the all-time ratings (as `/api/ratings` without `league_id`) and
+/// reports `rating_before`, `delta` and `rating_after` for every match the
+/// player took part in, so the latest `rating_after` is their current rating.
+pub async fn get_player_elo_history(
+    State(storage): State<StorageClient>,
+    Path(player_id): Path<String>,
+    Query(query): Query<EloHistoryQuery>,
+) -> Result<Json<Vec<EloHistoryEntry>>, StatsError> {
+    let player = players::get_player(&storage, &player_id).await?;
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let all_matches = filter_matches(all_matches, &None, query.exclude_forfeits);
+
+    Ok(Json(
+        elo::player_rating_changes(&all_matches, &player.id)
+            .into_iter()
+            .map(|(m, change)| EloHistoryEntry {
+                match_record: m.clone(),
+                change,
+            })
+            .collect(),
+    ))
+}
+
+/// Query parameters for the prediction endpoint.
+#[derive(Deserialize)]
+pub struct PredictQuery {
+    /// Comma-separated player IDs of the first team, e.g. `alice,bob`.
+    pub team1: String,
+    /// Comma-separated player IDs of the second team.
+    pub team2: String,
+}
+
+/// GET /api/predict?team1=a,b&team2=c,d — Pre-game odds.
+///
+/// Uses all-time individual ELO (see `stats::elo::predict` for the formula).
+/// Returns `400` unless each team names exactly two known players.
+pub async fn get_prediction(
+    State(storage): State<StorageClient>,
+    Query(query): Query<PredictQuery>,
+) -> Result<Json<elo::Prediction>, StatsError> {
+    let all_players = players::list_players(&storage).await?;
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let ratings = elo::compute_elo(&all_matches, &HashMap::new());
+
+    let team1 = team_ratings(&query.team1, &all_players, &ratings)?;
+    let team2 = team_ratings(&query.team2, &all_players, &ratings)?;
+    Ok(Json(elo::predict(team1, team2)))
+}
+
+/// Parse a `a,b` team parameter into its two players' ratings. Unknown
+/// players and teams that aren't exactly two IDs are `Invalid`.
+fn team_ratings(
+    team: &str,
+    all_players: &[Player],
+    ratings: &HashMap<String, f64>,
+) -> Result<[f64; 2], PlayerStorageError> {
+    let ids: Vec<&str> = team.split(',').map(str::trim).collect();
+    let [a, b] = ids[..] else {
+        return Err(PlayerStorageError::Invalid(format!(
+            "team '{team}' must list exactly two player IDs"
+        )));
+    };
+    let rating = |id: &str| {
+        if !all_players.iter().any(|p| p.id == id) {
+            return Err(PlayerStorageError::Invalid(format!("unknown player '{id}'")));
+        }
+        Ok(ratings.get(id).copied().unwrap_or(elo::BASE_RATING))
+    };
+    Ok([rating(a)?, rating(b)?])
+}
+
+/// Default and maximum number of suggestions from the matchups endpoint.
+const DEFAULT_MATCHUP_SUGGESTIONS: usize = 3;
+const MAX_MATCHUP_SUGGESTIONS: usize = 20;
+
+/// Query parameters for the matchup suggestions endpoint.
+#[derive(Deserialize)]
+pub struct MatchupQuery {
+    /// Comma-separated IDs of the players available to play. Defaults to
+    /// every player.
+    pub players: Option<String>,
+    /// Only count repetition within this league (balance still uses
+    /// all-time ELO).
+    pub league_id: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// GET /api/suggestions/matchups — Suggested 2v2 games that spread out
+/// partnerships and opponents while staying close (see `stats::scheduler`).
+///
+/// `?players=a,b,c,d,e` restricts suggestions to who's actually around;
+/// unknown IDs are a 400. Fewer than four players gives an empty list.
+pub async fn get_matchup_suggestions(
+    State(storage): State<StorageClient>,
+    Query(query): Query<MatchupQuery>,
+) -> Result<Json<Vec<scheduler::MatchupSuggestion>>, StatsError> {
+    let all_players = players::list_players(&storage).await?;
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let ratings = elo::compute_elo(&all_matches, &HashMap::new());
+
+    let available: Vec<&str> = match &query.players {
+        Some(list) => {
+            let ids: Vec<&str> = list
+                .split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .collect();
+            if let Some(unknown) = ids.iter().find(|&&id| !all_players.iter().any(|p| p.id == id)) {
+                let message = format!("unknown player '{unknown}'");
+                return Err(PlayerStorageError::Invalid(message).into());
+            }
+            ids
+        }
+        None => all_players.iter().map(|p| p.id.as_str()).collect(),
+    };
+
+    let history = filter_by_league(all_matches, &query.league_id);
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_MATCHUP_SUGGESTIONS)
+        .min(MAX_MATCHUP_SUGGESTIONS);
+    Ok(Json(scheduler::suggest_matchups(&available, &history, &ratings, limit)))
+}
+
+/// A pair's ELO rating, with player names resolved for display.
+#[derive(Debug, Serialize)]
+pub struct TeamRatingEntry {
+    pub player1_id: String,
+    pub player1_name: String,
+    pub player2_id: String,
+    pub player2_name: String,
+    pub rating: f64,
+    pub wins: u32,
+    pub losses: u32,
+}
+
+/// GET /api/teams/ratings — Doubles pairs ranked by team ELO.
+///
+/// Each unordered pair is rated as its own competitor (see `stats::elo`).
+/// Accepts optional `?league_id=xxx` to filter stats to a specific league.
+pub async fn get_team_ratings(
+    State(storage): State<StorageClient>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<Vec<TeamRatingEntry>>, StatsError> {
+    let all_players = players::list_players(&storage).await?;
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let all_matches = filter_matches(all_matches, &query.league_id, query.exclude_forfeits);
+
+    let player_names: HashMap<&str, &str> = all_players
+        .iter()
+        .map(|p| (p.id.as_str(), p.name.as_str()))
+        .collect();
+    let name_of = |id: &str| player_names.get(id).unwrap_or(&"Unknown").to_string();
+
+    let entries = elo::compute_team_elo(&all_matches)
+        .into_iter()
+        .map(|t| TeamRatingEntry {
+            player1_name: name_of(&t.player1_id),
+            player2_name: name_of(&t.player2_id),
+            player1_id: t.player1_id,
+            player2_id: t.player2_id,
+            rating: t.rating,
+            wins: t.wins,
+            losses: t.losses,
+        })
+        .collect();
+
+    Ok(Json(entries))
+}
+
+/// Games a pair must have played together to appear in `/api/duos` — the
+/// same bar as best partners and rivalries.
+const MIN_DUO_GAMES: u32 = 2;
+
+/// A doubles partnership's combined record.
+#[derive(Debug, Serialize)]
+pub struct DuoEntry {
+    pub player1_id: String,
+    pub player1_name: String,
+    pub player2_id: String,
+    pub player2_name: String,
+    pub wins: u32,
+    pub losses: u32,
+    pub games_together: u32,
+    pub win_rate: f64,
+}
+
+/// GET /api/duos — Doubles partnerships ranked by win rate together.
+///
+/// Every pair that has played on the same side at least `MIN_DUO_GAMES`
+/// times, best win rate first (ties: more games first). Unlike
+/// `/api/teams/ratings` this ignores opponent strength. Accepts the usual
+/// `?league_id=` / `?exclude_forfeits=` filters.
+pub async fn get_duos(
+    State(storage): State<StorageClient>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<Vec<DuoEntry>>, StatsError> {
+    let all_players = players::list_players(&storage).await?;
+    let all_matches = matches::list_matches(&storage, None).await?;
+    let all_matches = filter_matches(all_matches, &query.league_id, query.exclude_forfeits);
+
+    let player_names: HashMap<&str, &str> = all_players
+        .iter()
+        .map(|p| (p.id.as_str(), p.name.as_str()))
+        .collect();
+    let name_of = |id: &str| player_names.get(id).unwrap_or(&"Unknown").to_string();
+
+    // Sorted pair key → (wins, losses) together.
+    let mut records: HashMap<(String, String), (u32, u32)> = HashMap::new();
+    for m in &all_matches {
+        let Some([winners, losers]) = m.pairs() else {
+            continue;
+        };
+        records.entry(elo::pair_key(winners.0, winners.1)).or_default().0 += 1;
+        records.entry(elo::pair_key(losers.0, losers.1)).or_default().1 += 1;
+    }
+
+    let mut duos: Vec<DuoEntry> = records
+        .into_iter()
+        .filter(|(_, (w, l))| w + l >= MIN_DUO_GAMES)
+        .map(|((p1, p2), (wins, losses))| DuoEntry {
+            player1_name: name_of(&p1),
+            player2_name: name_of(&p2),
+            player1_id: p1,
+            player2_id: p2,
+            wins,
+            losses,
+            games_together: wins + losses,
+            win_rate: wins as f64 / (wins + losses) as f64,
+        })
+        .collect();
+
+    duos.sort_by(|a, b| {
+        b.win_rate
+            .total_cmp(&a.win_rate)
+            .then(b.games_together.cmp(&a.games_together))
+            .then_with(|| (&a.player1_id, &a.player2_id).cmp(&(&b.player1_id, &b.player2_id)))
+    });
+
+    Ok(Json(duos))
+}
+
+/// Calculate the current streak from a list of results (newest first).
+///
+/// Returns positive for a winning streak, negative for a losing streak.
+/// E.g., [true, true, false, ...] → 2 (two wins in a row).
+///       [false, false, false, true, ...] → -3 (three losses in a row).
+fn calculate_streak(results: &[bool]) -> i32 {
+    if results.is_empty() {
+        return 0;
+    }
+
+    let first = results[0];
+    let count = results.iter().take_while(|&&r| r == first).count() as i32;
+
+    if first { count } else { -count }
+}
+
+/// The longest runs of wins and of losses anywhere in `results`, as
+/// `(longest_win_streak, longest_loss_streak)`.
+///
+/// `results` is newest-first like everywhere else; a run is just as long read
+/// in either direction, so this scans it as is. E.g., [true, false, false,
+/// true, true, true] → (3, 2); no results → (0, 0).
+fn longest_streaks(results: &[bool]) -> (u32, u32) {
+    let (mut longest_win, mut longest_loss) = (0, 0);
+    let mut run = 0;
+    for (i, &won) in results.iter().enumerate() {
+        run = if i > 0 && results[i - 1] == won { run + 1 } else { 1 };
+        let longest = if won { &mut longest_win } else { &mut longest_loss };
+        *longest = (*longest).max(run);
+    }
+    (longest_win, longest_loss)
+}
+
+/// Like `calculate_streak`, but the run survives up to `tolerance` results
+/// going the other way; those don't count toward its length.
+///
+/// Both directions are tried and the longer run wins (ties go to the newest
+/// result's direction), so a single loss after ten wins still reads as a
+/// 10-game winning streak with a tolerance of 1.
+/// E.g., tolerance 1: [false, true, true, true] → 3, [true, false, true] → 2.
+/// With a tolerance of 0 this is exactly `calculate_streak`.
+fn calculate_resilient_streak(results: &[bool], tolerance: u32) -> i32 {
+    let Some(&first) = results.first() else {
+        return 0;
+    };
+
+    let run = |direction: bool| {
+        let mut count = 0;
+        let mut misses = 0;
+        for &r in results {
+            if r == direction {
+                count += 1;
+            } else {
+                misses += 1;
+                if misses > tolerance {
+                    break;
+                }
+            }
+        }
+        count
+    };
+
+    let (won, lost) = (run(true), run(false));
+    if won > lost || (won == lost && first) { won } else { -lost }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use crate::models::match_record::NewMatch;
+
+    fn user(oid: &str, player_id: Option<&str>) -> User {
+        User {
+            oid: oid.to_string(),
+            name: oid.to_string(),
+            email: format!("{oid}@example.com"),
+            role: "user".to_string(),
+            player_id: player_id.map(str::to_string),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn player(id: &str) -> Player {
+        Player {
+            id: id.to_string(),
+            name: id.to_string(),
+            nickname: String::new(),
+            avatar_emoji: "🏓".to_string(),
+            color: None,
+            active: true,
+        }
+    }
+
+    /// A singles match played `minutes_ago`.
+    fn game(winner: &str, loser: &str, minutes_ago: i64) -> MatchRecord {
+        game_at(winner, loser, Utc::now() - Duration::minutes(minutes_ago))
+    }
+
+    fn game_at(winner: &str, loser: &str, played_at: DateTime<Utc>) -> MatchRecord {
+        MatchRecord::new(NewMatch {
+            winner1_id: winner.to_string(),
+            loser1_id: loser.to_string(),
+            played_at,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn stats_for(id: &str, history: &[MatchRecord]) -> PlayerStats {
+        let players = [player("alice"), player("bob"), player("carol"), player("dave")];
+        let config = AppConfig::for_tests();
+        compute_player_stats(&config, player(id), history, &players, Some(RecentDetail::Full))
+    }
+
+    /// `wins` straight wins for alice over bob, newest first as storage
+    /// returns them.
+    fn winning_run(wins: i64) -> Vec<MatchRecord> {
+        (0..wins).map(|i| game("alice", "bob", i)).collect()
+    }
+
+    fn entry<'a>(board: &'a [LeaderboardEntry], id: &str) -> &'a LeaderboardEntry {
+        board.iter().find(|e| e.player_id == id).unwrap()
+    }
+
+    #[test]
+    fn on_fire_flips_exactly_at_the_threshold() {
+        let config = AppConfig::for_tests();
+        let players = [player("alice"), player("bob")];
+        let threshold = config.hot_streak_threshold as i64;
+
+        let below = compute_leaderboard(&players, &winning_run(threshold - 1), &config);
+        assert!(!entry(&below, "alice").on_fire);
+
+        let at = compute_leaderboard(&players, &winning_run(threshold), &config);
+        assert_eq!(entry(&at, "alice").streak, threshold as i32);
+        assert!(entry(&at, "alice").on_fire);
+        assert!(!entry(&at, "bob").on_fire, "a losing streak is never on fire");
+    }
+
+    #[test]
+    fn export_includes_every_match_not_just_the_recent_ten() {
+        let config = AppConfig::for_tests();
+        let players = [player("alice"), player("bob"), player("carol")];
+        let mut history: Vec<MatchRecord> = (0..15).map(|i| game("alice", "bob", i)).collect();
+        history.push(game("bob", "carol", 20));
+
+        let export = build_player_export(&config, player("alice"), history, &players);
+
+        assert_eq!(export.stats.recent_matches.len(), 10);
+        assert_eq!(export.matches.len(), 15);
+        assert!(export.matches.iter().all(|m| m.has_player("alice")));
+    }
+
+    #[test]
+    fn days_since_last_win_and_last_game() {
+        let days_ago = |days| Utc::now() - Duration::days(days) - Duration::hours(1);
+        let history = [
+            game_at("bob", "alice", days_ago(1)),
+            game_at("alice", "bob", days_ago(5)),
+            game_at("alice", "bob", days_ago(9)),
+        ];
+        let stats = stats_for("alice", &history);
+        assert_eq!(stats.days_since_last_win, Some(5));
+        assert_eq!(stats.days_since_last_played, Some(1));
+    }
+
+    #[test]
+    fn days_since_are_none_without_wins_or_games() {
+        let history = [game("bob", "alice", 60)];
+        let alice = stats_for("alice", &history);
+        assert_eq!(alice.days_since_last_win, None);
+        assert_eq!(alice.days_since_last_played, Some(0));
+        let carol = stats_for("carol", &history);
+        assert_eq!(carol.days_since_last_played, None);
+    }
+
+    #[test]
+    fn unlinking_a_player_stops_me_stats_resolving_it_for_the_old_subject() {
+        let config = AppConfig::for_tests();
+        let old_account = user("old-oid", Some("alice"));
+        assert_eq!(my_player(&config, &old_account), MyPlayer::Linked("alice"));
+
+        let users = vec![old_account, user("bob-oid", Some("bob")), user("new-oid", None)];
+        let unlinked = users::clear_player_links(users, "alice");
+
+        assert_eq!(unlinked.len(), 1, "only the user linked to alice changes");
+        assert_eq!(unlinked[0].oid, "old-oid");
+        assert_eq!(unlinked[0].player_id, None);
+        assert_eq!(my_player(&config, &unlinked[0]), MyPlayer::Unlinked);
+    }
+
+    #[test]
+    fn unlinked_users_get_a_player_created_when_enabled() {
+        let mut config = AppConfig::for_tests();
+        config.auto_create_players = true;
+        assert_eq!(my_player(&config, &user("oid", None)), MyPlayer::AutoCreate);
+    }
+
+    #[test]
+    fn summary_recent_matches_are_compact_and_from_the_players_side() {
+        let config = AppConfig::for_tests();
+        let players = [player("alice"), player("bob")];
+        let history = [MatchRecord::new(NewMatch {
+            winner1_id: "alice".to_string(),
+            loser1_id: "bob".to_string(),
+            winner_score: Some(11),
+            loser_score: Some(7),
+            played_at: Utc::now(),
+            ..Default::default()
+        })
+        .unwrap()];
+        let recent = |id, detail| {
+            let stats = compute_player_stats(&config, player(id), &history, &players, Some(detail));
+            serde_json::to_value(&stats.recent_matches[0]).unwrap()
+        };
+
+        let full = recent("bob", RecentDetail::Full);
+        assert_eq!(full, serde_json::to_value(&history[0]).unwrap());
+
+        let summary = recent("bob", RecentDetail::Summary);
+        let mut keys: Vec<&str> = summary.as_object().unwrap().keys().map(|k| k.as_str()).collect();
+        keys.sort();
+        assert_eq!(keys, ["forfeit", "id", "played_at", "result", "score"]);
+        assert_eq!(summary["id"], full["id"]);
+        assert_eq!(summary["result"], "loss");
+        assert_eq!(summary["score"], "7-11");
+    }
+
+    #[test]
+    fn the_last_timeline_snapshot_is_the_current_leaderboard() {
+        let mut config = AppConfig::for_tests();
+        config.timeline_max_snapshots = 4;
+        let players = [player("alice"), player("bob"), player("carol")];
+        let history: Vec<MatchRecord> = (0..10)
+            .map(|i| match i % 3 {
+                0 => game("alice", "bob", i),
+                1 => game("carol", "alice", i),
+                _ => game("bob", "carol", i),
+            })
+            .collect();
+
+        let timeline = compute_timeline(&players, &history, &config);
+
+        assert_eq!(timeline.len(), 4, "sampled down to the cap");
+        let last = timeline.last().unwrap();
+        assert_eq!(last.match_number, history.len());
+        assert_eq!(last.match_id, history[0].id);
+        assert_eq!(
+            serde_json::to_value(&last.standings).unwrap(),
+            serde_json::to_value(compute_leaderboard(&players, &history, &config)).unwrap()
+        );
+    }
+
+    #[test]
+    fn every_timeline_snapshot_matches_the_leaderboard_at_that_point() {
+        let config = AppConfig::for_tests();
+        let players = [player("alice"), player("bob"), player("carol")];
+        let history: Vec<MatchRecord> = (0..9)
+            .map(|i| match i % 4 {
+                0 | 1 => game("alice", "bob", i),
+                2 => game("carol", "alice", i),
+                _ => game("bob", "carol", i),
+            })
+            .collect();
+
+        let timeline = compute_timeline(&players, &history, &config);
+
+        assert_eq!(timeline.len(), history.len());
+        for snapshot in &timeline {
+            let played = &history[history.len() - snapshot.match_number..];
+            assert_eq!(snapshot.match_id, played[0].id);
+            assert_eq!(
+                serde_json::to_value(&snapshot.standings).unwrap(),
+                serde_json::to_value(compute_leaderboard(&players, played, &config)).unwrap(),
+                "after match {}",
+                snapshot.match_number
+            );
+            for entry in &snapshot.standings {
+                let results: Vec<bool> = played
+                    .iter()
+                    .filter(|m| m.player_ids().any(|id| id == entry.player_id))
+                    .map(|m| m.winner_ids().any(|id| id == entry.player_id))
+                    .collect();
+                assert_eq!(entry.streak, calculate_streak(&results));
+                assert_eq!(entry.longest_streak_all_time, longest_streaks(&results).0);
+            }
+        }
+    }
+
+    #[test]
+    fn dashboard_sections_agree_with_the_individual_endpoints() {
+        let config = AppConfig::for_tests();
+        let players = [player("alice"), player("bob"), player("carol"), player("dave")];
+        let mut history = winning_run(3);
+        history.extend((3..12).map(|i| game("bob", "carol", i)));
+
+        let dashboard = build_dashboard(&config, &players, history.clone(), false);
+
+        assert_eq!(
+            serde_json::to_value(&dashboard.leaderboard).unwrap(),
+            serde_json::to_value(compute_leaderboard(&players, &history, &config)).unwrap()
+        );
+        let recent: Vec<&str> = dashboard.recent_matches.iter().map(|m| m.id.as_str()).collect();
+        let listed: Vec<&str> = history.iter().take(10).map(|m| m.id.as_str()).collect();
+        assert_eq!(recent, listed);
+
+        assert_eq!(dashboard.summary.total_matches, 12);
+        assert_eq!(dashboard.summary.total_players, 4);
+        assert_eq!(dashboard.summary.active_players, 3, "dave never played");
+        assert_eq!(dashboard.summary.latest_match_at, Some(history[0].played_at));
+
+        let hot = dashboard.hot_streak.unwrap();
+        assert_eq!((hot.player_id.as_str(), hot.streak), ("alice", 3));
+        assert_eq!(hot.streak, entry(&dashboard.leaderboard, "alice").streak);
+    }
+
+    #[test]
+    fn monthly_buckets_follow_the_configured_timezone() {
+        let tz = chrono_tz::America::New_York;
+        let at = |m, d, h| Utc.with_ymd_and_hms(2024, m, d, h, 0, 0).unwrap();
+        // Newest first. 03:00 UTC on Feb 1 is still Jan 31 in New York.
+        let history = [
+            game_at("alice", "bob", at(4, 5, 12)),
+            game_at("bob", "alice", at(2, 10, 12)),
+            game_at("alice", "bob", at(2, 1, 3)),
+            game_at("alice", "bob", at(1, 15, 12)),
+        ];
+        let summary = |records: Vec<MonthlyRecord>| -> Vec<(String, u32, u32)> {
+            records.into_iter().map(|r| (r.month, r.wins, r.losses)).collect()
+        };
+
+        let sparse = monthly_records("alice", &history, tz, false);
+        assert_eq!(sparse[0].win_rate, 1.0);
+        assert_eq!(
+            summary(sparse),
+            [("2024-01".into(), 2, 0), ("2024-02".into(), 0, 1), ("2024-04".into(), 1, 0)]
+        );
+
+        let filled = monthly_records("alice", &history, tz, true);
+        assert_eq!(filled[2].month, "2024-03");
+        assert_eq!((filled[2].wins, filled[2].losses, filled[2].win_rate), (0, 0, 0.0));
+        assert_eq!(filled.len(), 4);
+
+        let utc = monthly_records("alice", &history, chrono_tz::UTC, false);
+        assert_eq!((utc[1].month.as_str(), utc[1].wins), ("2024-02", 1));
+    }
+
+    #[test]
+    fn the_sharpest_improver_is_comeback_player() {
+        let config = AppConfig::for_tests();
+        let players = [player("alice"), player("bob"), player("carol")];
+        let at = |m, d| Utc.with_ymd_and_hms(2024, m, d, 12, 0, 0).unwrap();
+        // March: alice 3-1. February: alice 1-4. January is out of range.
+        let history = [
+            game_at("alice", "bob", at(3, 10)),
+            game_at("alice", "bob", at(3, 9)),
+            game_at("alice", "bob", at(3, 8)),
+            game_at("bob", "alice", at(3, 7)),
+            game_at("bob", "alice", at(2, 10)),
+            game_at("bob", "alice", at(2, 9)),
+            game_at("bob", "alice", at(2, 8)),
+            game_at("alice", "bob", at(2, 7)),
+            game_at("carol", "alice", at(2, 1)),
+            game_at("bob", "alice", at(1, 10)),
+            game_at("bob", "alice", at(1, 9)),
+        ];
+
+        let comeback = find_comeback_player(&config, &players, &history, at(3, 15)).unwrap();
+
+        assert_eq!(comeback.player_id, "alice");
+        assert_eq!(comeback.previous_period_start, "2024-02");
+        assert_eq!(comeback.current_period_start, "2024-03");
+        assert_eq!((comeback.previous_games, comeback.current_games), (5, 4));
+        assert_eq!(comeback.previous_win_rate, 0.2);
+        assert_eq!(comeback.current_win_rate, 0.75);
+        assert!((comeback.delta - 0.55).abs() < 1e-9);
+    }
+
+    #[test]
+    fn nobody_is_comeback_player_without_enough_games() {
+        let config = AppConfig::for_tests();
+        let players = [player("alice"), player("bob")];
+        let now = Utc::now();
+        let history = [
+            game_at("alice", "bob", now),
+            game_at("bob", "alice", now - Duration::days(40)),
+        ];
+        assert!(find_comeback_player(&config, &players, &history, now).is_none());
+    }
+
+    #[test]
+    fn predictions_reject_unknown_players_and_odd_teams() {
+        let players = [player("alice"), player("bob"), player("carol")];
+        let ratings = HashMap::from([("alice".to_string(), 1100.0)]);
+
+        let team = team_ratings("alice, bob", &players, &ratings).unwrap();
+        assert_eq!(team, [1100.0, elo::BASE_RATING]);
+
+        for team in ["alice,zed", "alice", "alice,bob,carol"] {
+            let err = team_ratings(team, &players, &ratings).unwrap_err();
+            let status = StatsError::from(err).into_response().status();
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{team}");
+        }
+    }
+
+    #[test]
+    fn forfeits_count_for_standings_but_not_scores() {
+        let config = AppConfig::for_tests();
+        let players = [player("alice"), player("bob")];
+        let scored = |loser_score, forfeit, minutes_ago| {
+            MatchRecord::new(NewMatch {
+                winner1_id: "alice".to_string(),
+                loser1_id: "bob".to_string(),
+                winner_score: Some(11),
+                loser_score: Some(loser_score),
+                forfeit,
+                played_at: Utc::now() - Duration::minutes(minutes_ago),
+                ..Default::default()
+            })
+            .unwrap()
+        };
+        let history = [scored(0, true, 1), scored(5, false, 2)];
+
+        let board = compute_leaderboard(&players, &history, &config);
+        assert_eq!((entry(&board, "alice").wins, entry(&board, "bob").losses), (2, 2));
+
+        let alice = stats_for("alice", &history);
+        assert_eq!((alice.wins, alice.losses), (2, 0));
+        assert_eq!((alice.points.points_for, alice.points.points_against), (11, 5));
+        assert_eq!(alice.points.avg_point_differential, Some(6.0));
+
+        let records = compute_record_book(&history, &players, config.timezone);
+        let blowout = records.biggest_blowout.unwrap();
+        assert_eq!((blowout.winner_score, blowout.loser_score), (11, 5));
+
+        let played = filter_matches(history.to_vec(), &None, true);
+        assert_eq!(played.len(), 1);
+        assert!(!played[0].forfeit);
+    }
+
+    #[test]
+    fn the_record_book_finds_each_record() {
+        let players = [player("alice"), player("bob"), player("carol"), player("dave")];
+        let at = |day, hour| Utc.with_ymd_and_hms(2024, 6, day, hour, 0, 0).unwrap();
+        let scored = |winners: &[&str], losers: &[&str], (w, l), forfeit, played_at| {
+            MatchRecord::new(NewMatch {
+                winner1_id: winners[0].to_string(),
+                winner2_id: winners.get(1).map(|id| id.to_string()),
+                loser1_id: losers[0].to_string(),
+                loser2_id: losers.get(1).map(|id| id.to_string()),
+                winner_score: Some(w),
+                loser_score: Some(l),
+                forfeit,
+                played_at,
+                ..Default::default()
+            })
+            .unwrap()
+        };
+        let chronological = [
+            scored(&["carol"], &["dave"], (11, 7), false, at(1, 9)),
+            scored(&["alice", "bob"], &["carol", "dave"], (11, 9), false, at(1, 10)),
+            scored(&["alice", "bob"], &["carol", "dave"], (15, 13), false, at(1, 11)),
+            scored(&["bob", "alice"], &["dave", "carol"], (11, 2), false, at(1, 12)),
+            scored(&["carol"], &["alice"], (11, 4), false, at(1, 13)),
+            scored(&["bob"], &["dave"], (11, 0), true, at(2, 10)),
+            scored(&["carol", "alice"], &["bob", "dave"], (11, 8), false, at(2, 11)),
+        ];
+        let history: Vec<MatchRecord> = chronological.iter().rev().cloned().collect();
+
+        let records = compute_record_book(&history, &players, chrono_tz::UTC);
+
+        // bob's fourth straight win was the forfeit: it counts for streaks...
+        let streak = records.longest_win_streak.unwrap();
+        assert_eq!((streak.player_id.as_str(), streak.length), ("bob", 4));
+        assert_eq!(streak.match_id, chronological[5].id);
+        // ...but not for score records, or 11-0 would be the blowout.
+        let blowout = records.biggest_blowout.unwrap();
+        assert_eq!(blowout.match_id, chronological[3].id);
+        assert_eq!(blowout.winner_ids, ["bob", "alice"]);
+        assert_eq!((blowout.winner_score, blowout.loser_score), (11, 2));
+        let highest = records.highest_score.unwrap();
+        assert_eq!((highest.winner_score, highest.loser_score), (15, 13));
+
+        let day = records.most_games_in_a_day.unwrap();
+        assert_eq!((day.player_id.as_str(), day.games), ("carol", 5));
+        assert_eq!(day.date, NaiveDate::from_ymd_opt(2024, 6, 1).unwrap());
+
+        let pairing = records.most_frequent_pairing.unwrap();
+        assert_eq!((pairing.player1_id.as_str(), pairing.player2_id.as_str()), ("alice", "bob"));
+        assert_eq!(pairing.games, 3);
+    }
+
+    #[test]
+    fn an_empty_record_book_has_no_records() {
+        let records = compute_record_book(&[], &[player("alice")], chrono_tz::UTC);
+        assert!(records.longest_win_streak.is_none());
+        assert!(records.biggest_blowout.is_none() && records.highest_score.is_none());
+        assert!(records.most_games_in_a_day.is_none());
+        assert!(records.most_frequent_pairing.is_none());
+    }
+
+    #[test]
+    fn the_player_card_has_exactly_the_template_fields() {
+        let config = AppConfig::for_tests();
+        let players = [player("alice"), player("bob")];
+        // bob: won once, then lost twice.
+        let history = [game("alice", "bob", 1), game("alice", "bob", 2), game("bob", "alice", 3)];
+
+        let card = build_player_card(&config, player("bob"), &players, &history).unwrap();
+
+        let json = serde_json::to_value(&card).unwrap();
+        let mut keys: Vec<&str> = json.as_object().unwrap().keys().map(|k| k.as_str()).collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            ["emoji", "losses", "name", "rank", "streak", "version", "win_rate_pct", "wins"]
+        );
+        assert_eq!(json["version"], PLAYER_CARD_VERSION);
+        assert_eq!((card.wins, card.losses, card.win_rate_pct), (1, 2, 33));
+        assert_eq!((card.streak, card.rank), (-2, 2));
+    }
+
+    #[test]
+    fn recent_matches_are_skipped_when_not_wanted() {
+        let config = AppConfig::for_tests();
+        let players = [player("alice"), player("bob")];
+        let history = winning_run(3);
+
+        let lean = compute_player_stats(&config, player("alice"), &history, &players, None);
+        assert!(lean.recent_matches.is_empty());
+        assert_eq!(lean.wins, 3, "headline numbers are unaffected");
+
+        let full = stats_for("alice", &history);
+        assert_eq!(full.recent_matches.len(), 3);
+    }
+
+    #[test]
+    fn every_stat_handles_a_completely_empty_dataset() {
+        let config = AppConfig::for_tests();
+        let tz = config.timezone;
+        let now = Utc::now();
+        let none: &[MatchRecord] = &[];
+
+        for players in [&[][..], &[player("alice")][..]] {
+            let board = compute_leaderboard(players, none, &config);
+            assert_eq!(board.len(), players.len());
+            assert!(board.iter().all(|e| e.win_rate == 0.0 && e.total_games == 0));
+
+            let dashboard = build_dashboard(&config, players, Vec::new(), false);
+            assert_eq!(dashboard.summary.total_matches, 0);
+            assert_eq!(dashboard.summary.active_players, 0);
+            assert!(dashboard.summary.latest_match_at.is_none());
+            assert!(dashboard.recent_matches.is_empty() && dashboard.hot_streak.is_none());
+
+            assert!(compute_timeline(players, none, &config).is_empty());
+            assert!(compute_rivalries(players, none, RivalrySort::Intensity).is_empty());
+            assert!(find_comeback_player(&config, players, none, now).is_none());
+            let records = compute_record_book(none, players, tz);
+            assert!(records.longest_win_streak.is_none() && records.biggest_blowout.is_none());
+            assert!(elo::compute_team_elo(none).is_empty());
+            assert!(elo::compute_elo(none, &HashMap::new()).is_empty());
+            assert_eq!(glicko::compute_glicko(players, none, 7).len(), players.len());
+            assert!(attendance::participation_rates(none, tz).is_empty());
+            let ids: Vec<&str> = players.iter().map(|p| p.id.as_str()).collect();
+            assert!(scheduler::suggest_matchups(&ids, none, &HashMap::new(), 5).is_empty());
+        }
+
+        assert!(monthly_records("alice", none, tz, true).is_empty());
+        let stats = stats_for("alice", none);
+        assert_eq!((stats.wins, stats.losses, stats.win_rate), (0, 0, 0.0));
+        assert!(stats.recent_matches.is_empty());
+        assert_eq!(stats.points.avg_point_differential, None);
+        assert!(serde_json::to_string(&stats).is_ok());
+        let card = build_player_card(&config, player("alice"), &[player("alice")], none).unwrap();
+        assert_eq!((card.win_rate_pct, card.rank), (0, 1));
+    }
+
+    #[test]
+    fn close_frequent_rivalries_outrank_lopsided_ones() {
+        assert_eq!(rivalry_intensity(5, 5), 10.0);
+        assert!((rivalry_intensity(11, 1) - 2.0).abs() < 1e-9);
+        assert_eq!(rivalry_intensity(0, 0), 0.0);
+
+        let players = [player("alice"), player("bob"), player("carol"), player("dave")];
+        let mut history: Vec<MatchRecord> = Vec::new();
+        for i in 0..10 {
+            let (winner, loser) = if i % 2 == 0 { ("alice", "bob") } else { ("bob", "alice") };
+            history.push(game(winner, loser, i));
+        }
+        history.extend((0..11).map(|i| game("carol", "dave", 20 + i)));
+        history.push(game("dave", "carol", 40));
+
+        let pair = |r: &RivalryEntry| (r.player1_id.clone(), r.player2_id.clone());
+        let by_games = compute_rivalries(&players, &history, RivalrySort::Games);
+        assert_eq!(pair(&by_games[0]), ("carol".to_string(), "dave".to_string()));
+
+        let by_intensity = compute_rivalries(&players, &history, RivalrySort::Intensity);
+        assert_eq!(pair(&by_intensity[0]), ("alice".to_string(), "bob".to_string()));
+        assert_eq!(by_intensity[0].intensity, 10.0);
+        assert!((by_intensity[1].intensity - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn best_and_worst_months_skip_thin_months() {
+        let month = |month: &str, wins: u32, losses: u32| MonthlyRecord {
+            month: month.to_string(),
+            wins,
+            losses,
+            win_rate: f64::from(wins) / f64::from(wins + losses),
+        };
+        let months = vec![
+            month("2024-01", 3, 1),
+            // Perfect and winless, but under the minimum.
+            month("2024-02", 2, 0),
+            month("2024-03", 0, 1),
+            month("2024-04", 1, 3),
+            month("2024-05", 6, 2),
+            month("2024-06", 2, 6),
+        ];
+
+        let (best, worst) = month_extremes(months.clone(), 3);
+        // Ties go to the month with more games.
+        assert_eq!(best.unwrap().month, "2024-05");
+        assert_eq!(worst.unwrap().month, "2024-06");
+
+        let (best, worst) = month_extremes(months, 1);
+        assert_eq!(best.unwrap().month, "2024-02");
+        assert_eq!(worst.unwrap().month, "2024-03");
+
+        assert!(matches!(month_extremes(vec![month("2024-01", 1, 0)], 2), (None, None)));
+    }
+
+    #[test]
+    fn include_inactive_false_drops_players_without_games() {
+        let players = [player("alice"), player("bob"), player("carol")];
+        let history = [game("alice", "bob", 10)];
+        let config = AppConfig::for_tests();
+        let ranked = |uri: &str| {
+            let uri: axum::http::Uri = uri.parse().unwrap();
+            let Query(query) = Query::<LeaderboardQuery>::try_from_uri(&uri).unwrap();
+            let entries = rank_leaderboard(&players, &history, &config, &query);
+            entries.into_iter().map(|e| e.player_id).collect::<Vec<_>>()
+        };
+
+        assert_eq!(ranked("/api/leaderboard"), ["alice", "bob", "carol"]);
+        assert_eq!(ranked("/api/leaderboard?include_inactive=true").len(), 3);
+        assert_eq!(ranked("/api/leaderboard?include_inactive=false"), ["alice", "bob"]);
+    }
+
+    #[test]
+    fn adjusted_win_rate_ignores_easy_wins() {
+        // Alice beats carol four times and splits two games with bob, who
+        // also beats dave twice. Rates: alice 5/6, bob 3/4, carol and dave 0.
+        let mut history: Vec<MatchRecord> =
+            (0..4).map(|i| game("alice", "carol", 10 + i)).collect();
+        history.extend([
+            game("alice", "bob", 20),
+            game("bob", "alice", 21),
+            game("bob", "dave", 22),
+            game("bob", "dave", 23),
+        ]);
+
+        let alice = stats_for("alice", &history);
+
+        assert!((alice.win_rate - 5.0 / 6.0).abs() < 1e-9);
+        assert_eq!(alice.adjusted_win_rate, Some(0.5));
+        // With a gap wide enough to keep every game, nothing changes.
+        let everything = adjusted_win_rate("alice", &history, 1.0).unwrap();
+        assert!((everything - 5.0 / 6.0).abs() < 1e-9);
+        // Carol's opponent is stronger, never weaker, so all her games count.
+        assert_eq!(stats_for("carol", &history).adjusted_win_rate, Some(0.0));
+        assert_eq!(adjusted_win_rate("nobody", &history, 0.25), None);
+    }
+
+    #[test]
+    fn rivalry_csv_matches_the_json_rivalries() {
+        let mut players = vec![player("alice"), player("bob"), player("carol"), player("dave")];
+        players[1].name = "Aaron".to_string();
+        // alice/bob and bob/carol are rivalries; alice/dave met only once.
+        let history = [
+            game("alice", "bob", 10),
+            game("alice", "bob", 11),
+            game("bob", "alice", 12),
+            game("carol", "bob", 13),
+            game("carol", "bob", 14),
+            game("alice", "dave", 15),
+        ];
+        let rivalries = compute_rivalries(&players, &history, RivalrySort::Games);
+
+        let csv = rivalries_csv(&rivalries);
+
+        let rows: Vec<Vec<&str>> = csv
+            .strip_suffix("\r\n")
+            .unwrap()
+            .split("\r\n")
+            .map(|row| row.split(',').collect())
+            .collect();
+        // Headed and ordered by display name; dave has no rivalry.
+        let order = ["bob", "alice", "carol"];
+        assert_eq!(rows[0], ["", "Aaron", "alice", "carol"]);
+        assert_eq!(rows.len(), order.len() + 1);
+        let cell = |row: &str, col: &str| {
+            let index = |id| order.iter().position(|p| *p == id).unwrap() + 1;
+            rows[index(row)][index(col)]
+        };
+        assert_eq!(rivalries.len(), 2);
+        for r in &rivalries {
+            assert_eq!(cell(&r.player1_id, &r.player2_id), r.player1_wins.to_string());
+            assert_eq!(cell(&r.player2_id, &r.player1_id), r.player2_wins.to_string());
+        }
+        assert_eq!(cell("alice", "bob"), "2");
+        assert_eq!(cell("alice", "carol"), "");
+        assert_eq!(cell("alice", "alice"), "");
+    }
+
+    #[test]
+    fn resilient_streaks_survive_a_single_interruption() {
+        // Newest first: a loss right after ten wins, then a loss further back.
+        let mut results = vec![false];
+        results.extend([true; 10]);
+        results.extend([false, true]);
+
+        assert_eq!(calculate_streak(&results), -1);
+        assert_eq!(calculate_resilient_streak(&results, 1), 10);
+        // With room for both losses, the older win counts too.
+        assert_eq!(calculate_resilient_streak(&results, 2), 11);
+        assert_eq!(calculate_resilient_streak(&results, 0), calculate_streak(&results));
+
+        let interrupted = [true, true, false, true, true, true];
+        assert_eq!(calculate_streak(&interrupted), 2);
+        assert_eq!(calculate_resilient_streak(&interrupted, 1), 5);
+        assert_eq!(calculate_resilient_streak(&[], 1), 0);
+    }
+
+    #[test]
+    fn tied_players_share_a_rank_and_the_next_rank_skips() {
+        let players = [player("alice"), player("bob"), player("carol"), player("dave")];
+        // alice and bob are 1-0, carol 1-1, dave 0-2.
+        let history = [
+            game("alice", "carol", 10),
+            game("bob", "dave", 20),
+            game("carol", "dave", 30),
+        ];
+        let mut config = AppConfig::for_tests();
+        let ranks = |config: &AppConfig| {
+            compute_leaderboard(&players, &history, config)
+                .into_iter()
+                .map(|e| e.rank)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ranks(&config), [1, 2, 3, 4]);
+        config.shared_ranks = true;
+        assert_eq!(ranks(&config), [1, 1, 3, 4]);
+    }
+
+    #[test]
+    fn longest_streaks_scan_the_whole_history() {
+        assert_eq!(longest_streaks(&[true, false, true, false, true]), (1, 1));
+        assert_eq!(longest_streaks(&[true; 6]), (6, 0));
+        assert_eq!(longest_streaks(&[false; 4]), (0, 4));
+        assert_eq!(longest_streaks(&[]), (0, 0));
+        // The best run is in the past, not the current streak.
+        assert_eq!(longest_streaks(&[false, true, true, true, false, false, true]), (3, 2));
+    }
+
+    #[test]
+    fn longest_streaks_reach_stats_and_leaderboard() {
+        // Newest first: alice won three in a row, then lost the latest.
+        let history = [
+            game("bob", "alice", 10),
+            game("alice", "bob", 20),
+            game("alice", "bob", 30),
+            game("alice", "bob", 40),
+        ];
+
+        let alice = stats_for("alice", &history);
+        assert_eq!((alice.longest_win_streak, alice.longest_loss_streak), (3, 1));
+        let bob = stats_for("bob", &history);
+        assert_eq!((bob.longest_win_streak, bob.longest_loss_streak), (1, 3));
+
+        let players = [player("alice"), player("bob"), player("carol")];
+        let entries = compute_leaderboard(&players, &history, &AppConfig::for_tests());
+        let longest = |id: &str| {
+            entries.iter().find(|e| e.player_id == id).unwrap().longest_streak_all_time
+        };
+        assert_eq!((longest("alice"), longest("bob"), longest("carol")), (3, 1, 0));
+    }
+
+    #[test]
+    fn export_cache_keeps_forfeit_variants_apart() {
+        let cache = ResponseCache::new(8);
+        let with = StatsQuery { league_id: None, exclude_forfeits: false };
+        let without = StatsQuery { league_id: None, exclude_forfeits: true };
+
+        cache.insert(with.cache_key("player-export:alice"), 1, Bytes::from_static(b"all"));
+        assert_eq!(cache.get(&without.cache_key("player-export:alice"), 1), None);
+
+        cache.insert(without.cache_key("player-export:alice"), 1, Bytes::from_static(b"some"));
+        assert_eq!(
+            cache.get(&with.cache_key("player-export:alice"), 1).as_deref(),
+            Some(&b"all"[..])
+        );
+        assert_eq!(
+            cache.get(&without.cache_key("player-export:alice"), 1).as_deref(),
+            Some(&b"some"[..])
+        );
+    }
 }
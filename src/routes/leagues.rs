@@ -21,6 +21,7 @@ use axum::{
 use chrono::Utc;
 
 use crate::auth::oidc::SessionClaims;
+use crate::extract::ApiJson;
 use crate::models::league::{CreateLeagueRequest, League, UpdateLeagueRequest};
 use crate::storage::client::StorageClient;
 use crate::storage::leagues::{self, LeagueStorageError};
@@ -75,7 +76,7 @@ pub async fn get_league(
 pub async fn create_league(
     State(storage): State<StorageClient>,
     Extension(claims): Extension<SessionClaims>,
-    Json(req): Json<CreateLeagueRequest>,
+    ApiJson(req): ApiJson<CreateLeagueRequest>,
 ) -> Result<(StatusCode, Json<League>), LeagueStorageError> {
     let league = League {
         id: req.id,
@@ -99,11 +100,11 @@ pub async fn update_league(
     State(storage): State<StorageClient>,
     Extension(claims): Extension<SessionClaims>,
     Path(id): Path<String>,
-    Json(req): Json<UpdateLeagueRequest>,
+    ApiJson(req): ApiJson<UpdateLeagueRequest>,
 ) -> Result<Json<League>, LeagueStorageError> {
     // Check authorization: must be admin or the league creator.
     let existing = leagues::get_league(&storage, &id).await?;
-    if claims.role != "admin" && claims.sub != existing.created_by {
+    if !claims.is_admin && claims.sub != existing.created_by {
         return Err(LeagueStorageError::Forbidden(
             "Only the creator or an admin can update this league".to_string(),
         ));
@@ -124,7 +125,7 @@ pub async fn close_league(
 ) -> Result<Json<League>, LeagueStorageError> {
     // Check authorization: must be admin or the league creator.
     let existing = leagues::get_league(&storage, &id).await?;
-    if claims.role != "admin" && claims.sub != existing.created_by {
+    if !claims.is_admin && claims.sub != existing.created_by {
         return Err(LeagueStorageError::Forbidden(
             "Only the creator or an admin can close this league".to_string(),
         ));
@@ -18,17 +18,35 @@
 
 use axum::{
     Extension, Json,
+    body::Body,
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::IntoResponse,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
 };
-use chrono::Utc;
-use serde::Deserialize;
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+use chrono_tz::Tz;
+use futures::{Stream, StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
 use crate::auth::oidc::SessionClaims;
-use crate::models::match_record::{CreateMatchRequest, MatchRecord, UpdateMatchRequest};
+use crate::config::{AppConfig, DuplicatePolicy};
+use crate::csv;
+use crate::extract::ApiJson;
+use crate::idempotency::{Claim, IdempotencyKey};
+use crate::metrics::Metrics;
+use crate::models::event::MatchEventKind;
+use crate::models::match_record::{
+    ANONYMOUS_PLAYER_ID, ANONYMOUS_PLAYER_NAME, CreateMatchRequest, CreateSessionRequest,
+    MatchRecord, MatchType, NewMatch, SessionGame, UpdateMatchRequest, parse_played_at,
+    sanitize_comment, second_players,
+};
+use crate::models::player::{Player, default_avatar};
 use crate::storage::client::StorageClient;
-use crate::storage::matches::{self, MatchStorageError};
+use crate::storage::events;
+use crate::storage::matches::{self, MatchPageFilter, MatchStorageError};
+use crate::storage::players::{self, PlayerStorageError};
+use crate::webhook;
 
 /// Map storage errors to HTTP responses.
 ///
@@ -39,6 +57,18 @@ impl IntoResponse for MatchStorageError {
         let (status, message) = match &self {
             MatchStorageError::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
             MatchStorageError::Forbidden(_) => (StatusCode::FORBIDDEN, self.to_string()),
+            MatchStorageError::Invalid(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            MatchStorageError::Duplicate(_) => (StatusCode::CONFLICT, self.to_string()),
+            MatchStorageError::TooMany(_) => (StatusCode::TOO_MANY_REQUESTS, self.to_string()),
+            MatchStorageError::QuietHours(_) => {
+                // Carries a machine-readable code so the SPA can show a
+                // friendlier message than the raw error.
+                return (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    Json(serde_json::json!({ "error": self.to_string(), "code": "quiet_hours" })),
+                )
+                    .into_response();
+            }
             MatchStorageError::Azure(_) => {
                 tracing::error!("Azure storage error: {self}");
                 (
@@ -52,66 +82,1166 @@ impl IntoResponse for MatchStorageError {
     }
 }
 
+/// Response header carrying non-blocking warnings about a recorded match
+/// (one header value per warning).
+pub const WARNING_HEADER: &str = "x-scoreboard-warning";
+
+/// Response header with the token for the next page of `GET /api/matches`.
+pub const CONTINUATION_HEADER: &str = "x-continuation";
+
+/// A match as returned by the list and create endpoints: the stored record
+/// plus, when the request asked for a `?tz=`, `played_at` in that zone.
+///
+/// Storage is always UTC; the local time is purely a convenience for clients
+/// that don't want to convert themselves.
+#[derive(Debug, Serialize)]
+pub struct LocalizedMatch {
+    #[serde(flatten)]
+    pub record: MatchRecord,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub played_at_local: Option<DateTime<FixedOffset>>,
+    /// The participants, winners first (two for singles, four for doubles),
+    /// only with `?include_players=true`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub players: Option<Vec<PlayerSummary>>,
+}
+
+impl LocalizedMatch {
+    fn new(record: MatchRecord, tz: Option<Tz>) -> Self {
+        let played_at_local = tz.map(|tz| record.played_at.with_timezone(&tz).fixed_offset());
+        Self {
+            record,
+            played_at_local,
+            players: None,
+        }
+    }
+}
+
+/// Just enough of a player to render a match card.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerSummary {
+    pub id: String,
+    pub name: String,
+    pub avatar_emoji: String,
+    pub color: Option<String>,
+}
+
+impl PlayerSummary {
+    /// Resolve `id` from the roster. Deleted players fall back to their ID as
+    /// the name, the default avatar and no color; hidden losers are shown as
+    /// `ANONYMOUS_PLAYER_NAME`.
+    fn resolve(id: &str, roster: &HashMap<&str, &Player>) -> Self {
+        match roster.get(id) {
+            Some(p) => Self {
+                id: p.id.clone(),
+                name: p.name.clone(),
+                avatar_emoji: p.avatar_emoji.clone(),
+                color: p.color.clone(),
+            },
+            None => Self {
+                id: id.to_string(),
+                name: display_name_fallback(id).to_string(),
+                avatar_emoji: default_avatar(),
+                color: None,
+            },
+        }
+    }
+}
+
+/// Name shown for a player ID that isn't on the roster.
+fn display_name_fallback(id: &str) -> &str {
+    if id == ANONYMOUS_PLAYER_ID { ANONYMOUS_PLAYER_NAME } else { id }
+}
+
+/// Whether this request gets the anonymized public view: `ANONYMIZE_LOSERS`
+/// is on and there is no session, as on the `/api/display/*` routes.
+pub fn hide_losers(config: &AppConfig, claims: Option<&SessionClaims>) -> bool {
+    config.anonymize_losers && claims.is_none()
+}
+
+/// Reject a player filter on the anonymized view: every match it returns
+/// where that player isn't a winner would reveal that they lost.
+fn ensure_player_filter_allowed(
+    anonymize: bool,
+    player_id: Option<&str>,
+) -> Result<(), MatchStorageError> {
+    if anonymize && player_id.is_some() {
+        return Err(MatchStorageError::Forbidden(
+            "filtering by player is not available without signing in".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Parse an optional `?tz=` IANA zone name, rejecting unknown zones.
+fn parse_tz(tz: Option<&str>) -> Result<Option<Tz>, MatchStorageError> {
+    tz.map(|name| {
+        name.parse::<Tz>()
+            .map_err(|_| MatchStorageError::Invalid(format!("unknown timezone '{name}'")))
+    })
+    .transpose()
+}
+
 /// Query parameters for listing matches.
 #[derive(Deserialize)]
 pub struct ListMatchesQuery {
-    /// Maximum number of matches to return.
+    /// Maximum number of matches to return. Also turns on paging: the
+    /// response carries an `X-Continuation` header while more matches exist.
     pub limit: Option<usize>,
+    /// The `X-Continuation` value from the previous page.
+    pub continuation: Option<String>,
+    /// Only matches this player took part in (any slot). 404 if unknown.
+    pub player: Option<String>,
     /// Filter to a specific league. Omit for all-time.
     pub league_id: Option<String>,
+    /// Drop forfeited matches.
+    #[serde(default)]
+    pub exclude_forfeits: bool,
+    /// IANA timezone; adds `played_at_local` to each match.
+    pub tz: Option<String>,
+    /// Embed each participant's name and avatar as `players`.
+    #[serde(default)]
+    pub include_players: bool,
+}
+
+/// Query parameters for recording a match.
+#[derive(Deserialize)]
+pub struct CreateMatchQuery {
+    /// IANA timezone for interpreting a naive `played_at` (and for the
+    /// `played_at_local` in the response).
+    pub tz: Option<String>,
 }
 
 /// GET /api/matches — List recent matches.
 ///
-/// Supports optional filtering by league_id. When a league_id is provided,
-/// we filter in-memory after fetching from storage. This is fine for our
-/// small dataset (<1000 matches). For larger datasets, you'd want an
-/// Azure Table Storage filter or a secondary index.
+/// The league and `?player=` filters are pushed into the storage query (like
+/// the feed), so only matching rows are read. With `?limit=N` an
+/// `X-Continuation` header is set when older matches remain; pass it back as
+/// `?continuation=` for the next page. The body stays a plain array either
+/// way. `exclude_forfeits` is applied to the page afterwards, so such pages
+/// may hold fewer than N matches. An unknown `player` is a `404`.
+///
+/// When `ANONYMIZE_LOSERS` is on and the request carries no session (a
+/// display screen), losing players are replaced by a placeholder and
+/// `?player=` is a `403`, since its results would give the losers away.
+///
+/// With `?tz=Europe/Berlin` (any IANA zone), each match also carries
+/// `played_at_local` in that zone. With `?include_players=true`, each match
+/// embeds its participants, resolved from one roster fetch.
 pub async fn list_matches(
     State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    claims: Option<Extension<SessionClaims>>,
     Query(query): Query<ListMatchesQuery>,
-) -> Result<Json<Vec<MatchRecord>>, MatchStorageError> {
-    let mut all_matches = matches::list_matches(&storage, None).await?;
+) -> Result<Response, MatchStorageError> {
+    let tz = parse_tz(query.tz.as_deref())?;
+    let anonymize = hide_losers(&config, claims.as_deref());
+    ensure_player_filter_allowed(anonymize, query.player.as_deref())?;
+    if let Some(player_id) = &query.player {
+        match players::get_player(&storage, player_id).await {
+            Ok(_) => {}
+            Err(e @ PlayerStorageError::NotFound(_)) => return Ok(e.into_response()),
+            Err(e) => return Err(MatchStorageError::Azure(e.to_string())),
+        }
+    }
+
+    let filter = MatchPageFilter {
+        player_id: query.player.as_deref(),
+        league_id: query.league_id.as_deref(),
+        ..Default::default()
+    };
+    let (mut all_matches, continuation) = matches::list_matches_page(
+        &storage,
+        &filter,
+        query.continuation.as_deref(),
+        query.limit.unwrap_or(usize::MAX),
+    )
+    .await?;
+    if query.exclude_forfeits {
+        all_matches.retain(|m| !m.forfeit);
+    }
 
-    // Filter by league if requested.
+    if anonymize {
+        all_matches.iter_mut().for_each(MatchRecord::anonymize_losers);
+    }
+
+    let all_players = if query.include_players {
+        players::list_players(&storage)
+            .await
+            .map_err(|e| MatchStorageError::Azure(e.to_string()))?
+    } else {
+        Vec::new()
+    };
+    let roster: HashMap<&str, &Player> =
+        all_players.iter().map(|p| (p.id.as_str(), p)).collect();
+
+    let body: Vec<LocalizedMatch> = all_matches
+        .into_iter()
+        .map(|m| {
+            let players = query.include_players.then(|| {
+                m.player_ids()
+                    .map(|id| PlayerSummary::resolve(id, &roster))
+                    .collect()
+            });
+            LocalizedMatch {
+                players,
+                ..LocalizedMatch::new(m, tz)
+            }
+        })
+        .collect();
+
+    let mut headers = HeaderMap::new();
+    if let Some(value) = continuation.and_then(|c| HeaderValue::from_str(&c).ok()) {
+        headers.insert(CONTINUATION_HEADER, value);
+    }
+    Ok((headers, Json(body)).into_response())
+}
+
+/// Query parameters for the sessions view.
+#[derive(Deserialize)]
+pub struct SessionsQuery {
+    /// Only the most recent N days that had matches. Omit for all of them.
+    pub limit_days: Option<usize>,
+    pub league_id: Option<String>,
+}
+
+/// All matches played on one calendar day.
+#[derive(Debug, Serialize)]
+pub struct MatchSession {
+    /// The day, in `TIMEZONE`.
+    pub date: NaiveDate,
+    pub games_played: usize,
+    pub distinct_players: usize,
+    /// That day's matches, newest first.
+    pub matches: Vec<MatchRecord>,
+}
+
+/// GET /api/matches/sessions — Match history grouped by day.
+///
+/// Days are calendar days in `TIMEZONE`, newest first; days without matches
+/// are left out. `?limit_days=N` keeps the N most recent days. Losers are
+/// anonymized for display screens, as in `list_matches`.
+pub async fn get_match_sessions(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    claims: Option<Extension<SessionClaims>>,
+    Query(query): Query<SessionsQuery>,
+) -> Result<Json<Vec<MatchSession>>, MatchStorageError> {
+    let mut all_matches = matches::list_matches(&storage, None).await?;
     if let Some(ref league_id) = query.league_id {
         all_matches.retain(|m| m.league_id.as_deref() == Some(league_id.as_str()));
     }
+    let anonymize = hide_losers(&config, claims.as_deref());
+
+    Ok(Json(group_by_day(all_matches, config.timezone, query.limit_days, anonymize)))
+}
+
+/// Group newest-first matches into per-day sessions, newest day first.
+///
+/// With `anonymize`, losers are hidden in each session's `matches` only after
+/// `distinct_players` has been counted from the real lineups.
+fn group_by_day(
+    all_matches: Vec<MatchRecord>,
+    tz: Tz,
+    limit_days: Option<usize>,
+    anonymize: bool,
+) -> Vec<MatchSession> {
+    let mut sessions: Vec<MatchSession> = Vec::new();
+    for m in all_matches {
+        let date = m.played_at.with_timezone(&tz).date_naive();
+        match sessions.last_mut() {
+            Some(session) if session.date == date => session.matches.push(m),
+            _ => {
+                if limit_days.is_some_and(|limit| sessions.len() >= limit) {
+                    break;
+                }
+                sessions.push(MatchSession {
+                    date,
+                    games_played: 0,
+                    distinct_players: 0,
+                    matches: vec![m],
+                });
+            }
+        }
+    }
 
-    // Apply limit after filtering (so we get `limit` matches from the filtered set).
-    if let Some(limit) = query.limit {
-        all_matches.truncate(limit);
+    for session in &mut sessions {
+        session.games_played = session.matches.len();
+        session.distinct_players = session
+            .matches
+            .iter()
+            .flat_map(MatchRecord::player_ids)
+            .collect::<HashSet<_>>()
+            .len();
+        if anonymize {
+            session.matches.iter_mut().for_each(MatchRecord::anonymize_losers);
+        }
     }
+    sessions
+}
+
+/// Columns of the match CSV export, in order.
+pub const MATCH_CSV_COLUMNS: [&str; 13] = [
+    "id",
+    "played_at",
+    "winner1_id",
+    "winner2_id",
+    "loser1_id",
+    "loser2_id",
+    "winner_score",
+    "loser_score",
+    "forfeit",
+    "league_id",
+    "session_id",
+    "recorded_by",
+    "comment",
+];
+
+/// One match as a CSV line in `MATCH_CSV_COLUMNS` order. Missing values are
+/// empty fields.
+fn match_csv_row(m: &MatchRecord) -> String {
+    let opt = |v: &Option<String>| v.clone().unwrap_or_default();
+    let score = |v: Option<i32>| v.map(|s| s.to_string()).unwrap_or_default();
+    csv::row(&[
+        m.id.clone(),
+        m.played_at.to_rfc3339(),
+        m.winner1_id.clone(),
+        opt(&m.winner2_id),
+        m.loser1_id.clone(),
+        opt(&m.loser2_id),
+        score(m.winner_score),
+        score(m.loser_score),
+        m.forfeit.to_string(),
+        opt(&m.league_id),
+        opt(&m.session_id),
+        m.recorded_by.clone(),
+        m.comment.clone(),
+    ])
+}
+
+/// Log a storage failure part-way through a streamed export. The status line
+/// is long gone by then, so the error just aborts the response body.
+fn log_export_error(e: MatchStorageError) -> MatchStorageError {
+    tracing::error!("Match export aborted: {e}");
+    e
+}
+
+/// GET /api/matches/export — Every match as one JSON array, newest first.
+///
+/// Streamed page by page (`EXPORT_PAGE_SIZE` matches at a time) rather than
+/// built in memory, so it works for any history size. A storage failure
+/// mid-way cuts the download short, leaving invalid JSON rather than a
+/// silently incomplete file.
+pub async fn export_matches_json(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+) -> Response {
+    let pages = matches::stream_matches(&storage, config.export_page_size);
+    (
+        [
+            (header::CONTENT_TYPE, "application/json"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"matches.json\""),
+        ],
+        json_export_body(pages),
+    )
+        .into_response()
+}
+
+/// GET /api/matches/export.csv — Every match as CSV (`MATCH_CSV_COLUMNS`),
+/// newest first, streamed the same way as `export_matches_json`.
+pub async fn export_matches_csv(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+) -> Response {
+    let pages = matches::stream_matches(&storage, config.export_page_size);
+    csv::response("matches.csv", csv_export_body(pages))
+}
+
+/// A JSON array of the matches in `pages`, written as the pages arrive.
+fn json_export_body(
+    pages: impl Stream<Item = Result<Vec<MatchRecord>, MatchStorageError>> + Send + 'static,
+) -> Body {
+    let mut first = true;
+    let items = pages.map(move |page| {
+        let mut chunk = String::new();
+        for m in page.map_err(log_export_error)? {
+            if !first {
+                chunk.push(',');
+            }
+            first = false;
+            let json = serde_json::to_string(&m).expect("MatchRecord serialization cannot fail");
+            chunk.push_str(&json);
+        }
+        Ok::<_, MatchStorageError>(chunk)
+    });
+    let body = stream::once(async { Ok("[".to_string()) })
+        .chain(items)
+        .chain(stream::once(async { Ok("]".to_string()) }));
+    Body::from_stream(body)
+}
+
+/// The CSV header row, then one row per match in `pages`, as they arrive.
+fn csv_export_body(
+    pages: impl Stream<Item = Result<Vec<MatchRecord>, MatchStorageError>> + Send + 'static,
+) -> Body {
+    let rows = pages.map(|page| {
+        let page = page.map_err(log_export_error)?;
+        Ok::<_, MatchStorageError>(page.iter().map(match_csv_row).collect::<String>())
+    });
+    let body = stream::once(async { Ok(csv::row(&MATCH_CSV_COLUMNS)) }).chain(rows);
+    Body::from_stream(body)
+}
+
+/// Columns an import must have; the rest of `MATCH_CSV_COLUMNS` is optional.
+const IMPORT_REQUIRED_COLUMNS: [&str; 3] = ["played_at", "winner1_id", "loser1_id"];
 
-    Ok(Json(all_matches))
+/// A CSV row that couldn't be imported.
+#[derive(Debug, Serialize)]
+pub struct ImportRowError {
+    /// CSV record number, counting the header as row 1.
+    pub row: usize,
+    pub error: String,
+}
+
+/// Outcome of `POST /api/matches/import`.
+#[derive(Debug, Serialize)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub failed: usize,
+    pub errors: Vec<ImportRowError>,
+}
+
+/// POST /api/matches/import — Record historical matches from a CSV body.
+///
+/// The body is CSV text with a header row naming the export's columns
+/// (`MATCH_CSV_COLUMNS`, any order; `played_at`, `winner1_id` and
+/// `loser1_id` required). `id`, `recorded_by` and `session_id` are ignored:
+/// imported matches get fresh IDs and are recorded by the importing user.
+/// `?tz=` applies to naive `played_at` values, as when recording a match.
+///
+/// Admin only. Each row goes through the same validation as
+/// `POST /api/matches`, plus a check that every player exists; bad rows are
+/// reported in `errors` and skipped, the rest are stored. The checks aimed at
+/// live entry — quiet hours, duplicates, the daily cap and
+/// `RECORDER_MUST_PARTICIPATE` — don't apply to historical data, and no
+/// webhooks fire. A malformed header or CSV is a 400 and imports nothing.
+pub async fn import_matches(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    Extension(claims): Extension<SessionClaims>,
+    Extension(metrics): Extension<Metrics>,
+    Query(query): Query<CreateMatchQuery>,
+    body: String,
+) -> Result<Json<ImportSummary>, MatchStorageError> {
+    let tz = parse_tz(query.tz.as_deref())?;
+
+    let records =
+        csv::parse(&body).map_err(|e| MatchStorageError::Invalid(format!("CSV: {e}")))?;
+    let Some((header, rows)) = records.split_first() else {
+        return Err(MatchStorageError::Invalid("CSV is empty".to_string()));
+    };
+    let columns: HashMap<&str, usize> = header
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.trim(), i))
+        .collect();
+    if let Some(missing) = IMPORT_REQUIRED_COLUMNS.iter().find(|c| !columns.contains_key(*c)) {
+        return Err(MatchStorageError::Invalid(format!("CSV is missing column '{missing}'")));
+    }
+
+    let known_players = known_player_ids(&storage).await?;
+
+    let mut summary = ImportSummary {
+        imported: 0,
+        failed: 0,
+        errors: Vec::new(),
+    };
+    for (i, row) in rows.iter().enumerate() {
+        let get = |name: &str| {
+            columns
+                .get(name)
+                .and_then(|&col| row.get(col))
+                .map(|v| v.trim())
+                .unwrap_or("")
+        };
+        let result = match import_request(get) {
+            Ok(req) => import_row(&storage, &config, &claims, tz, &known_players, req).await,
+            Err(e) => Err(MatchStorageError::Invalid(e)),
+        };
+
+        match result {
+            Ok(created) => {
+                summary.imported += 1;
+                metrics.matches_created(1);
+                events::log_match_event(
+                    &config,
+                    &storage,
+                    MatchEventKind::Created,
+                    &created.recorded_by,
+                    &created,
+                )
+                .await;
+            }
+            Err(e) => {
+                if let MatchStorageError::Azure(_) = e {
+                    tracing::error!("Match import row {} failed: {e}", i + 2);
+                }
+                summary.failed += 1;
+                summary.errors.push(ImportRowError {
+                    row: i + 2,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(Json(summary))
+}
+
+/// Validate and store one imported match.
+async fn import_row(
+    storage: &StorageClient,
+    config: &AppConfig,
+    claims: &SessionClaims,
+    tz: Option<Tz>,
+    known_players: &HashSet<String>,
+    req: CreateMatchRequest,
+) -> Result<MatchRecord, MatchStorageError> {
+    let record = build_record(config, claims, req, tz)?;
+    ensure_known_players(&record, known_players)?;
+    matches::create_match(storage, record).await
+}
+
+/// IDs of every player, archived ones included, for checking lineups.
+///
+/// One partition query, so checking a match costs a single round trip
+/// however many players it has.
+async fn known_player_ids(storage: &StorageClient) -> Result<HashSet<String>, MatchStorageError> {
+    Ok(players::list_players(storage)
+        .await
+        .map_err(|e| MatchStorageError::Azure(e.to_string()))?
+        .into_iter()
+        .map(|p| p.id)
+        .collect())
+}
+
+/// Reject a match naming players that don't exist, listing all of them;
+/// otherwise it would show up as "Unknown" everywhere.
+fn ensure_known_players(
+    record: &MatchRecord,
+    known_players: &HashSet<String>,
+) -> Result<(), MatchStorageError> {
+    let unknown: Vec<String> = record
+        .player_ids()
+        .filter(|id| !known_players.contains(*id))
+        .map(|id| format!("'{id}'"))
+        .collect();
+    match unknown.len() {
+        0 => Ok(()),
+        1 => Err(MatchStorageError::Invalid(format!("unknown player {}", unknown[0]))),
+        _ => Err(MatchStorageError::Invalid(format!(
+            "unknown players {}",
+            unknown.join(", ")
+        ))),
+    }
+}
+
+/// Build a create request from one import row, read through `get` (column
+/// name → trimmed value, empty when absent).
+fn import_request<'a>(get: impl Fn(&str) -> &'a str) -> Result<CreateMatchRequest, String> {
+    let optional = |name: &str| Some(get(name).to_string()).filter(|v| !v.is_empty());
+    let score = |name: &str| {
+        optional(name)
+            .map(|v| v.parse::<i32>().map_err(|_| format!("invalid {name} '{v}'")))
+            .transpose()
+    };
+    let forfeit = match get("forfeit").to_ascii_lowercase().as_str() {
+        "" | "false" | "0" => false,
+        "true" | "1" => true,
+        other => return Err(format!("invalid forfeit '{other}'")),
+    };
+    let played_at = optional("played_at").ok_or("played_at is required")?;
+
+    Ok(CreateMatchRequest {
+        winner1_id: get("winner1_id").to_string(),
+        winner2_id: optional("winner2_id"),
+        loser1_id: get("loser1_id").to_string(),
+        loser2_id: optional("loser2_id"),
+        winner_score: score("winner_score")?,
+        loser_score: score("loser_score")?,
+        comment: get("comment").to_string(),
+        played_at: Some(played_at),
+        league_id: optional("league_id"),
+        forfeit,
+    })
+}
+
+/// Default and maximum page sizes for the match feed.
+const FEED_DEFAULT_LIMIT: usize = 20;
+const FEED_MAX_LIMIT: usize = 100;
+
+/// Query parameters for the match feed.
+#[derive(Deserialize)]
+pub struct MatchFeedQuery {
+    /// `next_cursor` from the previous page. Omit for the first page.
+    pub cursor: Option<String>,
+    /// Page size (default 20, capped at 100).
+    pub limit: Option<usize>,
+    /// Only matches this player took part in.
+    pub player_id: Option<String>,
+    pub league_id: Option<String>,
+    /// Only matches played at or after this instant (RFC 3339).
+    pub from: Option<DateTime<Utc>>,
+    /// Only matches played at or before this instant (RFC 3339).
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// A match with player display names resolved, for rendering without
+/// a second lookup on the client.
+#[derive(Debug, Serialize)]
+pub struct MatchWithNames {
+    #[serde(flatten)]
+    pub record: MatchRecord,
+    pub winner1_name: String,
+    /// `None` for singles.
+    pub winner2_name: Option<String>,
+    pub loser1_name: String,
+    /// `None` for singles.
+    pub loser2_name: Option<String>,
+}
+
+/// One page of the match feed.
+#[derive(Debug, Serialize)]
+pub struct MatchFeed {
+    pub items: Vec<MatchWithNames>,
+    /// Pass back as `cursor` to fetch the next page; `null` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+/// GET /api/matches/feed — Paginated match history for infinite scroll.
+///
+/// Unlike `GET /api/matches`, filtering happens in the storage query and only
+/// one page is fetched, so scrolling deep into history stays cheap. Player
+/// names are resolved from a single player listing; deleted players fall back
+/// to their ID. Losers are anonymized for display screens, and `player_id`
+/// refused there, as in `list_matches`.
+pub async fn get_match_feed(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    claims: Option<Extension<SessionClaims>>,
+    Query(query): Query<MatchFeedQuery>,
+) -> Result<Json<MatchFeed>, MatchStorageError> {
+    let anonymize = hide_losers(&config, claims.as_deref());
+    ensure_player_filter_allowed(anonymize, query.player_id.as_deref())?;
+    let limit = query
+        .limit
+        .unwrap_or(FEED_DEFAULT_LIMIT)
+        .clamp(1, FEED_MAX_LIMIT);
+    let filter = MatchPageFilter {
+        player_id: query.player_id.as_deref(),
+        league_id: query.league_id.as_deref(),
+        from: query.from,
+        to: query.to,
+    };
+
+    let (page, next_cursor) =
+        matches::list_matches_page(&storage, &filter, query.cursor.as_deref(), limit).await?;
+
+    let all_players = players::list_players(&storage)
+        .await
+        .map_err(|e| MatchStorageError::Azure(e.to_string()))?;
+    let items = with_names(page, &all_players, anonymize);
+
+    Ok(Json(MatchFeed { items, next_cursor }))
+}
+
+/// Resolve display names for a page of matches; unknown players fall back to
+/// their ID. With `anonymize`, losers are hidden first.
+fn with_names(
+    page: Vec<MatchRecord>,
+    all_players: &[Player],
+    anonymize: bool,
+) -> Vec<MatchWithNames> {
+    let names: HashMap<&str, &str> = all_players
+        .iter()
+        .map(|p| (p.id.as_str(), p.name.as_str()))
+        .collect();
+    let name = |id: &str| {
+        names.get(id).copied().unwrap_or_else(|| display_name_fallback(id)).to_string()
+    };
+
+    page
+        .into_iter()
+        .map(|mut record| {
+            if anonymize {
+                record.anonymize_losers();
+            }
+            MatchWithNames {
+                winner1_name: name(&record.winner1_id),
+                winner2_name: record.winner2_id.as_deref().map(name),
+                loser1_name: name(&record.loser1_id),
+                loser2_name: record.loser2_id.as_deref().map(name),
+                record,
+            }
+        })
+        .collect()
 }
 
 /// POST /api/matches — Record a new match result.
 ///
 /// The `recorded_by` field is automatically set from the authenticated user's
 /// session claims (their Microsoft OID), replacing the old "anonymous" hardcode.
+///
+/// Impossible results (negative scores, a winner not outscoring the loser, a
+/// player in two slots) are rejected with `400` — see `MatchRecord::validate`.
+/// So are player IDs that don't exist; the error lists all of them.
+///
+/// With `RECORDER_MUST_PARTICIPATE` on, non-admins must have a linked player
+/// that is one of the participants.
+///
+/// During `QUIET_HOURS` (evaluated against the current time in `TIMEZONE`)
+/// every submission is rejected with `422` and `"code": "quiet_hours"`.
+///
+/// With `MAX_MATCHES_PER_PLAYER_PER_DAY` set, a match that would put any
+/// participant over the cap for that calendar day is rejected with `429`.
+///
+/// Non-blocking concerns (e.g. a suspected duplicate under the `warn` policy)
+/// are returned in `X-Scoreboard-Warning` headers alongside the `201`.
+///
+/// With `?tz=`, a `played_at` without an offset is read as local time in that
+/// zone (stored as UTC), and the response includes `played_at_local`.
+///
+/// An `Idempotency-Key` header makes retries safe: a key the same user already
+/// sent within `IDEMPOTENCY_KEY_TTL_SECS` returns the match it created with
+/// `200` instead of recording another; one whose first request is still
+/// running gets `409`. Keys are remembered in memory only (see idempotency.rs).
 pub async fn create_match(
     State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
     Extension(claims): Extension<SessionClaims>,
-    Json(req): Json<CreateMatchRequest>,
-) -> Result<(StatusCode, Json<MatchRecord>), MatchStorageError> {
-    let played_at = req.played_at.unwrap_or_else(Utc::now);
-
-    let record = MatchRecord::new(
-        req.winner1_id,
-        req.winner2_id,
-        req.loser1_id,
-        req.loser2_id,
-        req.winner_score,
-        req.loser_score,
-        req.comment,
-        claims.sub,
-        played_at,
-        req.league_id,
-    );
+    Extension(metrics): Extension<Metrics>,
+    Query(query): Query<CreateMatchQuery>,
+    idempotency_key: IdempotencyKey,
+    ApiJson(req): ApiJson<CreateMatchRequest>,
+) -> Result<(StatusCode, HeaderMap, Json<LocalizedMatch>), MatchStorageError> {
+    let tz = parse_tz(query.tz.as_deref())?;
+
+    // Scoped to the user, so one person's key can't replay another's match.
+    let idempotency_guard = match idempotency_key.claim(&claims.sub) {
+        None => None,
+        Some(Claim::New(guard)) => Some(guard),
+        Some(Claim::Done(match_id)) => {
+            let original = matches::get_match(&storage, &match_id).await?;
+            return Ok((
+                StatusCode::OK,
+                HeaderMap::new(),
+                Json(LocalizedMatch::new(original, tz)),
+            ));
+        }
+        Some(Claim::InFlight) => {
+            return Err(MatchStorageError::Duplicate(
+                "A request with this Idempotency-Key is still being processed".to_string(),
+            ));
+        }
+    };
+
+    ensure_recording_open(&config, Utc::now())?;
+    let record = record_from_request(&config, &claims, req, tz)?;
+    ensure_known_players(&record, &known_player_ids(&storage).await?)?;
+
+    let mut warnings: Vec<String> = Vec::new();
+
+    // Both checks below need match history; fetch it at most once.
+    let needs_history = config.duplicate_match_policy != DuplicatePolicy::Off
+        || config.max_matches_per_player_per_day.is_some()
+        || config.repeat_matchup_threshold.is_some();
+    let recent = if needs_history {
+        matches::list_matches(&storage, None).await?
+    } else {
+        Vec::new()
+    };
+
+    if let Some(cap) = config.max_matches_per_player_per_day
+        && let Some(player_id) = player_over_daily_cap(&recent, &record, cap, config.timezone)
+    {
+        return Err(MatchStorageError::TooMany(format!(
+            "{player_id} already has {cap} matches on that day"
+        )));
+    }
+
+    if config.duplicate_match_policy != DuplicatePolicy::Off {
+        let window = Duration::seconds(config.duplicate_match_window_secs);
+        if let Some(previous) = find_near_duplicate(&recent, &record, window) {
+            let message = format!(
+                "Same players and score as match {} you recorded moments ago",
+                previous.id
+            );
+            if config.duplicate_match_policy == DuplicatePolicy::Reject {
+                return Err(MatchStorageError::Duplicate(message));
+            }
+            warnings.push(message);
+        }
+    }
+
+    warnings.extend(repeat_matchup_warning(&config, &recent, &record));
 
     let created = matches::create_match(&storage, record).await?;
-    Ok((StatusCode::CREATED, Json(created)))
+    if let Some(guard) = idempotency_guard {
+        guard.complete(created.id.clone());
+    }
+    metrics.matches_created(1);
+    events::log_match_event(
+        &config,
+        &storage,
+        MatchEventKind::Created,
+        &created.recorded_by,
+        &created,
+    )
+    .await;
+
+    // Announce the match (no-op unless MATCH_WEBHOOK_URL is set). Runs in the
+    // background so a slow receiver never delays the response.
+    webhook::notify_match_recorded(&config, storage.clone(), created.clone());
+
+    let mut headers = HeaderMap::new();
+    for warning in warnings {
+        if let Ok(value) = HeaderValue::from_str(&warning) {
+            headers.append(WARNING_HEADER, value);
+        }
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        headers,
+        Json(LocalizedMatch::new(created, tz)),
+    ))
+}
+
+/// Turn a create request into a validated, not yet stored, `MatchRecord`
+/// recorded by `claims.sub`. A missing `played_at` means now.
+fn record_from_request(
+    config: &AppConfig,
+    claims: &SessionClaims,
+    req: CreateMatchRequest,
+    tz: Option<Tz>,
+) -> Result<MatchRecord, MatchStorageError> {
+    let record = build_record(config, claims, req, tz)?;
+    ensure_recorder_participates(config, claims, &record)?;
+    Ok(record)
+}
+
+/// `record_from_request` without the live-entry recorder check, for imports.
+fn build_record(
+    config: &AppConfig,
+    claims: &SessionClaims,
+    req: CreateMatchRequest,
+    tz: Option<Tz>,
+) -> Result<MatchRecord, MatchStorageError> {
+    let (winner2_id, loser2_id) =
+        second_players(req.winner2_id, req.loser2_id).map_err(MatchStorageError::Invalid)?;
+
+    let played_at = match req.played_at.as_deref() {
+        Some(raw) => parse_played_at(raw, tz).map_err(MatchStorageError::Invalid)?,
+        None => Utc::now(),
+    };
+    let comment = sanitize_comment(
+        &req.comment,
+        config.comment_max_graphemes,
+        config.comment_max_bytes,
+    )
+    .map_err(MatchStorageError::Invalid)?;
+
+    let record = MatchRecord::new(NewMatch {
+        winner1_id: req.winner1_id,
+        winner2_id,
+        loser1_id: req.loser1_id,
+        loser2_id,
+        winner_score: req.winner_score,
+        loser_score: req.loser_score,
+        comment,
+        recorded_by: claims.sub.clone(),
+        played_at,
+        league_id: req.league_id,
+        forfeit: req.forfeit,
+    })
+    .map_err(|e| MatchStorageError::Invalid(e.to_string()))?;
+    record.validate().map_err(MatchStorageError::Invalid)?;
+    Ok(record)
+}
+
+/// Reject submissions made at `now` if it falls in `QUIET_HOURS`.
+fn ensure_recording_open(config: &AppConfig, now: DateTime<Utc>) -> Result<(), MatchStorageError> {
+    if let Some(quiet) = config.quiet_hours
+        && quiet.contains(now.with_timezone(&config.timezone).time())
+    {
+        return Err(MatchStorageError::QuietHours(format!(
+            "Match recording is closed between {} and {}. Go to bed!",
+            quiet.start.format("%H:%M"),
+            quiet.end.format("%H:%M")
+        )));
+    }
+    Ok(())
+}
+
+/// With `RECORDER_MUST_PARTICIPATE`, require a non-admin recorder's linked
+/// player to be one of `record`'s participants.
+fn ensure_recorder_participates(
+    config: &AppConfig,
+    claims: &SessionClaims,
+    record: &MatchRecord,
+) -> Result<(), MatchStorageError> {
+    if !config.recorder_must_participate || claims.is_admin {
+        return Ok(());
+    }
+    if claims.player_id.as_deref().is_some_and(|pid| record.has_player(pid)) {
+        Ok(())
+    } else {
+        Err(MatchStorageError::Forbidden(
+            "You can only record matches you played in".to_string(),
+        ))
+    }
+}
+
+/// Time between consecutive games of a session. Just enough to keep them in
+/// entry order; the real gaps aren't known.
+const SESSION_GAME_SPACING_SECS: i64 = 1;
+
+/// The games recorded by `POST /api/matches/session`.
+#[derive(Debug, Serialize)]
+pub struct RecordedSession {
+    pub session_id: String,
+    /// In entry order (oldest first).
+    pub matches: Vec<LocalizedMatch>,
+}
+
+/// POST /api/matches/session — Record several games from one sitting.
+///
+/// Game `i` (0-based) is played at `played_at_start + i` seconds, so the
+/// games keep their entry order, and all of them share a fresh `session_id`.
+/// Each game is validated like a single match (scores, lineup, recorder
+/// participation, daily cap, quiet hours); if any is rejected, nothing is
+/// stored. At most `SESSION_MAX_GAMES` games per request. Duplicate detection
+/// is skipped — identical back-to-back games are normal in a session.
+///
+/// `?tz=` works as for `POST /api/matches`.
+pub async fn create_match_session(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    Extension(claims): Extension<SessionClaims>,
+    Extension(metrics): Extension<Metrics>,
+    Query(query): Query<CreateMatchQuery>,
+    ApiJson(req): ApiJson<CreateSessionRequest>,
+) -> Result<(StatusCode, Json<RecordedSession>), MatchStorageError> {
+    let tz = parse_tz(query.tz.as_deref())?;
+    if req.games.is_empty() {
+        return Err(MatchStorageError::Invalid(
+            "A session needs at least one game".to_string(),
+        ));
+    }
+    if req.games.len() > config.session_max_games {
+        return Err(MatchStorageError::Invalid(format!(
+            "A session can have at most {} games",
+            config.session_max_games
+        )));
+    }
+    ensure_recording_open(&config, Utc::now())?;
+
+    let start = parse_played_at(&req.played_at_start, tz).map_err(MatchStorageError::Invalid)?;
+    let session_id = uuid::Uuid::new_v4().to_string();
+
+    let records = session_records(&config, &claims, req.games, start, req.league_id, &session_id)?;
+    let known_players = known_player_ids(&storage).await?;
+    for (i, record) in records.iter().enumerate() {
+        ensure_known_players(record, &known_players).map_err(|e| match e {
+            MatchStorageError::Invalid(msg) => {
+                MatchStorageError::Invalid(format!("game {}: {msg}", i + 1))
+            }
+            e => e,
+        })?;
+    }
+
+    if let Some(cap) = config.max_matches_per_player_per_day {
+        let mut existing = matches::list_matches(&storage, None).await?;
+        for record in &records {
+            if let Some(player_id) = player_over_daily_cap(&existing, record, cap, config.timezone)
+            {
+                return Err(MatchStorageError::TooMany(format!(
+                    "{player_id} already has {cap} matches on that day"
+                )));
+            }
+            existing.push(record.clone());
+        }
+    }
+
+    let created = matches::create_matches(&storage, records).await?;
+    metrics.matches_created(created.len() as u64);
+    for record in &created {
+        events::log_match_event(
+            &config,
+            &storage,
+            MatchEventKind::Created,
+            &record.recorded_by,
+            record,
+        )
+        .await;
+        webhook::notify_match_recorded(&config, storage.clone(), record.clone());
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Json(RecordedSession {
+            session_id,
+            matches: created
+                .into_iter()
+                .map(|m| LocalizedMatch::new(m, tz))
+                .collect(),
+        }),
+    ))
+}
+
+/// Build and validate the records for a session's games, spaced
+/// `SESSION_GAME_SPACING_SECS` apart from `start` in entry order.
+fn session_records(
+    config: &AppConfig,
+    claims: &SessionClaims,
+    games: Vec<SessionGame>,
+    start: DateTime<Utc>,
+    league_id: Option<String>,
+    session_id: &str,
+) -> Result<Vec<MatchRecord>, MatchStorageError> {
+    let mut records = Vec::with_capacity(games.len());
+    for (i, game) in games.into_iter().enumerate() {
+        let played_at = start + Duration::seconds(i as i64 * SESSION_GAME_SPACING_SECS);
+        let record = session_game_record(
+            config,
+            game,
+            played_at,
+            &claims.sub,
+            league_id.clone(),
+            session_id,
+        )
+        .map_err(|e| MatchStorageError::Invalid(format!("game {}: {e}", i + 1)))?;
+        ensure_recorder_participates(config, claims, &record)?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Build and validate the record for one game of a session.
+fn session_game_record(
+    config: &AppConfig,
+    game: SessionGame,
+    played_at: DateTime<Utc>,
+    recorded_by: &str,
+    league_id: Option<String>,
+    session_id: &str,
+) -> Result<MatchRecord, String> {
+    let (winner1_id, winner2_id) = one_or_two(game.winners, "winners")?;
+    let (loser1_id, loser2_id) = one_or_two(game.losers, "losers")?;
+    let (winner2_id, loser2_id) = second_players(winner2_id, loser2_id)?;
+    let comment = sanitize_comment(
+        &game.comment,
+        config.comment_max_graphemes,
+        config.comment_max_bytes,
+    )?;
+
+    let mut record = MatchRecord::new(NewMatch {
+        winner1_id,
+        winner2_id,
+        loser1_id,
+        loser2_id,
+        winner_score: game.winner_score,
+        loser_score: game.loser_score,
+        comment,
+        recorded_by: recorded_by.to_string(),
+        played_at,
+        league_id,
+        forfeit: game.forfeit,
+    })
+    .map_err(|e| e.to_string())?;
+    record.session_id = Some(session_id.to_string());
+    record.validate()?;
+    Ok(record)
+}
+
+/// Split a team list into its first and optional second player.
+fn one_or_two(ids: Vec<String>, side: &str) -> Result<(String, Option<String>), String> {
+    let mut ids = ids.into_iter();
+    match (ids.next(), ids.next(), ids.next()) {
+        (Some(first), second, None) => Ok((first, second)),
+        _ => Err(format!("{side} must list one or two players")),
+    }
+}
+
+/// Find the recorder's previous match if `candidate` looks like a resubmit
+/// of it: same players (any arrangement), same scores, and `played_at`
+/// within `window`.
+///
+/// Only the recorder's most recent match is considered — this catches
+/// double-taps, not legitimate rematches recorded later.
+fn find_near_duplicate<'a>(
+    existing: &'a [MatchRecord],
+    candidate: &MatchRecord,
+    window: Duration,
+) -> Option<&'a MatchRecord> {
+    existing
+        .iter()
+        .find(|m| m.recorded_by == candidate.recorded_by)
+        .filter(|previous| {
+            previous.same_lineup(candidate)
+                && previous.forfeit == candidate.forfeit
+                && previous.winner_score == candidate.winner_score
+                && previous.loser_score == candidate.loser_score
+                && (previous.played_at - candidate.played_at).abs() <= window
+        })
+}
+
+/// How many of `existing` have `candidate`'s lineup (same players, any
+/// arrangement) and were played within `window` before it.
+fn count_recent_lineup(
+    existing: &[MatchRecord],
+    candidate: &MatchRecord,
+    window: Duration,
+) -> usize {
+    let since = candidate.played_at - window;
+    existing
+        .iter()
+        .filter(|m| m.played_at >= since && m.played_at <= candidate.played_at)
+        .filter(|m| m.same_lineup(candidate))
+        .count()
+}
+
+/// The `REPEAT_MATCHUP_THRESHOLD` warning for `candidate`, if its lineup
+/// (counting `candidate` itself) is past the threshold within the window.
+fn repeat_matchup_warning(
+    config: &AppConfig,
+    existing: &[MatchRecord],
+    candidate: &MatchRecord,
+) -> Option<String> {
+    let threshold = config.repeat_matchup_threshold?;
+    let days = config.repeat_matchup_window_days;
+    let times = count_recent_lineup(existing, candidate, Duration::days(days)) + 1;
+    (times > threshold as usize)
+        .then(|| format!("You've played these exact teams {times} times in the last {days} days"))
+}
+
+/// The first participant of `candidate` who already has `cap` matches on the
+/// same calendar day (in `tz`) as `candidate.played_at`, if any.
+fn player_over_daily_cap<'a>(
+    existing: &[MatchRecord],
+    candidate: &'a MatchRecord,
+    cap: u32,
+    tz: Tz,
+) -> Option<&'a str> {
+    let day = candidate.played_at.with_timezone(&tz).date_naive();
+    let same_day: Vec<&MatchRecord> = existing
+        .iter()
+        .filter(|m| m.played_at.with_timezone(&tz).date_naive() == day)
+        .collect();
+
+    candidate
+        .player_ids()
+        .find(|id| same_day.iter().filter(|m| m.has_player(id)).count() >= cap as usize)
 }
 
 /// PUT /api/matches/{id} — Update an existing match.
@@ -123,66 +1253,774 @@ pub async fn create_match(
 /// Mutable fields from the request: players, scores, comment, league_id.
 pub async fn update_match(
     State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
     Extension(claims): Extension<SessionClaims>,
     Path(id): Path<String>,
-    Json(req): Json<UpdateMatchRequest>,
+    ApiJson(req): ApiJson<UpdateMatchRequest>,
 ) -> Result<Json<MatchRecord>, MatchStorageError> {
     // Fetch the existing match to check authorization and preserve immutable fields.
     let existing = matches::get_match(&storage, &id).await?;
 
     // Authorization check: admin or participant.
-    let is_admin = claims.role == "admin";
-    let is_participant = claims.player_id.as_ref().is_some_and(|pid| {
-        // Check if the user's linked player_id matches any player in the match.
-        // We check both the existing match players AND the new request players
-        // to cover the case where the user is being removed from the match.
-        pid == &existing.winner1_id
-            || pid == &existing.winner2_id
-            || pid == &existing.loser1_id
-            || pid == &existing.loser2_id
-    });
+    // Check if the user's linked player_id matches any player in the match.
+    let is_participant = claims
+        .player_id
+        .as_deref()
+        .is_some_and(|pid| existing.has_player(pid));
 
-    if !is_admin && !is_participant {
+    if !claims.is_admin && !is_participant {
         return Err(MatchStorageError::Forbidden(
             "Only admins or match participants can edit matches".to_string(),
         ));
     }
 
+    let comment = sanitize_comment(
+        &req.comment,
+        config.comment_max_graphemes,
+        config.comment_max_bytes,
+    )
+    .map_err(MatchStorageError::Invalid)?;
+    let (winner2_id, loser2_id) =
+        second_players(req.winner2_id, req.loser2_id).map_err(MatchStorageError::Invalid)?;
+    let match_type = if winner2_id.is_some() {
+        MatchType::Doubles
+    } else {
+        MatchType::Singles
+    };
+
     // Build the updated record, preserving immutable fields from the original.
     let updated = MatchRecord {
         id: existing.id,
+        match_type,
         winner1_id: req.winner1_id,
-        winner2_id: req.winner2_id,
+        winner2_id,
         loser1_id: req.loser1_id,
-        loser2_id: req.loser2_id,
+        loser2_id,
         winner_score: req.winner_score,
         loser_score: req.loser_score,
-        comment: req.comment,
+        comment,
         recorded_by: existing.recorded_by,
         played_at: existing.played_at,
         league_id: req.league_id,
+        forfeit: req.forfeit,
+        session_id: existing.session_id,
     };
+    updated.validate().map_err(MatchStorageError::Invalid)?;
+    ensure_known_players(&updated, &known_player_ids(&storage).await?)?;
 
     let result = matches::update_match(&storage, updated).await?;
     Ok(Json(result))
 }
 
+/// Query parameters for deleting a match.
+#[derive(Deserialize)]
+pub struct DeleteMatchQuery {
+    /// Remove the row for good instead of soft-deleting it.
+    #[serde(default)]
+    pub hard: bool,
+}
+
 /// DELETE /api/matches/{id} — Delete a match.
 ///
-/// Restricted to admins only. Regular users should edit matches instead
-/// of deleting them (preserves audit trail).
+/// Restricted to admins (via `require_admin`). Regular users should edit
+/// matches instead of deleting them (preserves audit trail). With `EVENT_LOG` on, the
+/// deleted record is kept in the event log, for hard deletes too (a match
+/// soft-deleted earlier was logged then and isn't logged twice).
+///
+/// Deletes are soft — the match vanishes from listings and stats but can be
+/// brought back with `POST /api/matches/{id}/restore`. `?hard=true` removes
+/// it permanently (including an already soft-deleted match).
 pub async fn delete_match(
     State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
     Extension(claims): Extension<SessionClaims>,
     Path(id): Path<String>,
+    Query(query): Query<DeleteMatchQuery>,
 ) -> Result<StatusCode, MatchStorageError> {
-    // Only admins can delete matches.
-    if claims.role != "admin" {
+    // The event log keeps the full record, so read it before it's gone,
+    // soft-deleted or not. A match that was already soft-deleted had its
+    // deletion logged back then; every other delete, hard ones included, is
+    // logged now.
+    let to_log = if config.event_log {
+        let (existing, already_deleted) = matches::get_match_with_deleted(&storage, &id).await?;
+        (!already_deleted).then_some(existing)
+    } else {
+        None
+    };
+
+    if query.hard {
+        matches::hard_delete_match(&storage, &id).await?;
+    } else {
+        matches::delete_match(&storage, &id).await?;
+    }
+
+    if let Some(existing) = to_log {
+        events::log_match_event(
+            &config,
+            &storage,
+            MatchEventKind::Deleted,
+            &claims.sub,
+            &existing,
+        )
+        .await;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/matches/undo — Delete the newest match the caller recorded.
+///
+/// For "oops, wrong score": responds with the deleted `MatchRecord` so the
+/// SPA can offer to record it again prefilled. Only the caller's own
+/// entries are considered, so nobody can undo someone else's match; the
+/// delete is soft, so an admin can still restore it. 404 if the caller
+/// hasn't recorded any matches.
+pub async fn undo_last_match(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    Extension(claims): Extension<SessionClaims>,
+) -> Result<Response, MatchStorageError> {
+    // Newest first, so the first hit is the caller's latest entry.
+    let Some(last) = matches::list_matches(&storage, None)
+        .await?
+        .into_iter()
+        .find(|m| m.recorded_by == claims.sub)
+    else {
+        return Ok((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "You haven't recorded any matches to undo"})),
+        )
+            .into_response());
+    };
+
+    matches::delete_match(&storage, &last.id).await?;
+    events::log_match_event(&config, &storage, MatchEventKind::Deleted, &claims.sub, &last).await;
+
+    Ok(Json(last).into_response())
+}
+
+/// POST /api/matches/{id}/restore — Undo a (soft) delete. Admins only.
+///
+/// Returns the restored match; restoring one that isn't deleted just
+/// returns it. Hard-deleted matches are gone (404).
+pub async fn restore_match(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    Extension(claims): Extension<SessionClaims>,
+    Path(id): Path<String>,
+) -> Result<Json<MatchRecord>, MatchStorageError> {
+    if !claims.is_admin {
         return Err(MatchStorageError::Forbidden(
-            "Only admins can delete matches".to_string(),
+            "Only admins can restore matches".to_string(),
         ));
     }
 
-    matches::delete_match(&storage, &id).await?;
-    Ok(StatusCode::NO_CONTENT)
+    let restored = matches::restore_match(&storage, &id).await?;
+    events::log_match_event(
+        &config,
+        &storage,
+        MatchEventKind::Restored,
+        &claims.sub,
+        &restored,
+    )
+    .await;
+    Ok(Json(restored))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, TimeZone};
+
+    fn claims() -> SessionClaims {
+        SessionClaims {
+            sub: "user-1".to_string(),
+            name: "Alice".to_string(),
+            email: "alice@example.com".to_string(),
+            role: "user".to_string(),
+            is_admin: false,
+            player_id: None,
+            exp: 0,
+            iat: 0,
+        }
+    }
+
+    fn doubles() -> MatchRecord {
+        MatchRecord::new(NewMatch {
+            winner1_id: "alice".to_string(),
+            winner2_id: Some("bob".to_string()),
+            loser1_id: "carol".to_string(),
+            loser2_id: Some("dave".to_string()),
+            winner_score: Some(11),
+            loser_score: Some(7),
+            played_at: Utc::now(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn display_screens_get_the_anonymized_view() {
+        let mut config = AppConfig::for_tests();
+        config.anonymize_losers = true;
+        assert!(hide_losers(&config, None));
+        assert!(!hide_losers(&config, Some(&claims())));
+    }
+
+    #[test]
+    fn nothing_is_hidden_with_the_flag_off() {
+        let config = AppConfig::for_tests();
+        assert!(!hide_losers(&config, None));
+        assert!(!hide_losers(&config, Some(&claims())));
+    }
+
+    #[test]
+    fn anonymizing_keeps_winners_and_hides_losers() {
+        let mut record = doubles();
+        record.anonymize_losers();
+        assert_eq!(record.winner_ids().collect::<Vec<_>>(), ["alice", "bob"]);
+        assert_eq!(
+            record.loser_ids().collect::<Vec<_>>(),
+            [ANONYMOUS_PLAYER_ID, ANONYMOUS_PLAYER_ID]
+        );
+        // The loser is usually the one who entered the result.
+        assert_eq!(record.recorded_by, "");
+    }
+
+    #[test]
+    fn player_filters_are_refused_on_the_anonymized_view() {
+        let err = ensure_player_filter_allowed(true, Some("carol")).unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::FORBIDDEN);
+        assert!(ensure_player_filter_allowed(true, None).is_ok());
+        assert!(ensure_player_filter_allowed(false, Some("carol")).is_ok());
+    }
+
+    #[test]
+    fn anonymizing_singles_adds_no_phantom_partner() {
+        let mut record = doubles();
+        record.winner2_id = None;
+        record.loser2_id = None;
+        record.anonymize_losers();
+        assert_eq!(record.loser1_id, ANONYMOUS_PLAYER_ID);
+        assert_eq!(record.loser2_id, None);
+    }
+
+    #[test]
+    fn recorders_must_have_played_when_required() {
+        let mut config = AppConfig::for_tests();
+        config.recorder_must_participate = true;
+        let record = doubles();
+
+        let participant = SessionClaims {
+            player_id: Some("carol".to_string()),
+            ..claims()
+        };
+        assert!(ensure_recorder_participates(&config, &participant, &record).is_ok());
+
+        let outsider = SessionClaims {
+            player_id: Some("erin".to_string()),
+            ..claims()
+        };
+        let err = ensure_recorder_participates(&config, &outsider, &record).unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::FORBIDDEN);
+        assert!(ensure_recorder_participates(&config, &claims(), &record).is_err());
+
+        let admin = SessionClaims {
+            is_admin: true,
+            ..claims()
+        };
+        assert!(ensure_recorder_participates(&config, &admin, &record).is_ok());
+    }
+
+    #[test]
+    fn imported_rows_skip_the_recorder_check() {
+        let mut config = AppConfig::for_tests();
+        config.recorder_must_participate = true;
+        let req = || create_request("2024-07-01T18:30");
+
+        let utc = Some(chrono_tz::UTC);
+
+        assert!(record_from_request(&config, &claims(), req(), utc).is_err());
+        assert!(build_record(&config, &claims(), req(), utc).is_ok());
+    }
+
+    #[test]
+    fn anyone_can_record_with_the_check_off() {
+        let config = AppConfig::for_tests();
+        assert!(ensure_recorder_participates(&config, &claims(), &doubles()).is_ok());
+    }
+
+    #[test]
+    fn resubmits_with_rearranged_teams_are_near_duplicates() {
+        let window = Duration::seconds(120);
+        let previous = MatchRecord {
+            recorded_by: "user-1".to_string(),
+            ..doubles()
+        };
+        let existing = [previous.clone()];
+
+        let mut resubmit = previous.clone();
+        resubmit.winner1_id = "bob".to_string();
+        resubmit.winner2_id = Some("alice".to_string());
+        resubmit.played_at += Duration::seconds(30);
+        let found = find_near_duplicate(&existing, &resubmit, window);
+        assert_eq!(found.map(|m| m.id.as_str()), Some(previous.id.as_str()));
+
+        let other_score = MatchRecord {
+            loser_score: Some(9),
+            ..resubmit.clone()
+        };
+        assert!(find_near_duplicate(&existing, &other_score, window).is_none());
+
+        let much_later = MatchRecord {
+            played_at: previous.played_at + Duration::minutes(10),
+            ..resubmit.clone()
+        };
+        assert!(find_near_duplicate(&existing, &much_later, window).is_none());
+
+        let someone_else = MatchRecord {
+            recorded_by: "user-2".to_string(),
+            ..resubmit
+        };
+        assert!(find_near_duplicate(&existing, &someone_else, window).is_none());
+    }
+
+    #[test]
+    fn only_the_recorders_latest_match_is_compared() {
+        let older = MatchRecord {
+            recorded_by: "user-1".to_string(),
+            ..doubles()
+        };
+        let mut latest = older.clone();
+        latest.loser1_id = "erin".to_string();
+        let existing = [latest, older.clone()];
+        assert!(find_near_duplicate(&existing, &older, Duration::seconds(120)).is_none());
+    }
+
+    #[test]
+    fn feed_items_carry_names_with_id_fallback() {
+        let players = [
+            Player {
+                id: "alice".to_string(),
+                name: "Alice".to_string(),
+                nickname: String::new(),
+                avatar_emoji: default_avatar(),
+                color: None,
+                active: true,
+            },
+            Player {
+                id: "carol".to_string(),
+                name: "Carol".to_string(),
+                nickname: String::new(),
+                avatar_emoji: default_avatar(),
+                color: None,
+                active: false,
+            },
+        ];
+
+        let items = with_names(vec![doubles()], &players, false);
+        assert_eq!(items[0].winner1_name, "Alice");
+        assert_eq!(items[0].winner2_name.as_deref(), Some("bob"));
+        assert_eq!(items[0].loser1_name, "Carol", "archived players keep their name");
+
+        let items = with_names(vec![doubles()], &players, true);
+        assert_eq!(items[0].winner1_name, "Alice");
+        assert_eq!(items[0].record.loser1_id, ANONYMOUS_PLAYER_ID);
+        assert_eq!(items[0].loser1_name, ANONYMOUS_PLAYER_NAME);
+    }
+
+    #[test]
+    fn the_daily_cap_rejects_only_players_already_at_it() {
+        let at = |day, hour| MatchRecord {
+            played_at: Utc.with_ymd_and_hms(2024, 6, day, hour, 0, 0).unwrap(),
+            ..doubles()
+        };
+        let candidate = at(2, 10);
+        let mut singles_with_erin = at(2, 9);
+        singles_with_erin.winner2_id = None;
+        singles_with_erin.loser1_id = "erin".to_string();
+        singles_with_erin.loser2_id = None;
+        // alice played once on the 2nd; the game on the 1st doesn't count.
+        let existing = vec![singles_with_erin, at(1, 20)];
+        let utc = chrono_tz::UTC;
+
+        assert_eq!(player_over_daily_cap(&existing, &candidate, 2, utc), None);
+        assert_eq!(player_over_daily_cap(&existing, &candidate, 1, utc), Some("alice"));
+
+        // 20:00 UTC on the 1st is already the 2nd in Sydney.
+        let sydney = chrono_tz::Australia::Sydney;
+        assert_eq!(player_over_daily_cap(&existing, &candidate, 2, sydney), Some("alice"));
+    }
+
+    fn create_request(played_at: &str) -> CreateMatchRequest {
+        serde_json::from_value(serde_json::json!({
+            "winner1_id": "alice",
+            "loser1_id": "bob",
+            "winner_score": 11,
+            "loser_score": 6,
+            "played_at": played_at,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn local_times_round_trip_through_create_and_list() {
+        let config = AppConfig::for_tests();
+        let berlin = parse_tz(Some("Europe/Berlin")).unwrap();
+
+        let record =
+            record_from_request(&config, &claims(), create_request("2024-07-01T18:30"), berlin)
+                .unwrap();
+        assert_eq!(record.played_at, Utc.with_ymd_and_hms(2024, 7, 1, 16, 30, 0).unwrap());
+
+        let listed = serde_json::to_value(LocalizedMatch::new(record, berlin)).unwrap();
+        assert_eq!(listed["played_at"], "2024-07-01T16:30:00Z");
+        assert_eq!(listed["played_at_local"], "2024-07-01T18:30:00+02:00");
+    }
+
+    #[test]
+    fn local_times_need_a_valid_tz() {
+        let config = AppConfig::for_tests();
+        let naive = || create_request("2024-07-01T18:30");
+        assert!(record_from_request(&config, &claims(), naive(), None).is_err());
+        assert!(parse_tz(Some("Mars/Olympus_Mons")).is_err());
+
+        // Clocks jump from 02:00 to 03:00 on this night in Berlin.
+        let berlin = parse_tz(Some("Europe/Berlin")).unwrap();
+        let gap = create_request("2024-03-31T02:30");
+        assert!(record_from_request(&config, &claims(), gap, berlin).is_err());
+
+        let listed = serde_json::to_value(LocalizedMatch::new(doubles(), None)).unwrap();
+        assert!(listed.get("played_at_local").is_none());
+    }
+
+    #[tokio::test]
+    async fn recording_is_closed_only_inside_quiet_hours() {
+        let mut config = AppConfig::for_tests();
+        config.timezone = chrono_tz::Europe::London;
+        config.quiet_hours = Some("23:00-06:00".parse().unwrap());
+        // London is UTC+1 in summer.
+        let at = |hour, minute| Utc.with_ymd_and_hms(2024, 7, 1, hour, minute, 0).unwrap();
+
+        for inside in [at(22, 0), at(1, 30), at(4, 59)] {
+            let err = ensure_recording_open(&config, inside).unwrap_err();
+            let response = err.into_response();
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY, "{inside}");
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(json["code"], "quiet_hours");
+        }
+        for outside in [at(21, 59), at(5, 0), at(12, 0)] {
+            assert!(ensure_recording_open(&config, outside).is_ok(), "{outside}");
+        }
+
+        config.quiet_hours = None;
+        assert!(ensure_recording_open(&config, at(1, 30)).is_ok());
+    }
+
+    #[test]
+    fn embedded_players_come_from_the_roster_winners_first() {
+        let alice = Player {
+            id: "alice".to_string(),
+            name: "Alice".to_string(),
+            nickname: String::new(),
+            avatar_emoji: "🔥".to_string(),
+            color: None,
+            active: true,
+        };
+        let dave = Player {
+            id: "dave".to_string(),
+            name: "Dave".to_string(),
+            avatar_emoji: "💀".to_string(),
+            color: Some("#1e90ff".to_string()),
+            ..alice.clone()
+        };
+        let roster: HashMap<&str, &Player> = [("alice", &alice), ("dave", &dave)].into();
+
+        let summaries: Vec<PlayerSummary> = doubles()
+            .player_ids()
+            .map(|id| PlayerSummary::resolve(id, &roster))
+            .collect();
+
+        let shown: Vec<(&str, &str, &str)> = summaries
+            .iter()
+            .map(|p| (p.id.as_str(), p.name.as_str(), p.avatar_emoji.as_str()))
+            .collect();
+        let fallback = default_avatar();
+        assert_eq!(
+            shown,
+            [
+                ("alice", "Alice", "🔥"),
+                ("bob", "bob", fallback.as_str()),
+                ("carol", "carol", fallback.as_str()),
+                ("dave", "Dave", "💀"),
+            ]
+        );
+        let colors: Vec<Option<&str>> = summaries.iter().map(|p| p.color.as_deref()).collect();
+        assert_eq!(colors, [None, None, None, Some("#1e90ff")]);
+    }
+
+    #[tokio::test]
+    async fn impossible_results_are_a_400_with_a_json_error() {
+        let config = AppConfig::for_tests();
+        let mut req = create_request("2024-07-01T18:30:00Z");
+        req.loser_score = Some(13);
+
+        let err = record_from_request(&config, &claims(), req, None).unwrap_err();
+
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(json["error"].as_str().unwrap().contains("higher than loser_score"), "{json}");
+    }
+
+    #[test]
+    fn matches_group_into_local_days_newest_first() {
+        let at = |day, hour| MatchRecord {
+            played_at: Utc.with_ymd_and_hms(2024, 6, day, hour, 0, 0).unwrap(),
+            ..doubles()
+        };
+        let mut singles = at(2, 2);
+        singles.winner2_id = None;
+        singles.loser2_id = None;
+        // Newest first. In New York (UTC-4) 02:00 UTC on the 2nd is the 1st.
+        let history = vec![at(3, 15), at(3, 14), singles, at(1, 20), at(1, 18)];
+        let ny = chrono_tz::America::New_York;
+
+        let sessions = group_by_day(history.clone(), ny, None, false);
+
+        let days: Vec<(u32, usize, usize)> = sessions
+            .iter()
+            .map(|s| (s.date.day(), s.games_played, s.distinct_players))
+            .collect();
+        assert_eq!(days, [(3, 2, 4), (1, 3, 4)]);
+        assert!(sessions[1].matches.windows(2).all(|w| w[0].played_at > w[1].played_at));
+
+        assert_eq!(group_by_day(history.clone(), chrono_tz::UTC, None, false).len(), 3);
+        let latest = group_by_day(history, ny, Some(1), false);
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].date.day(), 3);
+    }
+
+    #[tokio::test]
+    async fn recorded_by_comes_from_the_session_cookie() {
+        use axum::{Extension, Router, middleware, routing::post};
+        use tower::ServiceExt;
+
+        let config = AppConfig::for_tests();
+        // Stands in for `create_match` up to the storage write.
+        let handler = |Extension(config): Extension<AppConfig>,
+                       Extension(claims): Extension<SessionClaims>,
+                       Json(req): Json<CreateMatchRequest>| async move {
+            record_from_request(&config, &claims, req, None).unwrap().recorded_by
+        };
+        let app = Router::new()
+            .route("/api/matches", post(handler))
+            .layer(middleware::from_fn(crate::auth::middleware::require_auth))
+            .layer(Extension(config.clone()));
+        let token = crate::auth::oidc::create_session_token(
+            &config,
+            "user-42",
+            "Zed",
+            "zed@example.com",
+            "user",
+            None,
+        )
+        .unwrap();
+        let request = axum::http::Request::post("/api/matches")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::COOKIE, format!("session={token}"))
+            .body(Body::from(r#"{"winner1_id":"alice","loser1_id":"bob"}"#))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, "user-42");
+    }
+
+    #[tokio::test]
+    async fn unknown_player_filter_is_a_404() {
+        let response = PlayerStorageError::NotFound("nobody".to_string()).into_response();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"], "Player 'nobody' not found");
+    }
+
+    #[test]
+    fn session_games_get_ordered_timestamps_and_one_session_id() {
+        let config = AppConfig::for_tests();
+        let req: CreateSessionRequest = serde_json::from_value(serde_json::json!({
+            "played_at_start": "2024-07-01T18:00:00Z",
+            "league_id": "office",
+            "games": [
+                {"winners": ["alice"], "losers": ["bob"], "winner_score": 11, "loser_score": 9},
+                {"winners": ["bob"], "losers": ["alice"], "winner_score": 11, "loser_score": 9},
+                {"winners": ["alice", "bob"], "losers": ["carol", "dave"]},
+            ],
+        }))
+        .unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 7, 1, 18, 0, 0).unwrap();
+
+        let records =
+            session_records(&config, &claims(), req.games, start, req.league_id, "s-1").unwrap();
+
+        let times: Vec<_> = records.iter().map(|m| m.played_at).collect();
+        assert_eq!(times, [start, start + Duration::seconds(1), start + Duration::seconds(2)]);
+        assert_eq!(records[1].winner1_id, "bob");
+        assert!(records.iter().all(|m| m.session_id.as_deref() == Some("s-1")));
+        assert!(records.iter().all(|m| m.league_id.as_deref() == Some("office")));
+        assert!(records.iter().all(|m| m.recorded_by == "user-1"));
+        // Storage lists newest first, so later games sort earlier.
+        assert!(records.windows(2).all(|w| w[0].id > w[1].id));
+    }
+
+    #[test]
+    fn a_bad_session_game_is_reported_by_position() {
+        let config = AppConfig::for_tests();
+        let games: Vec<SessionGame> = serde_json::from_value(serde_json::json!([
+            {"winners": ["alice"], "losers": ["bob"]},
+            {"winners": ["alice"], "losers": ["bob", "carol"]},
+        ]))
+        .unwrap();
+
+        let err = session_records(&config, &claims(), games, Utc::now(), None, "s-1").unwrap_err();
+
+        let MatchStorageError::Invalid(msg) = err else { panic!("{err:?}") };
+        assert!(msg.starts_with("game 2: "), "{msg}");
+    }
+
+    /// `count` matches, newest first, split into pages of `page_size`.
+    fn pages(
+        count: i64,
+        page_size: usize,
+    ) -> (Vec<MatchRecord>, Vec<Result<Vec<MatchRecord>, MatchStorageError>>) {
+        let start = Utc.with_ymd_and_hms(2024, 7, 1, 18, 0, 0).unwrap();
+        let all: Vec<MatchRecord> = (0..count)
+            .map(|i| {
+                MatchRecord::new(NewMatch {
+                    winner1_id: "alice".to_string(),
+                    loser1_id: "bob".to_string(),
+                    played_at: start - Duration::minutes(i),
+                    ..Default::default()
+                })
+                .unwrap()
+            })
+            .collect();
+        let pages = all.chunks(page_size).map(|page| Ok(page.to_vec())).collect();
+        (all, pages)
+    }
+
+    #[tokio::test]
+    async fn json_export_spans_every_page() {
+        let (all, pages) = pages(7, 3);
+
+        let body = json_export_body(stream::iter(pages));
+
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        let exported: Vec<MatchRecord> = serde_json::from_slice(&bytes).unwrap();
+        let ids = |matches: &[MatchRecord]| matches.iter().map(|m| m.id.clone()).collect();
+        let exported_ids: HashSet<String> = ids(&exported);
+        assert_eq!(exported_ids.len(), all.len());
+        assert_eq!(exported_ids, ids(&all));
+        assert!(exported.windows(2).all(|w| w[0].played_at > w[1].played_at));
+
+        let empty = json_export_body(stream::iter(Vec::new()));
+        assert_eq!(axum::body::to_bytes(empty, usize::MAX).await.unwrap(), "[]");
+    }
+
+    #[tokio::test]
+    async fn csv_export_spans_every_page() {
+        let (all, pages) = pages(5, 2);
+
+        let body = csv_export_body(stream::iter(pages));
+
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+        let expected: String = std::iter::once(csv::row(&MATCH_CSV_COLUMNS))
+            .chain(all.iter().map(match_csv_row))
+            .collect();
+        assert_eq!(text, expected);
+    }
+
+    #[tokio::test]
+    async fn a_failing_page_cuts_the_export_short() {
+        let (_, mut pages) = pages(4, 2);
+        pages.insert(1, Err(MatchStorageError::Azure("ServerBusy".to_string())));
+
+        let body = json_export_body(stream::iter(pages));
+
+        assert!(axum::body::to_bytes(body, usize::MAX).await.is_err());
+    }
+
+    #[test]
+    fn repeat_matchup_warning_fires_past_the_threshold() {
+        let mut config = AppConfig::for_tests();
+        config.repeat_matchup_threshold = Some(3);
+        let candidate = doubles();
+        let days_ago = |days| MatchRecord {
+            played_at: candidate.played_at - Duration::days(days),
+            ..doubles()
+        };
+        // Same four players, different teams; and one from before the window.
+        let mut rearranged = days_ago(2);
+        std::mem::swap(&mut rearranged.winner2_id, &mut rearranged.loser2_id);
+        let mut history = vec![days_ago(1), rearranged, days_ago(10)];
+
+        assert_eq!(repeat_matchup_warning(&config, &history, &candidate), None);
+
+        history.push(days_ago(3));
+        assert_eq!(
+            repeat_matchup_warning(&config, &history, &candidate).as_deref(),
+            Some("You've played these exact teams 4 times in the last 7 days")
+        );
+
+        config.repeat_matchup_threshold = None;
+        assert_eq!(repeat_matchup_warning(&config, &history, &candidate), None);
+    }
+
+    #[test]
+    fn matches_must_name_known_distinct_players() {
+        let known: HashSet<String> =
+            ["alice", "bob", "carol", "dave"].map(String::from).into();
+        assert!(ensure_known_players(&doubles(), &known).is_ok());
+
+        let mut bogus = doubles();
+        bogus.loser2_id = Some("zed".to_string());
+        let err = ensure_known_players(&bogus, &known).unwrap_err();
+        assert!(matches!(&err, MatchStorageError::Invalid(msg) if msg == "unknown player 'zed'"));
+        assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+        bogus.winner1_id = "yan".to_string();
+        let err = ensure_known_players(&bogus, &known).unwrap_err();
+        assert!(matches!(&err, MatchStorageError::Invalid(msg) if msg.contains("'yan', 'zed'")));
+
+        // A player on both sides is caught before the roster is even needed.
+        let overlap: CreateMatchRequest = serde_json::from_value(serde_json::json!({
+            "winner1_id": "alice",
+            "winner2_id": "bob",
+            "loser1_id": "carol",
+            "loser2_id": "alice",
+        }))
+        .unwrap();
+        let config = AppConfig::for_tests();
+        let err = record_from_request(&config, &claims(), overlap, None).unwrap_err();
+        assert!(matches!(err, MatchStorageError::Invalid(_)), "{err:?}");
+    }
+
+    #[test]
+    fn hidden_losers_still_count_as_distinct_players() {
+        let first = doubles();
+        let mut rematch = first.clone();
+        rematch.loser1_id = "erin".to_string();
+        rematch.loser2_id = Some("frank".to_string());
+
+        let sessions = group_by_day(vec![rematch, first], chrono_tz::UTC, None, true);
+
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].distinct_players, 6);
+        assert!(sessions[0].matches.iter().all(|m| m.loser1_id == ANONYMOUS_PLAYER_ID));
+    }
 }
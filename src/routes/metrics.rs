@@ -0,0 +1,50 @@
+// routes/metrics.rs — Prometheus scrape endpoint.
+//
+//   GET /api/metrics → 200 text/plain, Prometheus text exposition format
+//
+// Public like `/api/health` so a scraper needs no session. When
+// `METRICS_TOKEN` is set, requests must carry `Authorization: Bearer <token>`
+// (Prometheus' `authorization` scrape option) or get a `401`.
+
+use axum::{
+    Extension, Json,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+use crate::config::AppConfig;
+use crate::metrics::Metrics;
+
+/// GET /api/metrics — Counters and request latencies (see metrics.rs).
+pub async fn metrics(
+    Extension(metrics): Extension<Metrics>,
+    Extension(config): Extension<AppConfig>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(expected) = &config.metrics_token {
+        let presented = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        if !constant_time_eq(presented.as_bytes(), expected.as_bytes()) {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(serde_json::json!({"error": "Invalid or missing metrics token"})),
+            )
+                .into_response();
+        }
+    }
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        metrics.render(),
+    )
+        .into_response()
+}
+
+/// Compare without bailing out at the first differing byte, so response
+/// timing doesn't leak how much of the token was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
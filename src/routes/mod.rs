@@ -4,10 +4,20 @@
 // The router is then merged with static file serving in main.rs.
 //
 // Auth strategy:
-//   - Auth endpoints (login, callback, logout) are always public.
+//   - Auth endpoints (login, callback, refresh, logout) are always public;
+//     refresh checks the session cookie itself.
+//   - `/api/health` is public too, for load balancers and uptime monitors, and
+//     so is `/api/metrics` unless `METRICS_TOKEN` is set (see routes/metrics.rs).
 //   - The `/api/auth/me` endpoint uses optional auth (returns info if logged in).
 //   - All data endpoints (players, matches, leaderboard, users, leagues) require auth.
-//   - Authorization (role checks) is handled inside individual handlers.
+//   - `/api/display/*` serves read-only leaderboard, dashboard and match list
+//     copies to screens holding a signed `?token=` instead of a session (see
+//     auth/display_token.rs). These are the unauthenticated views that
+//     `ANONYMIZE_LOSERS` applies to.
+//   - Destructive routes (deleting, archiving and merging players, deleting
+//     matches) and bulk match imports additionally require an admin session
+//     (`require_admin`). Other authorization (role checks) is handled inside
+//     individual handlers.
 //
 // Router structure:
 //   - Auth routes get both AppConfig and StorageClient as Extensions (the callback
@@ -15,17 +25,25 @@
 //   - Data routes get StorageClient as State (the standard Axum pattern for shared
 //     state that handlers depend on).
 
+pub mod admin;
 pub mod auth;
+pub mod health;
 pub mod leaderboard;
 pub mod leagues;
 pub mod matches;
+pub mod metrics;
 pub mod players;
 pub mod users;
 
 use axum::{Extension, Router, middleware, routing::{delete, get, post, put}};
+use std::time::Duration;
 
-use crate::auth::middleware::require_auth;
+use crate::auth::display_token::require_display_token;
+use crate::auth::middleware::{require_admin, require_auth};
+use crate::cache::ResponseCache;
 use crate::config::AppConfig;
+use crate::idempotency::IdempotencyCache;
+use crate::metrics::{Metrics, track_requests};
 use crate::storage::client::StorageClient;
 
 /// Build the API router with all endpoints.
@@ -35,35 +53,85 @@ use crate::storage::client::StorageClient;
 /// State (for data routes). This dual injection is needed because auth routes
 /// and data routes are separate router branches with different middleware layers.
 pub fn api_router(storage: StorageClient, config: AppConfig) -> Router {
+    // One response cache shared by every route group that serves cached bodies.
+    let cache = ResponseCache::new(config.export_cache_entries);
+    let idempotency_keys = IdempotencyCache::new(
+        config.idempotency_key_entries,
+        Duration::from_secs(config.idempotency_key_ttl_secs),
+    );
+
     // Auth routes — always public (no auth middleware).
     // These need StorageClient as Extension because the callback upserts user records.
     let auth_routes = Router::new()
         .route("/auth/login", get(auth::login))
         .route("/auth/callback", get(auth::callback))
         .route("/auth/me", get(auth::me))
+        .route("/auth/refresh", post(auth::refresh))
         .route("/auth/logout", post(auth::logout))
         .layer(Extension(storage.clone()));
 
-    // Protected data routes — require authentication.
+    // Health probe — public, needs only storage.
+    let health_routes = Router::new()
+        .route("/health", get(health::health))
+        .with_state(storage.clone());
+
+    // Prometheus scrape endpoint — public, optionally token-protected.
+    let metrics_routes = Router::new().route("/metrics", get(metrics::metrics));
+
+    // Protected data routes — require authentication. Route-level admin
+    // layers run after `require_auth`, so the claims they check are in place.
+    let admin_only = || middleware::from_fn(require_admin);
     let data_routes = Router::new()
         // Player endpoints
         .route("/players", get(players::list_players))
         .route("/players", post(players::create_player))
+        .route("/players/merge", post(players::merge_players).route_layer(admin_only()))
         .route("/players/{id}", put(players::update_player))
-        .route("/players/{id}", delete(players::delete_player))
+        .route("/players/{id}", delete(players::delete_player).route_layer(admin_only()))
+        .route("/players/{id}/archive", post(players::archive_player).route_layer(admin_only()))
+        .route("/players/{id}/link", delete(users::unlink_player))
         // Match endpoints
         .route("/matches", get(matches::list_matches))
         .route("/matches", post(matches::create_match))
+        .route("/matches/feed", get(matches::get_match_feed))
+        .route("/matches/export", get(matches::export_matches_json))
+        .route("/matches/export.csv", get(matches::export_matches_csv))
+        .route("/matches/import", post(matches::import_matches).route_layer(admin_only()))
+        .route("/matches/sessions", get(matches::get_match_sessions))
+        .route("/matches/session", post(matches::create_match_session))
+        .route("/matches/undo", post(matches::undo_last_match))
         .route("/matches/{id}", put(matches::update_match))
-        .route("/matches/{id}", delete(matches::delete_match))
+        .route("/matches/{id}", delete(matches::delete_match).route_layer(admin_only()))
+        .route("/matches/{id}/restore", post(matches::restore_match))
         // Leaderboard & stats endpoints
         .route("/leaderboard", get(leaderboard::get_leaderboard))
+        .route("/leaderboard/timeline", get(leaderboard::get_leaderboard_timeline))
+        .route("/dashboard", get(leaderboard::get_dashboard))
+        .route("/stats/comeback-player", get(leaderboard::get_comeback_player))
+        .route("/mvp", get(leaderboard::get_mvp))
+        .route("/stats/records", get(leaderboard::get_record_book))
         .route("/players/{id}/stats", get(leaderboard::get_player_stats))
+        .route("/me/stats", get(leaderboard::get_my_stats))
+        .route("/players/{id}/export", get(leaderboard::export_player))
+        .route("/players/{id}/card", get(leaderboard::get_player_card))
+        .route("/players/{id}/monthly", get(leaderboard::get_player_monthly))
+        .route("/players/{id}/elo-history", get(leaderboard::get_player_elo_history))
         .route("/rivalries", get(leaderboard::get_rivalries))
+        .route("/rivalries/export.csv", get(leaderboard::export_rivalries_csv))
+        .route("/rivalries/{id1}/{id2}", get(leaderboard::get_head_to_head))
+        .route("/ratings", get(leaderboard::get_ratings))
+        .route("/teams/ratings", get(leaderboard::get_team_ratings))
+        .route("/duos", get(leaderboard::get_duos))
+        .route("/predict", get(leaderboard::get_prediction))
+        .route("/suggestions/matchups", get(leaderboard::get_matchup_suggestions))
         // User management endpoints
         .route("/users", get(users::list_users))
         .route("/users/{oid}/role", put(users::update_user_role))
         .route("/users/{oid}/player", put(users::link_player))
+        // Admin maintenance endpoints
+        .route("/admin/migrate", post(admin::migrate))
+        .route("/admin/display-url", post(admin::create_display_url))
+        .route("/debug/suspicious-matches", get(admin::get_suspicious_matches))
         // League endpoints
         .route("/leagues", get(leagues::list_leagues))
         .route("/leagues", post(leagues::create_league))
@@ -71,13 +139,65 @@ pub fn api_router(storage: StorageClient, config: AppConfig) -> Router {
         .route("/leagues/{id}", put(leagues::update_league))
         .route("/leagues/{id}/close", post(leagues::close_league))
         // Data handlers need the StorageClient as state.
-        .with_state(storage)
+        .with_state(storage.clone())
         // Protect all data routes with auth middleware.
-        .layer(middleware::from_fn(require_auth));
+        .layer(middleware::from_fn(require_auth))
+        .layer(Extension(cache.clone()))
+        .layer(Extension(idempotency_keys));
+
+    // Read-only routes for signed display URLs — no session involved.
+    let display_routes = Router::new()
+        .route("/display/leaderboard", get(leaderboard::get_leaderboard))
+        .route("/display/dashboard", get(leaderboard::get_dashboard))
+        .route("/display/matches", get(matches::list_matches))
+        .route("/display/matches/feed", get(matches::get_match_feed))
+        .route("/display/matches/sessions", get(matches::get_match_sessions))
+        .with_state(storage)
+        .layer(middleware::from_fn(require_display_token))
+        .layer(Extension(cache));
 
-    // Combine auth and data routes, both sharing the AppConfig extension.
+    // Combine all route groups, each sharing the AppConfig extension and the
+    // metrics registry. Request metrics are a route layer so every request is
+    // labelled with the route it matched.
+    let metrics = Metrics::default();
     Router::new()
         .merge(auth_routes)
+        .merge(health_routes)
+        .merge(metrics_routes)
         .merge(data_routes)
+        .merge(display_routes)
+        .route_layer(middleware::from_fn_with_state(metrics.clone(), track_requests))
         .layer(Extension(config))
+        .layer(Extension(metrics))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode, header};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn importing_matches_needs_an_admin() {
+        let config = AppConfig::for_tests();
+        let app = api_router(StorageClient::new(&config), config.clone());
+        let token = crate::auth::oidc::create_session_token(
+            &config,
+            "user-42",
+            "Zed",
+            "zed@example.com",
+            "user",
+            None,
+        )
+        .unwrap();
+
+        let request = Request::post("/matches/import")
+            .header(header::COOKIE, format!("session={token}"))
+            .body(Body::from("played_at,winner1_id,loser1_id\n"))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
 }
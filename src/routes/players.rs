@@ -9,27 +9,39 @@
 //
 // Authorization:
 //   - List and create: any authenticated user (enforced by middleware).
-//   - Update: any authenticated user (could be restricted further).
-//   - Delete: admin only (to prevent accidental deletions).
+//   - Update / ensure (PUT): any authenticated user (could be restricted further).
+//   - Archive, delete and merge: admin only (to prevent accidental removals).
 
 use axum::{
     Extension, Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::auth::oidc::SessionClaims;
-use crate::models::player::{CreatePlayerRequest, Player, UpdatePlayerRequest};
+use crate::config::AppConfig;
+use crate::extract::ApiJson;
+use crate::metrics::Metrics;
+use crate::models::player::{
+    CreatePlayerRequest, Player, UpdatePlayerRequest, default_avatar, generate_nickname,
+    player_id_from_email, slugify, validate_avatar_emoji, validate_color, validate_player_id,
+};
 use crate::storage::client::StorageClient;
+use crate::storage::matches::{self, MatchStorageError};
 use crate::storage::players::{self, PlayerStorageError};
+use crate::storage::users::{self, UserStorageError};
 
 /// Map storage errors to HTTP responses.
 impl IntoResponse for PlayerStorageError {
     fn into_response(self) -> axum::response::Response {
         let (status, message) = match &self {
             PlayerStorageError::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
-            PlayerStorageError::AlreadyExists(_) => (StatusCode::CONFLICT, self.to_string()),
+            PlayerStorageError::AlreadyExists(_) | PlayerStorageError::Conflict(_) => {
+                (StatusCode::CONFLICT, self.to_string())
+            }
+            PlayerStorageError::Invalid(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             PlayerStorageError::Azure(_) => {
                 // Log the actual error but don't expose Azure internals to the client.
                 tracing::error!("Azure storage error: {self}");
@@ -44,57 +56,444 @@ impl IntoResponse for PlayerStorageError {
     }
 }
 
-/// GET /api/players — List all players.
+/// Query parameters for `GET /api/players`.
+#[derive(Deserialize)]
+pub struct ListPlayersQuery {
+    /// Include archived players too. Defaults to `false`.
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+/// GET /api/players — List active players (`?include_archived=true` for all).
 pub async fn list_players(
     State(storage): State<StorageClient>,
+    Query(query): Query<ListPlayersQuery>,
 ) -> Result<Json<Vec<Player>>, PlayerStorageError> {
-    let players = players::list_players(&storage).await?;
+    let mut players = players::list_players(&storage).await?;
+    if !query.include_archived {
+        players.retain(|p| p.active);
+    }
     Ok(Json(players))
 }
 
 /// POST /api/players — Create a new player.
+///
+/// The ID must be a slug (see `validate_player_id`); when it's omitted one is
+/// made from the name (see `slugify`), and a clash with an existing player is
+/// a `409` like any other. The avatar must be a single emoji (see
+/// `validate_avatar_emoji`) and the color, if any, a `#rrggbb` hex code (see
+/// `validate_color`). When `AUTO_NICKNAME` is
+/// enabled and no nickname is given, one is generated from the player ID
+/// (see `generate_nickname`).
 pub async fn create_player(
     State(storage): State<StorageClient>,
-    Json(req): Json<CreatePlayerRequest>,
+    Extension(config): Extension<AppConfig>,
+    Extension(metrics): Extension<Metrics>,
+    ApiJson(req): ApiJson<CreatePlayerRequest>,
 ) -> Result<(StatusCode, Json<Player>), PlayerStorageError> {
-    let player = Player {
-        id: req.id,
-        name: req.name,
-        nickname: req.nickname,
-        avatar_emoji: req.avatar_emoji,
-    };
-
+    let player = player_from_request(&config, req)?;
     let created = players::create_player(&storage, player).await?;
+    metrics.player_created();
     Ok((StatusCode::CREATED, Json(created)))
 }
 
-/// PUT /api/players/{id} — Update an existing player.
+/// The player `create_player` stores for `req`, after validation.
+fn player_from_request(
+    config: &AppConfig,
+    req: CreatePlayerRequest,
+) -> Result<Player, PlayerStorageError> {
+    let id = if req.id.trim().is_empty() { slugify(&req.name) } else { req.id };
+    validate_player_id(&id).map_err(PlayerStorageError::Invalid)?;
+    let avatar_emoji =
+        validate_avatar_emoji(&req.avatar_emoji).map_err(PlayerStorageError::Invalid)?;
+    let color = validate_color(&req.color).map_err(PlayerStorageError::Invalid)?;
+    let nickname = resolve_nickname(config, &id, req.nickname);
+
+    Ok(Player {
+        id,
+        name: req.name,
+        nickname,
+        avatar_emoji,
+        color,
+        active: true,
+    })
+}
+
+/// Response for `PUT /api/players/{id}`: the player plus which fields the
+/// request actually changed, so the UI can say e.g. "Nickname updated".
+#[derive(Debug, Serialize)]
+pub struct PlayerUpdateResponse {
+    #[serde(flatten)]
+    pub player: Player,
+    /// Changed fields (`name`, `nickname`, `avatar_emoji`, `color`). Empty when the
+    /// request matched what was stored; every field when the player was created.
+    pub changed_fields: Vec<&'static str>,
+}
+
+/// PUT /api/players/{id} — Update a player, creating it if it doesn't exist.
+///
+/// Idempotent "ensure player" semantics for sync scripts:
+///   - Existing player: only the provided fields change → `200 OK`.
+///   - Unknown player: created from the body (`name` required) → `201 Created`.
 pub async fn update_player(
+    State(storage): State<StorageClient>,
+    Extension(config): Extension<AppConfig>,
+    Extension(metrics): Extension<Metrics>,
+    Path(id): Path<String>,
+    ApiJson(req): ApiJson<UpdatePlayerRequest>,
+) -> Result<(StatusCode, Json<PlayerUpdateResponse>), PlayerStorageError> {
+    let avatar_emoji = req
+        .avatar_emoji
+        .as_deref()
+        .map(validate_avatar_emoji)
+        .transpose()
+        .map_err(PlayerStorageError::Invalid)?;
+    let color = req
+        .color
+        .as_deref()
+        .map(validate_color)
+        .transpose()
+        .map_err(PlayerStorageError::Invalid)?;
+
+    match players::get_player(&storage, &id).await {
+        Ok(previous) => {
+            let updated = players::update_player(
+                &storage,
+                &id,
+                req.name,
+                req.nickname,
+                avatar_emoji,
+                color,
+            )
+            .await?;
+            let changed_fields = previous.changed_fields(&updated);
+            Ok((
+                StatusCode::OK,
+                Json(PlayerUpdateResponse {
+                    player: updated,
+                    changed_fields,
+                }),
+            ))
+        }
+        Err(PlayerStorageError::NotFound(_)) => {
+            let player =
+                new_player(&config, id, req.name, req.nickname, avatar_emoji, color.flatten())?;
+            let created = players::upsert_player(&storage, player).await?;
+            metrics.player_created();
+            Ok((
+                StatusCode::CREATED,
+                Json(PlayerUpdateResponse {
+                    player: created,
+                    changed_fields: vec!["name", "nickname", "avatar_emoji", "color"],
+                }),
+            ))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Build the player `PUT /api/players/{id}` creates for an unknown `id`.
+fn new_player(
+    config: &AppConfig,
+    id: String,
+    name: Option<String>,
+    nickname: Option<String>,
+    avatar_emoji: Option<String>,
+    color: Option<String>,
+) -> Result<Player, PlayerStorageError> {
+    validate_player_id(&id).map_err(PlayerStorageError::Invalid)?;
+    let name = name.ok_or_else(|| {
+        PlayerStorageError::Invalid("name is required to create a player".to_string())
+    })?;
+    let nickname = resolve_nickname(config, &id, nickname.unwrap_or_default());
+    Ok(Player {
+        id,
+        name,
+        nickname,
+        avatar_emoji: avatar_emoji.unwrap_or_else(default_avatar),
+        color,
+        active: true,
+    })
+}
+
+/// Use the given nickname, or generate one when it's empty and
+/// `AUTO_NICKNAME` is enabled.
+fn resolve_nickname(config: &AppConfig, player_id: &str, nickname: String) -> String {
+    if nickname.trim().is_empty() && config.auto_nickname {
+        generate_nickname(player_id, &config.nickname_adjectives, &config.nickname_nouns)
+    } else {
+        nickname
+    }
+}
+
+/// Highest numeric suffix tried when an auto-created player's ID is taken.
+const MAX_AUTO_ID_SUFFIX: u32 = 99;
+
+/// Create a player for a signed-in user from their profile (see
+/// `AUTO_CREATE_PLAYERS`).
+///
+/// The ID comes from the email's local part; if that's taken we try `-2`,
+/// `-3`, … up to `MAX_AUTO_ID_SUFFIX`. Inserting is what claims an ID, so two
+/// users racing for the same one can't both get it. Linking the user to the
+/// new player is left to the caller.
+pub async fn auto_create_player(
+    storage: &StorageClient,
+    config: &AppConfig,
+    claims: &SessionClaims,
+) -> Result<Player, PlayerStorageError> {
+    for player in auto_player_candidates(config, claims) {
+        match players::create_player(storage, player).await {
+            Err(PlayerStorageError::AlreadyExists(_)) => continue,
+            result => return result,
+        }
+    }
+    Err(PlayerStorageError::AlreadyExists(format!(
+        "{} (and suffixes up to -{MAX_AUTO_ID_SUFFIX})",
+        player_id_from_email(&claims.email)
+    )))
+}
+
+/// The players `auto_create_player` tries to insert, in order: the email's
+/// local part, then the same with `-2` … `-MAX_AUTO_ID_SUFFIX`.
+fn auto_player_candidates<'a>(
+    config: &'a AppConfig,
+    claims: &'a SessionClaims,
+) -> impl Iterator<Item = Player> + 'a {
+    let base = player_id_from_email(&claims.email);
+    (1..=MAX_AUTO_ID_SUFFIX).map(move |suffix| {
+        let id = if suffix == 1 { base.clone() } else { format!("{base}-{suffix}") };
+        Player {
+            nickname: resolve_nickname(config, &id, String::new()),
+            id,
+            name: claims.name.clone(),
+            avatar_emoji: default_avatar(),
+            color: None,
+            active: true,
+        }
+    })
+}
+
+/// POST /api/players/{id}/archive — Archive a player. Admins only (the
+/// route sits behind `require_admin`).
+///
+/// The preferred way to retire a player: they disappear from the player list
+/// but their matches keep pointing at a real record, so stats still show
+/// their name. Returns the archived player.
+pub async fn archive_player(
     State(storage): State<StorageClient>,
     Path(id): Path<String>,
-    Json(req): Json<UpdatePlayerRequest>,
 ) -> Result<Json<Player>, PlayerStorageError> {
-    let updated =
-        players::update_player(&storage, &id, req.name, req.nickname, req.avatar_emoji).await?;
-    Ok(Json(updated))
+    let player = players::archive_player(&storage, &id).await?;
+    Ok(Json(player))
 }
 
 /// DELETE /api/players/{id} — Delete a player.
 ///
-/// Restricted to admins to prevent accidental deletions. Regular users
-/// should ask an admin to delete a player if needed.
+/// Restricted to admins (via `require_admin`) to prevent accidental
+/// deletions. Regular users should ask an admin to delete a player if needed.
+/// Meant for entries that were created by mistake: a deleted player's matches
+/// are left pointing at an unknown ID, so retiring a real player should use
+/// the archive endpoint.
 pub async fn delete_player(
     State(storage): State<StorageClient>,
-    Extension(claims): Extension<SessionClaims>,
     Path(id): Path<String>,
 ) -> Result<StatusCode, PlayerStorageError> {
-    // Only admins can delete players.
-    if claims.role != "admin" {
-        return Err(PlayerStorageError::Azure(
-            "Forbidden: only admins can delete players".to_string(),
-        ));
-    }
-
     players::delete_player(&storage, &id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Request body for `POST /api/players/merge`.
+#[derive(Debug, Deserialize)]
+pub struct MergePlayersRequest {
+    /// The duplicate, removed by the merge.
+    pub from: String,
+    /// The player that is kept.
+    pub into: String,
+}
+
+/// Response for `POST /api/players/merge`.
+#[derive(Debug, Serialize)]
+pub struct MergeSummary {
+    pub from: String,
+    pub into: String,
+    /// Matches now pointing at `into`.
+    pub matches_rewritten: usize,
+    /// IDs of matches `from` and `into` both played in, left unchanged.
+    pub skipped_matches: Vec<String>,
+    /// IDs of matches that couldn't be rewritten.
+    pub failed_matches: Vec<String>,
+    /// Users whose player link moved from `from` to `into`.
+    pub users_relinked: usize,
+    /// Whether `from` was deleted. It's kept while any of its matches are
+    /// skipped or failed, so none of them end up pointing at nobody.
+    pub from_deleted: bool,
+}
+
+/// The storage errors a merge can run into.
+#[derive(Debug, thiserror::Error)]
+pub enum MergeError {
+    #[error("{0}")]
+    Player(#[from] PlayerStorageError),
+    #[error("{0}")]
+    Match(#[from] MatchStorageError),
+    #[error("{0}")]
+    User(#[from] UserStorageError),
+}
+
+impl IntoResponse for MergeError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            MergeError::Player(e) => e.into_response(),
+            MergeError::Match(e) => e.into_response(),
+            MergeError::User(e) => e.into_response(),
+        }
+    }
+}
+
+/// POST /api/players/merge — Fold a duplicate player into another. Admins only
+/// (via `require_admin`).
+///
+/// Every match referencing `from` is rewritten to `into`, users linked to
+/// `from` are relinked, and then `from` is deleted. Rewriting is best effort
+/// (see `MergeSummary`); re-running the merge picks up whatever was left.
+/// Both players must exist and differ.
+pub async fn merge_players(
+    State(storage): State<StorageClient>,
+    Extension(claims): Extension<SessionClaims>,
+    ApiJson(req): ApiJson<MergePlayersRequest>,
+) -> Result<Json<MergeSummary>, MergeError> {
+    if req.from == req.into {
+        return Err(
+            PlayerStorageError::Invalid("cannot merge a player into itself".to_string()).into(),
+        );
+    }
+    players::get_player(&storage, &req.from).await?;
+    players::get_player(&storage, &req.into).await?;
+
+    let report = matches::reassign_player(&storage, &req.from, &req.into).await?;
+    let users_relinked = users::relink_player_users(&storage, &req.from, &req.into).await?;
+
+    let from_deleted = report.skipped.is_empty() && report.failed.is_empty();
+    if from_deleted {
+        players::delete_player(&storage, &req.from).await?;
+    }
+    tracing::info!(
+        "{} merged player '{}' into '{}' ({} matches rewritten)",
+        claims.sub,
+        req.from,
+        req.into,
+        report.rewritten
+    );
+
+    Ok(Json(MergeSummary {
+        from: req.from,
+        into: req.into,
+        matches_rewritten: report.rewritten,
+        skipped_matches: report.skipped,
+        failed_matches: report.failed,
+        users_relinked,
+        from_deleted,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn put_creates_an_unknown_player_from_the_body() {
+        let config = AppConfig::for_tests();
+        let name = Some("Erin".to_string());
+        let player = new_player(&config, "erin".to_string(), name, None, None, None).unwrap();
+        assert_eq!(player.id, "erin");
+        assert_eq!(player.name, "Erin");
+        assert_eq!(player.avatar_emoji, default_avatar());
+        assert!(player.active);
+    }
+
+    #[test]
+    fn put_needs_a_name_and_a_valid_id_to_create() {
+        let config = AppConfig::for_tests();
+        let err = new_player(&config, "erin".to_string(), None, None, None, None).unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+        let name = Some("Erin".to_string());
+        let err =
+            new_player(&config, "Erin Smith".to_string(), name, None, None, None).unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn put_on_an_existing_player_changes_only_the_given_fields() {
+        let config = AppConfig::for_tests();
+        let name = Some("Erin".to_string());
+        let before = new_player(&config, "erin".to_string(), name, None, None, None).unwrap();
+        let mut after = before.clone();
+        let avatar = Some(before.avatar_emoji.clone());
+        after.apply_update(None, Some("The Wall".to_string()), avatar, None);
+
+        assert_eq!(after.name, "Erin");
+        assert_eq!(after.nickname, "The Wall");
+        assert_eq!(before.changed_fields(&after), ["nickname"]);
+    }
+
+    #[test]
+    fn concurrent_edits_are_a_409() {
+        let response = PlayerStorageError::Conflict("alice".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn auto_created_players_come_from_the_profile() {
+        let config = AppConfig::for_tests();
+        let claims = SessionClaims {
+            sub: "oid-1".to_string(),
+            name: "Jane Doe".to_string(),
+            email: "Jane.Doe@example.com".to_string(),
+            role: "user".to_string(),
+            is_admin: false,
+            player_id: None,
+            exp: 0,
+            iat: 0,
+        };
+
+        let candidates: Vec<Player> = auto_player_candidates(&config, &claims).collect();
+
+        let first = &candidates[0];
+        assert_eq!(first.id, "jane-doe");
+        assert_eq!(first.name, "Jane Doe");
+        assert_eq!(first.avatar_emoji, default_avatar());
+        assert!(first.active);
+        // Taken IDs fall back to numbered suffixes, all of them valid.
+        let ids: Vec<&str> = candidates.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids[1..3], ["jane-doe-2", "jane-doe-3"]);
+        assert_eq!(ids.len(), MAX_AUTO_ID_SUFFIX as usize);
+        assert_eq!(ids.last(), Some(&"jane-doe-99"));
+        assert!(ids.iter().all(|id| validate_player_id(id).is_ok()));
+    }
+
+    fn create_request(body: serde_json::Value) -> CreatePlayerRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn omitted_ids_are_slugified_from_the_name() {
+        let config = AppConfig::for_tests();
+        let req = create_request(serde_json::json!({"name": "Jane O'Neil"}));
+
+        let player = player_from_request(&config, req).unwrap();
+
+        assert_eq!(player.id, "jane-o-neil");
+        assert_eq!(player.name, "Jane O'Neil");
+    }
+
+    #[test]
+    fn forbidden_id_characters_are_a_400() {
+        let config = AppConfig::for_tests();
+        for id in ["jane/doe", "jane#1", "Jane", "jane doe"] {
+            let req = create_request(serde_json::json!({"id": id, "name": "Jane"}));
+            let err = player_from_request(&config, req).unwrap_err();
+            assert!(matches!(err, PlayerStorageError::Invalid(_)), "{id:?}: {err:?}");
+            assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+        }
+    }
+}
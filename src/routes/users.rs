@@ -8,6 +8,7 @@
 //   GET  /api/users          — List all users (admin only)
 //   PUT  /api/users/{oid}/role   — Change a user's role (admin only)
 //   PUT  /api/users/{oid}/player — Link/unlink player profile (admin or self)
+//   DELETE /api/players/{id}/link — Unlink a player from whichever user claimed it (admin only)
 //
 // Authorization is checked inside each handler using the `SessionClaims`
 // injected by the auth middleware. This is a common Axum pattern — the
@@ -18,12 +19,14 @@ use axum::{
     Extension, Json,
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
 };
 
 use crate::auth::oidc::SessionClaims;
+use crate::extract::ApiJson;
 use crate::models::user::{LinkPlayerRequest, UpdateUserRoleRequest};
 use crate::storage::client::StorageClient;
+use crate::storage::players::{self, PlayerStorageError};
 use crate::storage::users::{self, UserStorageError};
 
 /// Map UserStorageError variants to HTTP status codes.
@@ -63,7 +66,7 @@ pub async fn list_users(
     Extension(claims): Extension<SessionClaims>,
 ) -> Result<Json<Vec<crate::models::user::User>>, UserStorageError> {
     // Authorization check: only admins can list users.
-    if claims.role != "admin" {
+    if !claims.is_admin {
         return Err(UserStorageError::Forbidden(
             "Only admins can list users".to_string(),
         ));
@@ -83,10 +86,10 @@ pub async fn update_user_role(
     State(storage): State<StorageClient>,
     Extension(claims): Extension<SessionClaims>,
     Path(oid): Path<String>,
-    Json(req): Json<UpdateUserRoleRequest>,
+    ApiJson(req): ApiJson<UpdateUserRoleRequest>,
 ) -> Result<Json<crate::models::user::User>, UserStorageError> {
     // Authorization check: only admins can change roles.
-    if claims.role != "admin" {
+    if !claims.is_admin {
         return Err(UserStorageError::Forbidden(
             "Only admins can change user roles".to_string(),
         ));
@@ -104,10 +107,10 @@ pub async fn link_player(
     State(storage): State<StorageClient>,
     Extension(claims): Extension<SessionClaims>,
     Path(oid): Path<String>,
-    Json(req): Json<LinkPlayerRequest>,
+    ApiJson(req): ApiJson<LinkPlayerRequest>,
 ) -> Result<Json<crate::models::user::User>, UserStorageError> {
     // Authorization check: admin can link anyone, users can only link themselves.
-    if claims.role != "admin" && claims.sub != oid {
+    if !claims.is_admin && claims.sub != oid {
         return Err(UserStorageError::Forbidden(
             "You can only link your own player profile".to_string(),
         ));
@@ -116,3 +119,39 @@ pub async fn link_player(
     let updated = users::link_player(&storage, &oid, req.player_id).await?;
     Ok(Json(updated))
 }
+
+/// DELETE /api/players/{id}/link — Clear a player's account link.
+///
+/// Admin-only. The player-centric counterpart to `PUT /api/users/{oid}/player`
+/// for when an account was re-created or linked to the wrong profile: it
+/// unlinks every user pointing at this player so the profile can be claimed
+/// again, after which `/api/me/stats` no longer resolves it for them. `404`
+/// for an unknown player; otherwise `204`, even if nobody was linked.
+///
+/// The link is stored on the user, so this scans the users table (see
+/// `unlink_player_from_users`). Sessions cache `player_id`, so the old
+/// account keeps its link for match edit permissions until it next logs in.
+pub async fn unlink_player(
+    State(storage): State<StorageClient>,
+    Extension(claims): Extension<SessionClaims>,
+    Path(player_id): Path<String>,
+) -> Result<Response, UserStorageError> {
+    if !claims.is_admin {
+        return Err(UserStorageError::Forbidden(
+            "Only admins can unlink players".to_string(),
+        ));
+    }
+
+    match players::get_player(&storage, &player_id).await {
+        Ok(_) => {}
+        Err(e @ PlayerStorageError::NotFound(_)) => return Ok(e.into_response()),
+        Err(e) => return Err(UserStorageError::Azure(e.to_string())),
+    }
+
+    let unlinked = users::unlink_player_from_users(&storage, &player_id).await?;
+    tracing::info!(
+        "{} unlinked player '{player_id}' from {unlinked} user(s)",
+        claims.email
+    );
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
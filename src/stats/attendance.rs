@@ -0,0 +1,91 @@
+// stats/attendance.rs — Who actually shows up.
+//
+// A "session" is a calendar day (in the configured timezone) with at least
+// one match. A player's participation rate is the share of sessions in which
+// they played at least once, regardless of how many games that was.
+
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::models::match_record::MatchRecord;
+
+/// Group matches into sessions: each distinct local day mapped to the set of
+/// players who played that day.
+pub fn players_by_day(matches: &[MatchRecord], tz: Tz) -> BTreeMap<NaiveDate, HashSet<&str>> {
+    let mut days: BTreeMap<NaiveDate, HashSet<&str>> = BTreeMap::new();
+    for m in matches {
+        let day = m.played_at.with_timezone(&tz).date_naive();
+        days.entry(day).or_default().extend(m.player_ids());
+    }
+    days
+}
+
+/// Fraction of sessions (0.0–1.0) each player appeared in. Players who never
+/// played are absent from the map; with no matches at all the map is empty.
+pub fn participation_rates(matches: &[MatchRecord], tz: Tz) -> HashMap<&str, f64> {
+    let days = players_by_day(matches, tz);
+    let sessions = days.len() as f64;
+
+    let mut attended: HashMap<&str, u32> = HashMap::new();
+    for players in days.values() {
+        for player in players {
+            *attended.entry(player).or_default() += 1;
+        }
+    }
+
+    attended
+        .into_iter()
+        .map(|(player, n)| (player, n as f64 / sessions))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::match_record::NewMatch;
+    use chrono::{TimeZone, Utc};
+
+    fn singles(winner: &str, loser: &str, day: u32, hour: u32) -> MatchRecord {
+        MatchRecord::new(NewMatch {
+            winner1_id: winner.to_string(),
+            loser1_id: loser.to_string(),
+            played_at: Utc.with_ymd_and_hms(2024, 6, day, hour, 0, 0).unwrap(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn participation_counts_days_not_games() {
+        // Four match days; alice plays three games on the 1st alone.
+        let matches = [
+            singles("alice", "bob", 1, 10),
+            singles("alice", "bob", 1, 11),
+            singles("bob", "alice", 1, 12),
+            singles("alice", "carol", 2, 10),
+            singles("bob", "carol", 3, 10),
+            singles("alice", "bob", 4, 10),
+        ];
+
+        let rates = participation_rates(&matches, chrono_tz::UTC);
+
+        assert_eq!(rates["alice"], 0.75);
+        assert_eq!(rates["bob"], 0.75);
+        assert_eq!(rates["carol"], 0.5);
+        assert!(!rates.contains_key("dave"));
+    }
+
+    #[test]
+    fn sessions_are_local_days() {
+        // 23:00 and 01:00 UTC are one evening in New York.
+        let matches = [singles("alice", "bob", 1, 23), singles("carol", "dave", 2, 1)];
+        assert_eq!(players_by_day(&matches, chrono_tz::America::New_York).len(), 1);
+        assert_eq!(players_by_day(&matches, chrono_tz::UTC).len(), 2);
+    }
+
+    #[test]
+    fn no_matches_means_no_rates() {
+        assert!(participation_rates(&[], chrono_tz::UTC).is_empty());
+    }
+}
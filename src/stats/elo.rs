@@ -0,0 +1,373 @@
+// stats/elo.rs — ELO-style ratings.
+//
+// ELO assigns every competitor a number; after each game the winner takes
+// points from the loser, more of them when the result was an upset.
+//
+//   expected = 1 / (1 + 10^((opponent - rating) / 400))
+//   new      = rating + K * (actual - expected)
+//
+// where `actual` is 1 for a win and 0 for a loss. A 400-point gap means the
+// stronger side is expected to win ~91% of the time.
+//
+// Ratings are computed on the fly by replaying matches oldest-first. With a
+// few hundred matches that's instant, and it means ratings never drift out
+// of sync with edited or deleted matches.
+//
+// Doubles: for individual ratings, each team is rated as the average of its
+// two players, and both players on a team gain (or lose) the same delta.
+//
+// Seasons: when a new league starts, each player's starting rating regresses
+// toward the base by `carryover` (0.0 = everyone restarts at BASE_RATING,
+// 1.0 = ratings carry over unchanged):
+//
+//   start = BASE_RATING + carryover * (previous_final - BASE_RATING)
+//
+// Predictions: before a game, each team is rated as the average of its two
+// players and the win probability is the same logistic `expected` above:
+//
+//   p(team1) = 1 / (1 + 10^((avg(team2) - avg(team1)) / 400))
+//
+// The predicted margin scales the edge over a coin flip to a game to 11:
+//
+//   margin = (2 * p(team1) - 1) * POINTS_TO_WIN
+//
+// so evenly matched teams predict 0 and a near-certain win predicts 11-0.
+// It's a rough guide, not a fitted model.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::models::match_record::MatchRecord;
+
+/// Starting rating for every competitor.
+pub const BASE_RATING: f64 = 1000.0;
+
+/// How many points change hands in an evenly matched game (K / 2).
+pub const K_FACTOR: f64 = 32.0;
+
+/// Probability that a side rated `rating` beats a side rated `opponent`.
+pub fn expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+/// Compute individual ratings by replaying `matches` from `starting` ratings.
+///
+/// Players not in `starting` begin at `BASE_RATING`. `matches` is expected
+/// newest-first (storage order); it is replayed oldest-first.
+pub fn compute_elo(
+    matches: &[MatchRecord],
+    starting: &HashMap<String, f64>,
+) -> HashMap<String, f64> {
+    let mut ratings = starting.clone();
+    for m in matches.iter().rev() {
+        apply_match(&mut ratings, m);
+    }
+    ratings
+}
+
+/// Update `ratings` with one match's result and return the points the
+/// winners gained (and the losers lost).
+fn apply_match(ratings: &mut HashMap<String, f64>, m: &MatchRecord) -> f64 {
+    // A side's rating is its players' average (just the player in singles).
+    let side_avg = |ratings: &HashMap<String, f64>, ids: Vec<&str>| {
+        let total: f64 = ids
+            .iter()
+            .map(|id| ratings.get(*id).copied().unwrap_or(BASE_RATING))
+            .sum();
+        total / ids.len() as f64
+    };
+    let winner_avg = side_avg(ratings, m.winner_ids().collect());
+    let loser_avg = side_avg(ratings, m.loser_ids().collect());
+    let delta = K_FACTOR * (1.0 - expected_score(winner_avg, loser_avg));
+
+    for id in m.winner_ids() {
+        *ratings.entry(id.to_string()).or_insert(BASE_RATING) += delta;
+    }
+    for id in m.loser_ids() {
+        *ratings.entry(id.to_string()).or_insert(BASE_RATING) -= delta;
+    }
+    delta
+}
+
+/// How one match moved a player's rating.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct EloChange {
+    pub rating_before: f64,
+    /// Positive for a win, negative for a loss.
+    pub delta: f64,
+    pub rating_after: f64,
+}
+
+/// Replay `matches` from the base rating and record, for each match
+/// `player_id` played, how their rating changed.
+///
+/// Same replay as `compute_elo`, so the last entry's `rating_after` equals the
+/// player's rating there (and the deltas sum to `rating - BASE_RATING`).
+/// `matches` is expected newest-first; the result is newest-first too.
+pub fn player_rating_changes<'a>(
+    matches: &'a [MatchRecord],
+    player_id: &str,
+) -> Vec<(&'a MatchRecord, EloChange)> {
+    let mut ratings = HashMap::new();
+    let mut changes = Vec::new();
+
+    for m in matches.iter().rev() {
+        let rating_before = ratings.get(player_id).copied().unwrap_or(BASE_RATING);
+        let delta = apply_match(&mut ratings, m);
+        if m.has_player(player_id) {
+            let delta = if m.is_winner(player_id) { delta } else { -delta };
+            changes.push((
+                m,
+                EloChange {
+                    rating_before,
+                    delta,
+                    rating_after: rating_before + delta,
+                },
+            ));
+        }
+    }
+
+    changes.reverse();
+    changes
+}
+
+/// Starting ratings for a new season, regressed toward the base by `carryover`.
+pub fn season_start_ratings(
+    previous_finals: &HashMap<String, f64>,
+    carryover: f64,
+) -> HashMap<String, f64> {
+    previous_finals
+        .iter()
+        .map(|(id, rating)| (id.clone(), BASE_RATING + carryover * (rating - BASE_RATING)))
+        .collect()
+}
+
+/// Compute final ratings after a sequence of seasons, oldest season first.
+///
+/// Each season starts from the previous season's finals via
+/// `season_start_ratings`; the first season starts everyone at the base.
+pub fn compute_season_elo(seasons: &[Vec<MatchRecord>], carryover: f64) -> HashMap<String, f64> {
+    seasons.iter().fold(HashMap::new(), |finals, season| {
+        compute_elo(season, &season_start_ratings(&finals, carryover))
+    })
+}
+
+/// Points needed to win a standard game, used to scale predicted margins.
+pub const POINTS_TO_WIN: f64 = 11.0;
+
+/// Pre-game odds for a doubles matchup.
+#[derive(Debug, Clone, Serialize)]
+pub struct Prediction {
+    pub team1_rating: f64,
+    pub team2_rating: f64,
+    pub team1_win_probability: f64,
+    pub team2_win_probability: f64,
+    /// Expected points margin from team 1's side (negative when team 2 is
+    /// favored).
+    pub predicted_margin: f64,
+}
+
+/// Predict a game between two teams given each player's rating.
+pub fn predict(team1: [f64; 2], team2: [f64; 2]) -> Prediction {
+    let team1_rating = (team1[0] + team1[1]) / 2.0;
+    let team2_rating = (team2[0] + team2[1]) / 2.0;
+    let team1_win_probability = expected_score(team1_rating, team2_rating);
+
+    Prediction {
+        team1_rating,
+        team2_rating,
+        team1_win_probability,
+        team2_win_probability: 1.0 - team1_win_probability,
+        predicted_margin: (2.0 * team1_win_probability - 1.0) * POINTS_TO_WIN,
+    }
+}
+
+/// Normalize a pair of player IDs so (a, b) and (b, a) map to the same key.
+pub fn pair_key(a: &str, b: &str) -> (String, String) {
+    if a <= b {
+        (a.to_string(), b.to_string())
+    } else {
+        (b.to_string(), a.to_string())
+    }
+}
+
+/// The ELO rating of a doubles pair, treated as a single competitor.
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamRating {
+    /// The lexicographically smaller player ID of the pair.
+    pub player1_id: String,
+    /// The lexicographically larger player ID of the pair.
+    pub player2_id: String,
+    pub rating: f64,
+    pub wins: u32,
+    pub losses: u32,
+}
+
+/// Compute ratings for every pair that has played together.
+///
+/// Each unordered pair is its own competitor: when a pair wins, it gains
+/// rating from the pair it beat, regardless of how either player does with
+/// other partners. `matches` is expected newest-first (storage order); it is
+/// replayed oldest-first. Results are sorted by rating, highest first.
+/// Singles matches have no pairs and are skipped.
+pub fn compute_team_elo(matches: &[MatchRecord]) -> Vec<TeamRating> {
+    let mut teams: HashMap<(String, String), TeamRating> = HashMap::new();
+
+    for m in matches.iter().rev() {
+        let Some([(w1, w2), (l1, l2)]) = m.pairs() else {
+            continue;
+        };
+        let winners = pair_key(w1, w2);
+        let losers = pair_key(l1, l2);
+
+        let winner_rating = teams.get(&winners).map_or(BASE_RATING, |t| t.rating);
+        let loser_rating = teams.get(&losers).map_or(BASE_RATING, |t| t.rating);
+        let delta = K_FACTOR * (1.0 - expected_score(winner_rating, loser_rating));
+
+        let winner = teams
+            .entry(winners.clone())
+            .or_insert_with(|| new_team(&winners));
+        winner.rating += delta;
+        winner.wins += 1;
+
+        let loser = teams
+            .entry(losers.clone())
+            .or_insert_with(|| new_team(&losers));
+        loser.rating -= delta;
+        loser.losses += 1;
+    }
+
+    let mut ratings: Vec<TeamRating> = teams.into_values().collect();
+    ratings.sort_by(|a, b| {
+        b.rating
+            .partial_cmp(&a.rating)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    ratings
+}
+
+fn new_team((player1_id, player2_id): &(String, String)) -> TeamRating {
+    TeamRating {
+        player1_id: player1_id.clone(),
+        player2_id: player2_id.clone(),
+        rating: BASE_RATING,
+        wins: 0,
+        losses: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::match_record::NewMatch;
+    use chrono::{Duration, Utc};
+
+    fn doubles(winners: [&str; 2], losers: [&str; 2], minutes_ago: i64) -> MatchRecord {
+        MatchRecord::new(NewMatch {
+            winner1_id: winners[0].to_string(),
+            winner2_id: Some(winners[1].to_string()),
+            loser1_id: losers[0].to_string(),
+            loser2_id: Some(losers[1].to_string()),
+            played_at: Utc::now() - Duration::minutes(minutes_ago),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn singles(winner: &str, loser: &str, minutes_ago: i64) -> MatchRecord {
+        MatchRecord::new(NewMatch {
+            winner1_id: winner.to_string(),
+            loser1_id: loser.to_string(),
+            played_at: Utc::now() - Duration::minutes(minutes_ago),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn pair_ratings_follow_a_known_sequence() {
+        // Newest first: an even first game moves 16 points, then the
+        // underdogs (with the pair in the other order) win back ~17.47.
+        let matches = vec![
+            doubles(["dave", "carol"], ["bob", "alice"], 10),
+            singles("alice", "carol", 20),
+            doubles(["alice", "bob"], ["carol", "dave"], 30),
+        ];
+
+        let ratings = compute_team_elo(&matches);
+
+        assert_eq!(ratings.len(), 2);
+        let (top, bottom) = (&ratings[0], &ratings[1]);
+        assert_eq!((top.player1_id.as_str(), top.player2_id.as_str()), ("carol", "dave"));
+        assert_eq!((top.wins, top.losses), (1, 1));
+        assert!((top.rating - 1001.4695).abs() < 1e-3, "{}", top.rating);
+        assert_eq!((bottom.player1_id.as_str(), bottom.player2_id.as_str()), ("alice", "bob"));
+        assert!((bottom.rating - 998.5305).abs() < 1e-3, "{}", bottom.rating);
+    }
+
+    #[test]
+    fn carryover_zero_restarts_everyone_and_one_carries_fully() {
+        // An even game moves 16 points: alice 1016, bob 984.
+        let seasons = vec![vec![singles("alice", "bob", 10)], vec![]];
+
+        let fresh = compute_season_elo(&seasons, 0.0);
+        assert_eq!(fresh["alice"], BASE_RATING);
+        assert_eq!(fresh["bob"], BASE_RATING);
+
+        let carried = compute_season_elo(&seasons, 1.0);
+        assert_eq!(carried["alice"], 1016.0);
+        assert_eq!(carried["bob"], 984.0);
+
+        let halved = compute_season_elo(&seasons, 0.5);
+        assert_eq!(halved["alice"], 1008.0);
+    }
+
+    #[test]
+    fn newcomers_start_a_season_at_the_base() {
+        let seasons = vec![vec![singles("alice", "bob", 20)], vec![singles("carol", "alice", 10)]];
+        let starts = season_start_ratings(&compute_elo(&seasons[0], &HashMap::new()), 1.0);
+        assert!(!starts.contains_key("carol"));
+        let finals = compute_season_elo(&seasons, 1.0);
+        assert!(finals["carol"] > BASE_RATING);
+    }
+
+    #[test]
+    fn predictions_use_the_team_average_logistic() {
+        // Averages 1200 vs 1000: p = 1 / (1 + 10^(-200/400)) ≈ 0.7597.
+        let prediction = predict([1100.0, 1300.0], [1000.0, 1000.0]);
+        assert_eq!((prediction.team1_rating, prediction.team2_rating), (1200.0, 1000.0));
+        assert!((prediction.team1_win_probability - 0.759747).abs() < 1e-6);
+        assert!((prediction.team2_win_probability - 0.240253).abs() < 1e-6);
+        assert!((prediction.predicted_margin - 5.71444).abs() < 1e-5);
+
+        let even = predict([1000.0, 1200.0], [1100.0, 1100.0]);
+        assert_eq!(even.team1_win_probability, 0.5);
+        assert_eq!(even.predicted_margin, 0.0);
+    }
+
+    #[test]
+    fn rating_changes_sum_to_the_final_rating() {
+        // Newest first; carol's games don't involve alice but move bob.
+        let matches = vec![
+            doubles(["alice", "carol"], ["bob", "dave"], 10),
+            singles("bob", "alice", 20),
+            singles("carol", "bob", 30),
+            singles("alice", "bob", 40),
+            singles("dave", "carol", 50),
+        ];
+
+        let changes = player_rating_changes(&matches, "alice");
+        let ratings = compute_elo(&matches, &HashMap::new());
+
+        assert_eq!(changes.len(), 3);
+        assert!(std::ptr::eq(changes[0].0, &matches[0]));
+        let total: f64 = changes.iter().map(|(_, c)| c.delta).sum();
+        assert!((BASE_RATING + total - ratings["alice"]).abs() < 1e-9);
+        assert!((changes[0].1.rating_after - ratings["alice"]).abs() < 1e-9);
+        assert!(changes[1].1.delta < 0.0);
+        // Each entry picks up where the previous (older) one left off.
+        for pair in changes.windows(2) {
+            assert!((pair[0].1.rating_before - pair[1].1.rating_after).abs() < 1e-9);
+        }
+    }
+}
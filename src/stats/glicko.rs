@@ -0,0 +1,320 @@
+// stats/glicko.rs — Glicko-2 ratings.
+//
+// Glicko-2 (Glickman, 2012: "Example of the Glicko-2 system") extends ELO
+// with two extra numbers per player:
+//
+//   - rating deviation (RD): how uncertain the rating is. It shrinks as a
+//     player plays and grows while they're inactive.
+//   - volatility (σ): how erratic the player's results are. It controls how
+//     fast RD grows back.
+//
+// Games are grouped into rating periods (`period_days` long, counted from the
+// first match). Within a period every player's games are rated against the
+// ratings everyone had at the start of it, then all players update at once.
+// Players who sat a period out only have their RD grow:
+//
+//   φ' = sqrt(φ² + σ²)
+//
+// Doubles: a player's opponent in a game is the opposing team, treated as a
+// single competitor with the average of its two players' μ and
+// sqrt((φ₁² + φ₂²) / 2) as its φ. Partners don't affect each other directly.
+//
+// All the math below is on the Glicko-2 scale (μ, φ), converted from and to
+// the familiar Glicko scale (r = 1500 ± RD) with SCALE.
+
+use chrono::Duration;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use crate::models::match_record::MatchRecord;
+use crate::models::player::Player;
+
+/// Starting rating, RD and volatility for a new player.
+pub const DEFAULT_RATING: f64 = 1500.0;
+pub const DEFAULT_DEVIATION: f64 = 350.0;
+pub const DEFAULT_VOLATILITY: f64 = 0.06;
+
+/// System constant τ: how much volatility may change per period. Glickman
+/// suggests 0.3–1.2; smaller is more conservative.
+const TAU: f64 = 0.5;
+
+/// Glicko ↔ Glicko-2 scale factor (400 / ln 10).
+const SCALE: f64 = 173.7178;
+
+/// Convergence tolerance for the volatility iteration.
+const EPSILON: f64 = 0.000_001;
+
+/// A player's Glicko-2 rating, on the Glicko scale.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct GlickoRating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+impl Default for GlickoRating {
+    fn default() -> Self {
+        Self {
+            rating: DEFAULT_RATING,
+            deviation: DEFAULT_DEVIATION,
+            volatility: DEFAULT_VOLATILITY,
+        }
+    }
+}
+
+impl GlickoRating {
+    fn mu(&self) -> f64 {
+        (self.rating - DEFAULT_RATING) / SCALE
+    }
+
+    fn phi(&self) -> f64 {
+        self.deviation / SCALE
+    }
+}
+
+/// One game from a player's perspective: the opponent's (μ, φ) and the
+/// score (1 = win, 0 = loss).
+#[derive(Debug, Clone, Copy)]
+pub struct Outcome {
+    pub opponent_mu: f64,
+    pub opponent_phi: f64,
+    pub score: f64,
+}
+
+/// Compute ratings for every player by replaying `matches` in rating periods
+/// of `period_days`.
+///
+/// Every player in `players` gets an entry (unrated players keep the
+/// defaults); so does anyone who appears only in matches. `matches` is
+/// expected newest-first (storage order).
+pub fn compute_glicko(
+    players: &[Player],
+    matches: &[MatchRecord],
+    period_days: i64,
+) -> HashMap<String, GlickoRating> {
+    let mut ratings: HashMap<String, GlickoRating> = players
+        .iter()
+        .map(|p| (p.id.clone(), GlickoRating::default()))
+        .collect();
+
+    let Some(first) = matches.last() else {
+        return ratings;
+    };
+    let period = Duration::days(period_days.max(1));
+
+    // Bucket matches by period index, oldest first.
+    let mut periods: Vec<Vec<&MatchRecord>> = Vec::new();
+    for m in matches.iter().rev() {
+        let index = ((m.played_at - first.played_at).num_seconds() / period.num_seconds())
+            .max(0) as usize;
+        if periods.len() <= index {
+            periods.resize_with(index + 1, Vec::new);
+        }
+        periods[index].push(m);
+        for id in m.player_ids() {
+            ratings.entry(id.to_string()).or_default();
+        }
+    }
+
+    for games in periods {
+        let start = ratings.clone();
+        let mut outcomes: HashMap<&str, Vec<Outcome>> = HashMap::new();
+
+        for m in games {
+            // A side is its players' mean mu and RMS phi (the player
+            // themselves in singles).
+            let team = |ids: Vec<&str>| {
+                let n = ids.len() as f64;
+                let mu = ids.iter().map(|id| start[*id].mu()).sum::<f64>() / n;
+                let phi = (ids.iter().map(|id| start[*id].phi().powi(2)).sum::<f64>() / n).sqrt();
+                (mu, phi)
+            };
+            let winners = team(m.winner_ids().collect());
+            let losers = team(m.loser_ids().collect());
+
+            for id in m.winner_ids() {
+                outcomes.entry(id).or_default().push(Outcome {
+                    opponent_mu: losers.0,
+                    opponent_phi: losers.1,
+                    score: 1.0,
+                });
+            }
+            for id in m.loser_ids() {
+                outcomes.entry(id).or_default().push(Outcome {
+                    opponent_mu: winners.0,
+                    opponent_phi: winners.1,
+                    score: 0.0,
+                });
+            }
+        }
+
+        for (id, rating) in ratings.iter_mut() {
+            let played = outcomes.get(id.as_str()).map_or(&[][..], Vec::as_slice);
+            *rating = update(start[id], played);
+        }
+    }
+
+    ratings
+}
+
+/// Rate one player over one period (steps 2–8 of Glickman's paper).
+pub fn update(player: GlickoRating, outcomes: &[Outcome]) -> GlickoRating {
+    let (mu, phi, sigma) = (player.mu(), player.phi(), player.volatility);
+
+    if outcomes.is_empty() {
+        return GlickoRating {
+            deviation: (phi.powi(2) + sigma.powi(2)).sqrt() * SCALE,
+            ..player
+        };
+    }
+
+    // Step 3–4: estimated variance and improvement from this period's games.
+    let mut v_inv = 0.0;
+    let mut sum = 0.0;
+    for o in outcomes {
+        let g = g(o.opponent_phi);
+        let e = expected(mu, o.opponent_mu, g);
+        v_inv += g.powi(2) * e * (1.0 - e);
+        sum += g * (o.score - e);
+    }
+    let v = 1.0 / v_inv;
+    let delta = v * sum;
+
+    // Step 5–8.
+    let sigma = new_volatility(phi, sigma, v, delta);
+    let phi_star = (phi.powi(2) + sigma.powi(2)).sqrt();
+    let phi = 1.0 / (1.0 / phi_star.powi(2) + 1.0 / v).sqrt();
+    let mu = mu + phi.powi(2) * sum;
+
+    GlickoRating {
+        rating: mu * SCALE + DEFAULT_RATING,
+        deviation: phi * SCALE,
+        volatility: sigma,
+    }
+}
+
+/// Dampens the impact of a game by the opponent's uncertainty.
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi.powi(2) / PI.powi(2)).sqrt()
+}
+
+/// Expected score against an opponent rated `opponent_mu`.
+fn expected(mu: f64, opponent_mu: f64, g: f64) -> f64 {
+    1.0 / (1.0 + (-g * (mu - opponent_mu)).exp())
+}
+
+/// Step 5: solve for the new volatility with the Illinois algorithm.
+fn new_volatility(phi: f64, sigma: f64, v: f64, delta: f64) -> f64 {
+    let a = sigma.powi(2).ln();
+    let f = |x: f64| {
+        let ex = x.exp();
+        ex * (delta.powi(2) - phi.powi(2) - v - ex) / (2.0 * (phi.powi(2) + v + ex).powi(2))
+            - (x - a) / TAU.powi(2)
+    };
+
+    let mut big_a = a;
+    let mut big_b = if delta.powi(2) > phi.powi(2) + v {
+        (delta.powi(2) - phi.powi(2) - v).ln()
+    } else {
+        let mut k = 1.0;
+        while f(a - k * TAU) < 0.0 {
+            k += 1.0;
+        }
+        a - k * TAU
+    };
+
+    let mut f_a = f(big_a);
+    let mut f_b = f(big_b);
+    while (big_b - big_a).abs() > EPSILON {
+        let big_c = big_a + (big_a - big_b) * f_a / (f_b - f_a);
+        let f_c = f(big_c);
+        if f_c * f_b <= 0.0 {
+            big_a = big_b;
+            f_a = f_b;
+        } else {
+            f_a /= 2.0;
+        }
+        big_b = big_c;
+        f_b = f_c;
+    }
+
+    (big_a / 2.0).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::match_record::NewMatch;
+    use chrono::Utc;
+
+    fn opponent(rating: f64, deviation: f64, score: f64) -> Outcome {
+        let opponent = GlickoRating {
+            rating,
+            deviation,
+            volatility: DEFAULT_VOLATILITY,
+        };
+        Outcome {
+            opponent_mu: opponent.mu(),
+            opponent_phi: opponent.phi(),
+            score,
+        }
+    }
+
+    #[test]
+    fn matches_glickmans_worked_example() {
+        // "Example of the Glicko-2 system", section 3: a 1500 (RD 200) player
+        // beats a 1400 and loses to a 1550 and a 1700 in one period.
+        let player = GlickoRating {
+            rating: 1500.0,
+            deviation: 200.0,
+            volatility: 0.06,
+        };
+        let outcomes = [
+            opponent(1400.0, 30.0, 1.0),
+            opponent(1550.0, 100.0, 0.0),
+            opponent(1700.0, 300.0, 0.0),
+        ];
+
+        let rated = update(player, &outcomes);
+
+        assert!((rated.rating - 1464.06).abs() < 0.01, "{}", rated.rating);
+        assert!((rated.deviation - 151.52).abs() < 0.01, "{}", rated.deviation);
+        assert!((rated.volatility - 0.05999).abs() < 0.00001, "{}", rated.volatility);
+    }
+
+    #[test]
+    fn sitting_a_period_out_only_grows_the_deviation() {
+        let rated = update(GlickoRating::default(), &[]);
+        assert_eq!(rated.rating, DEFAULT_RATING);
+        assert_eq!(rated.volatility, DEFAULT_VOLATILITY);
+        assert!(rated.deviation > DEFAULT_DEVIATION);
+    }
+
+    #[test]
+    fn every_player_is_rated_and_winners_rise() {
+        let player = |id: &str| Player {
+            id: id.to_string(),
+            name: id.to_string(),
+            nickname: String::new(),
+            avatar_emoji: "🏓".to_string(),
+            color: None,
+            active: true,
+        };
+        let game = MatchRecord::new(NewMatch {
+            winner1_id: "alice".to_string(),
+            loser1_id: "bob".to_string(),
+            played_at: Utc::now(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let players = [player("alice"), player("bob"), player("carol")];
+        let ratings = compute_glicko(&players, &[game], 7);
+
+        assert!(ratings["alice"].rating > DEFAULT_RATING);
+        assert!(ratings["bob"].rating < DEFAULT_RATING);
+        assert!(ratings["alice"].deviation < DEFAULT_DEVIATION);
+        assert_eq!(ratings["carol"].rating, DEFAULT_RATING);
+    }
+}
@@ -0,0 +1,12 @@
+// stats/mod.rs — Pure stat computations.
+//
+// Everything in here works on in-memory slices of matches and players and
+// never touches storage or HTTP. Route handlers fetch the data, call into
+// these functions, and shape the results into responses. Keeping the math
+// separate makes it easy to reason about (and reuse across endpoints).
+
+pub mod attendance;
+pub mod elo;
+pub mod glicko;
+pub mod scheduler;
+pub mod suspicious;
@@ -0,0 +1,200 @@
+// stats/scheduler.rs — Suggesting who should play whom next.
+//
+// Left to themselves, people keep partnering with the same friends and
+// dodging the same opponents. To spread games out over a season we score
+// every possible 2v2 game among the available players and suggest the best:
+//
+//   - Repetition: how often each team's two players have partnered before,
+//     plus how often each player has faced each opponent. Lower is better.
+//   - Balance: how far the ELO win probability is from a coin flip. A rare
+//     pairing isn't much fun if it's a blowout.
+//
+// Each group of four players is suggested at most once, with whichever of
+// its three possible team splits scores best.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::models::match_record::MatchRecord;
+use crate::stats::elo::{self, BASE_RATING};
+
+/// Repetition-equivalent cost of a completely lopsided game (win probability
+/// 0 or 1). An evenly matched game costs nothing; in between it scales
+/// linearly, so a 75/25 game costs half of this.
+const IMBALANCE_WEIGHT: f64 = 6.0;
+
+/// A suggested 2v2 game.
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchupSuggestion {
+    /// Player IDs of each team, each pair sorted.
+    pub team1: [String; 2],
+    pub team2: [String; 2],
+    /// Games the two pairs have already played as partners, combined.
+    pub times_partnered: u32,
+    /// Games the four cross-team player pairs have already played as
+    /// opponents, combined.
+    pub times_faced: u32,
+    /// ELO chance of `team1` winning (see `elo::predict`).
+    pub team1_win_probability: f64,
+    /// Lower is better: `times_partnered + times_faced` plus the imbalance
+    /// cost.
+    pub score: f64,
+}
+
+/// How often each unordered pair of players has partnered and faced off.
+#[derive(Default)]
+struct PairCounts<'a> {
+    partnered: HashMap<(&'a str, &'a str), u32>,
+    faced: HashMap<(&'a str, &'a str), u32>,
+}
+
+impl<'a> PairCounts<'a> {
+    fn from_matches(matches: &'a [MatchRecord]) -> Self {
+        let mut counts = Self::default();
+        for m in matches {
+            if let Some(pairs) = m.pairs() {
+                for (a, b) in pairs {
+                    *counts.partnered.entry(key(a, b)).or_default() += 1;
+                }
+            }
+            for winner in m.winner_ids() {
+                for loser in m.loser_ids() {
+                    *counts.faced.entry(key(winner, loser)).or_default() += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    fn partnered(&self, a: &str, b: &str) -> u32 {
+        self.partnered.get(&key(a, b)).copied().unwrap_or(0)
+    }
+
+    fn faced(&self, a: &str, b: &str) -> u32 {
+        self.faced.get(&key(a, b)).copied().unwrap_or(0)
+    }
+}
+
+/// Order-independent key for a pair of player IDs.
+fn key<'a>(a: &'a str, b: &'a str) -> (&'a str, &'a str) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+/// Suggest up to `limit` games among `players`, best first.
+///
+/// `matches` is the history repetition is counted over (e.g. the current
+/// league's matches) and `ratings` the ELO ratings used for balance; players
+/// without a rating count as `BASE_RATING`. Fewer than four players yields
+/// no suggestions.
+pub fn suggest_matchups(
+    players: &[&str],
+    matches: &[MatchRecord],
+    ratings: &HashMap<String, f64>,
+    limit: usize,
+) -> Vec<MatchupSuggestion> {
+    let counts = PairCounts::from_matches(matches);
+    let rating = |id: &str| ratings.get(id).copied().unwrap_or(BASE_RATING);
+
+    let mut players = players.to_vec();
+    players.sort_unstable();
+    players.dedup();
+
+    let n = players.len();
+    let mut suggestions = Vec::new();
+    for a in 0..n {
+        for b in a + 1..n {
+            for c in b + 1..n {
+                for d in c + 1..n {
+                    let [p, q, r, s] = [players[a], players[b], players[c], players[d]];
+                    // The three ways to split four players into two teams.
+                    let best = [[[p, q], [r, s]], [[p, r], [q, s]], [[p, s], [q, r]]]
+                        .into_iter()
+                        .map(|[t1, t2]| score_game(&counts, &rating, t1, t2))
+                        .min_by(|x, y| x.score.total_cmp(&y.score));
+                    suggestions.extend(best);
+                }
+            }
+        }
+    }
+
+    // Stable sort: equal scores keep the (alphabetical) enumeration order.
+    suggestions.sort_by(|x, y| x.score.total_cmp(&y.score));
+    suggestions.truncate(limit);
+    suggestions
+}
+
+fn score_game(
+    counts: &PairCounts,
+    rating: &impl Fn(&str) -> f64,
+    team1: [&str; 2],
+    team2: [&str; 2],
+) -> MatchupSuggestion {
+    let times_partnered =
+        counts.partnered(team1[0], team1[1]) + counts.partnered(team2[0], team2[1]);
+    let times_faced = team1
+        .iter()
+        .flat_map(|a| team2.iter().map(move |b| counts.faced(a, b)))
+        .sum::<u32>();
+
+    let prediction = elo::predict(team1.map(rating), team2.map(rating));
+    let p = prediction.team1_win_probability;
+    let imbalance = (p - 0.5).abs() * 2.0;
+
+    MatchupSuggestion {
+        team1: team1.map(str::to_string),
+        team2: team2.map(str::to_string),
+        times_partnered,
+        times_faced,
+        team1_win_probability: p,
+        score: (times_partnered + times_faced) as f64 + imbalance * IMBALANCE_WEIGHT,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::match_record::NewMatch;
+
+    fn doubles(winners: [&str; 2], losers: [&str; 2]) -> MatchRecord {
+        MatchRecord::new(NewMatch {
+            winner1_id: winners[0].to_string(),
+            winner2_id: Some(winners[1].to_string()),
+            loser1_id: losers[0].to_string(),
+            loser2_id: Some(losers[1].to_string()),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn suggestions_favor_under_played_combinations() {
+        let history = vec![doubles(["a", "b"], ["c", "d"]); 3];
+
+        let players = ["a", "b", "c", "d", "e"];
+        let suggestions = suggest_matchups(&players, &history, &HashMap::new(), 5);
+
+        // One per group of four, and every group with the newcomer beats the
+        // group that has played three times already.
+        assert_eq!(suggestions.len(), 5);
+        let has_newcomer = |s: &MatchupSuggestion| s.team1.iter().chain(&s.team2).any(|p| p == "e");
+        assert!(suggestions[..4].iter().all(has_newcomer));
+        let regulars = &suggestions[4];
+        assert_eq!((regulars.times_partnered, regulars.times_faced), (0, 6));
+        assert_ne!(regulars.team1, ["a", "b"]);
+        assert_ne!(regulars.team1, ["c", "d"]);
+    }
+
+    #[test]
+    fn fresh_groups_are_split_into_even_teams() {
+        let ratings = HashMap::from([("a".to_string(), 1400.0), ("b".to_string(), 1400.0)]);
+
+        let suggestions = suggest_matchups(&["a", "b", "c", "d"], &[], &ratings, 3);
+
+        assert_eq!(suggestions.len(), 1);
+        let best = &suggestions[0];
+        assert_eq!(best.team1, ["a", "c"]);
+        assert_eq!(best.team2, ["b", "d"]);
+        assert!((best.team1_win_probability - 0.5).abs() < 1e-9);
+        assert!(suggest_matchups(&["a", "b", "c"], &[], &ratings, 3).is_empty());
+    }
+}
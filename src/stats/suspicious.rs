@@ -0,0 +1,129 @@
+// stats/suspicious.rs — Detecting likely data-entry errors.
+//
+// A real game takes minutes, so a player showing up in two matches whose
+// `played_at` are only seconds apart almost always means a duplicate entry
+// or a botched import. We look at each player's matches in time order and
+// flag consecutive pairs closer together than a minimum interval.
+//
+// Games entered together as a session (same `session_id`) are spaced only a
+// second apart on purpose, so pairs within one session are never flagged.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::models::match_record::MatchRecord;
+
+/// Two matches that share at least one player and were played too close
+/// together to both be real.
+#[derive(Debug, Clone, Serialize)]
+pub struct SuspiciousPair {
+    /// The earlier of the two matches.
+    pub match_id: String,
+    /// The later of the two matches.
+    pub other_match_id: String,
+    pub seconds_apart: i64,
+    /// Players who appear in both matches, sorted.
+    pub player_ids: Vec<String>,
+}
+
+/// Find pairs of matches where some player appears in both within
+/// `min_interval` of each other.
+///
+/// Each pair is reported once, listing every shared player. Results are
+/// ordered by the earlier match's `played_at`, oldest first.
+pub fn find_suspicious_matches(
+    matches: &[MatchRecord],
+    min_interval: Duration,
+) -> Vec<SuspiciousPair> {
+    let mut by_player: HashMap<&str, Vec<&MatchRecord>> = HashMap::new();
+    for m in matches {
+        for id in m.player_ids() {
+            by_player.entry(id).or_default().push(m);
+        }
+    }
+
+    // (earlier played_at, earlier id, later id) → pair; keyed so the output
+    // order is deterministic and shared players collapse into one entry.
+    let mut pairs: BTreeMap<(DateTime<Utc>, &str, &str), SuspiciousPair> = BTreeMap::new();
+    for (player_id, mut played) in by_player {
+        played.sort_by_key(|m| (m.played_at, m.id.as_str()));
+        played.dedup_by_key(|m| m.id.as_str());
+
+        for window in played.windows(2) {
+            let (earlier, later) = (window[0], window[1]);
+            let gap = later.played_at - earlier.played_at;
+            let same_session =
+                earlier.session_id.is_some() && earlier.session_id == later.session_id;
+            if gap >= min_interval || same_session {
+                continue;
+            }
+            pairs
+                .entry((earlier.played_at, earlier.id.as_str(), later.id.as_str()))
+                .or_insert_with(|| SuspiciousPair {
+                    match_id: earlier.id.clone(),
+                    other_match_id: later.id.clone(),
+                    seconds_apart: gap.num_seconds(),
+                    player_ids: Vec::new(),
+                })
+                .player_ids
+                .push(player_id.to_string());
+        }
+    }
+
+    pairs
+        .into_values()
+        .map(|mut pair| {
+            pair.player_ids.sort();
+            pair
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::match_record::NewMatch;
+    use chrono::TimeZone;
+
+    fn singles(winner: &str, loser: &str, played_at: DateTime<Utc>) -> MatchRecord {
+        MatchRecord::new(NewMatch {
+            winner1_id: winner.to_string(),
+            loser1_id: loser.to_string(),
+            played_at,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn closely_spaced_matches_for_one_player_are_flagged() {
+        let noon = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        let first = singles("alice", "bob", noon);
+        let duplicate = singles("alice", "bob", noon + Duration::seconds(20));
+        let next_game = singles("carol", "alice", noon + Duration::minutes(15));
+        // Newest first, as storage returns them.
+        let matches = [next_game, duplicate.clone(), first.clone()];
+
+        let flagged = find_suspicious_matches(&matches, Duration::minutes(2));
+
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].match_id, first.id);
+        assert_eq!(flagged[0].other_match_id, duplicate.id);
+        assert_eq!(flagged[0].seconds_apart, 20);
+        assert_eq!(flagged[0].player_ids, ["alice", "bob"]);
+    }
+
+    #[test]
+    fn games_from_one_session_are_not_flagged() {
+        let noon = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        let mut matches = [
+            singles("alice", "bob", noon + Duration::seconds(1)),
+            singles("bob", "alice", noon),
+        ];
+        for m in &mut matches {
+            m.session_id = Some("session-1".to_string());
+        }
+        assert!(find_suspicious_matches(&matches, Duration::minutes(2)).is_empty());
+    }
+}
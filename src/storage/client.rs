@@ -17,19 +17,31 @@
 //   - matches: Match records (2v2 doubles games)
 //   - users:   Authenticated users (role, linked player)
 //   - leagues: Time-bounded seasons that group matches
+//   - events:  Append-only match event log (only written with EVENT_LOG)
+//
+// Data version: every write to players, matches or leagues made through this
+// process bumps an in-memory counter. Caches of derived responses key on it,
+// so anything computed before the latest write is treated as stale. (With
+// multiple instances each keeps its own counter — fine for a single-instance
+// deployment, which is all we run.)
 
 use azure_data_tables::prelude::*;
+use futures::StreamExt;
 use azure_storage::StorageCredentials;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use crate::config::AppConfig;
+use crate::models::player::PLAYER_PARTITION_KEY;
 
 /// Names of the Azure Table Storage tables we use.
 const PLAYERS_TABLE: &str = "players";
 const MATCHES_TABLE: &str = "matches";
 const USERS_TABLE: &str = "users";
 const LEAGUES_TABLE: &str = "leagues";
+const EVENTS_TABLE: &str = "events";
 
-/// Wrapper around Azure Table Storage that provides access to our four tables.
+/// Wrapper around Azure Table Storage that provides access to our tables.
 ///
 /// This is cheap to clone (the inner client is Arc-based), so we pass it
 /// around freely in Axum's State extractor.
@@ -43,6 +55,12 @@ pub struct StorageClient {
     pub users: TableClient,
     /// Client for the "leagues" table — stores league/season definitions.
     pub leagues: TableClient,
+    /// Client for the "events" table — the append-only event log.
+    pub events: TableClient,
+    /// Retries for transient failures; see `storage::retry`.
+    pub max_retries: u32,
+    /// Bumped on every data write; see the module comment.
+    data_version: Arc<AtomicU64>,
 }
 
 impl StorageClient {
@@ -70,8 +88,45 @@ impl StorageClient {
         let matches = service_client.table_client(MATCHES_TABLE);
         let users = service_client.table_client(USERS_TABLE);
         let leagues = service_client.table_client(LEAGUES_TABLE);
+        let events = service_client.table_client(EVENTS_TABLE);
+
+        Self {
+            players,
+            matches,
+            users,
+            leagues,
+            events,
+            max_retries: config.storage_max_retries,
+            data_version: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// The current data version. Changes whenever players, matches or
+    /// leagues are written.
+    pub fn data_version(&self) -> u64 {
+        self.data_version.load(Ordering::SeqCst)
+    }
 
-        Self { players, matches, users, leagues }
+    /// Mark the data as changed, invalidating version-keyed caches.
+    pub fn bump_data_version(&self) {
+        self.data_version.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Cheap connectivity check: fetch at most one player row.
+    ///
+    /// Deliberately not retried — callers (the health probe) want to know
+    /// about failures, not ride them out.
+    pub async fn ping(&self) -> azure_core::Result<()> {
+        let mut stream = self
+            .players
+            .query()
+            .filter(format!("PartitionKey eq '{PLAYER_PARTITION_KEY}'"))
+            .top(1)
+            .into_stream::<serde_json::Value>();
+        if let Some(page) = stream.next().await {
+            page?;
+        }
+        Ok(())
     }
 
     /// Ensure our tables exist in Azure Table Storage.
@@ -89,6 +144,7 @@ impl StorageClient {
             (MATCHES_TABLE, &self.matches),
             (USERS_TABLE, &self.users),
             (LEAGUES_TABLE, &self.leagues),
+            (EVENTS_TABLE, &self.events),
         ] {
             match client.create().await {
                 Ok(_) => tracing::info!("Created table '{name}'"),
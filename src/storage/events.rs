@@ -0,0 +1,57 @@
+// storage/events.rs — Append-only event log in Azure Table Storage.
+//
+// Events are only ever inserted, never updated or deleted (see
+// models/event.rs). Writing one is best-effort from the caller's point of
+// view: the match operation has already succeeded by the time we log it, so
+// a failure here is reported loudly but doesn't fail the request.
+
+use crate::config::AppConfig;
+use crate::models::event::{EventEntity, MatchEventKind};
+use crate::models::match_record::MatchRecord;
+use crate::storage::client::StorageClient;
+
+/// Errors that can occur while writing events.
+#[derive(Debug, thiserror::Error)]
+pub enum EventStorageError {
+    #[error("Azure Table Storage error: {0}")]
+    Azure(String),
+}
+
+/// Insert a single event.
+pub async fn append_event(
+    storage: &StorageClient,
+    event: &EventEntity,
+) -> Result<(), EventStorageError> {
+    let _: azure_data_tables::operations::InsertEntityResponse<EventEntity> = storage
+        .events
+        .insert(event)
+        .map_err(|e| EventStorageError::Azure(format!("{e}")))?
+        .await
+        .map_err(|e| EventStorageError::Azure(format!("{e}")))?;
+    Ok(())
+}
+
+/// Record a match event if `EVENT_LOG` is enabled; no-op otherwise.
+///
+/// Failures are logged at error level (the log is then missing an entry,
+/// which an operator should know about) but not returned.
+pub async fn log_match_event(
+    config: &AppConfig,
+    storage: &StorageClient,
+    kind: MatchEventKind,
+    actor: &str,
+    record: &MatchRecord,
+) {
+    if !config.event_log {
+        return;
+    }
+
+    let event = EventEntity::for_match(kind, actor, record);
+    if let Err(e) = append_event(storage, &event).await {
+        tracing::error!(
+            "Failed to log {} event for match {}: {e}",
+            kind.as_str(),
+            record.id
+        );
+    }
+}
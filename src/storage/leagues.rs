@@ -126,6 +126,7 @@ pub async fn create_league(
                 }
             })?;
 
+    storage.bump_data_version();
     Ok(league)
 }
 
@@ -159,6 +160,7 @@ pub async fn update_league(
         .await
         .map_err(|e| LeagueStorageError::Azure(format!("{e}")))?;
 
+    storage.bump_data_version();
     Ok(current)
 }
 
@@ -186,5 +188,6 @@ pub async fn close_league(
         .await
         .map_err(|e| LeagueStorageError::Azure(format!("{e}")))?;
 
+    storage.bump_data_version();
     Ok(current)
 }
@@ -8,11 +8,27 @@
 //   - get_match: Point read of a single match by ID (for editing).
 //   - update_match: Replace an existing match record (for corrections).
 //   - Forbidden error variant for authorization failures.
+//   - migrate_matches: Backfill properties missing from older entities.
+//   - reassign_player: Move every match from one player ID to another.
+//
+// Deleting is soft: `delete_match` only sets the entity's `deleted` flag,
+// `restore_match` clears it, and every read below skips flagged rows as if
+// they weren't there. `hard_delete_match` removes a row for good.
+
+use azure_core::StatusCode;
+use azure_data_tables::operations::InsertEntityResponse;
+use azure_data_tables::prelude::IfMatchCondition;
+use chrono::{DateTime, Duration, Utc};
+use futures::{Stream, StreamExt};
 
-use futures::StreamExt;
+use serde::Serialize;
 
-use crate::models::match_record::{MatchEntity, MatchRecord, MATCH_PARTITION_KEY};
+use crate::models::match_record::{
+    LegacyMatchEntity, MatchEntity, MatchRecord, MATCH_PARTITION_KEY, is_match_row_key,
+    row_key_prefix,
+};
 use crate::storage::client::StorageClient;
+use crate::storage::retry::{http_status, retry_with_backoff, retry_write};
 
 /// Errors that can occur during match storage operations.
 ///
@@ -27,6 +43,22 @@ pub enum MatchStorageError {
     #[error("Forbidden: {0}")]
     Forbidden(String),
 
+    /// The request describes a match we refuse to store (bad dates, scores, etc.).
+    #[error("Invalid match: {0}")]
+    Invalid(String),
+
+    /// The match looks like an accidental resubmit of an existing one.
+    #[error("Duplicate match: {0}")]
+    Duplicate(String),
+
+    /// Recording the match would exceed a configured rate limit.
+    #[error("Too many matches: {0}")]
+    TooMany(String),
+
+    /// Matches can't be recorded right now (`QUIET_HOURS`).
+    #[error("{0}")]
+    QuietHours(String),
+
     #[error("Azure Table Storage error: {0}")]
     Azure(String),
 }
@@ -53,45 +85,225 @@ pub async fn list_matches(
     storage: &StorageClient,
     limit: Option<usize>,
 ) -> Result<Vec<MatchRecord>, MatchStorageError> {
-    let mut matches = Vec::new();
     let max = limit.unwrap_or(usize::MAX);
 
-    let mut stream = storage
-        .matches
-        .query()
-        .filter(format!("PartitionKey eq '{MATCH_PARTITION_KEY}'"))
-        .into_stream::<MatchEntity>();
+    // A transient failure part-way through restarts the whole query.
+    let matches = retry_with_backoff(storage.max_retries, || async move {
+        let mut matches = Vec::new();
+        let mut stream = storage
+            .matches
+            .query()
+            .filter(format!("PartitionKey eq '{MATCH_PARTITION_KEY}'"))
+            .into_stream::<MatchEntity>();
 
-    while let Some(page_result) = stream.next().await {
-        let page = page_result.map_err(MatchStorageError::from)?;
-        for entity in page.entities {
+        while let Some(page_result) = stream.next().await {
+            for entity in page_result?.entities {
+                if matches.len() >= max {
+                    break;
+                }
+                if entity.deleted {
+                    continue;
+                }
+                match MatchRecord::try_from(entity) {
+                    Ok(record) => matches.push(record),
+                    Err(e) => {
+                        // Log and skip malformed entities rather than failing the whole list.
+                        tracing::warn!("Skipping match with invalid played_at: {e}");
+                    }
+                }
+            }
             if matches.len() >= max {
                 break;
             }
-            match MatchRecord::try_from(entity) {
-                Ok(record) => matches.push(record),
-                Err(e) => {
-                    // Log and skip malformed entities rather than failing the whole list.
-                    tracing::warn!("Skipping match with invalid played_at: {e}");
+        }
+        Ok(matches)
+    })
+    .await
+    .map_err(MatchStorageError::from)?;
+
+    Ok(matches)
+}
+
+/// Stream every match, newest first, one Azure page at a time.
+///
+/// Unlike `list_matches` this never holds more than one page (at most
+/// `page_size` rows) in memory, so exports of the full history stay small no
+/// matter how many matches there are. Not retried: a failure part-way ends
+/// the stream with that error, since earlier pages may already be sent.
+pub fn stream_matches(
+    storage: &StorageClient,
+    page_size: u32,
+) -> impl Stream<Item = Result<Vec<MatchRecord>, MatchStorageError>> + Send + 'static {
+    storage
+        .matches
+        .query()
+        .filter(format!("PartitionKey eq '{MATCH_PARTITION_KEY}'"))
+        .top(page_size)
+        .into_stream::<MatchEntity>()
+        .map(|page_result| {
+            let page = page_result.map_err(MatchStorageError::from)?;
+            Ok(page
+                .entities
+                .into_iter()
+                .filter(|entity| !entity.deleted)
+                .filter_map(|entity| match MatchRecord::try_from(entity) {
+                    Ok(record) => Some(record),
+                    Err(e) => {
+                        tracing::warn!("Skipping match with invalid played_at: {e}");
+                        None
+                    }
+                })
+                .collect())
+        })
+}
+
+/// Server-side filters for a page of matches. All fields are optional and
+/// combine with AND.
+#[derive(Debug, Default)]
+pub struct MatchPageFilter<'a> {
+    /// Only matches this player took part in (any of the four slots).
+    pub player_id: Option<&'a str>,
+    pub league_id: Option<&'a str>,
+    /// Only matches played at or after this instant.
+    pub from: Option<DateTime<Utc>>,
+    /// Only matches played at or before this instant.
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Fetch one page of matches, newest first, starting after `cursor`.
+///
+/// The cursor is simply the RowKey of the last match on the previous page:
+/// because RowKeys sort newest-first, `RowKey gt cursor` picks up exactly
+/// where that page left off. Date bounds are also expressed as RowKey ranges
+/// (via the reverse-timestamp prefix), and player/league filters are pushed
+/// into the OData query, so Azure does the filtering instead of us.
+///
+/// Pass `usize::MAX` as `limit` to read every matching row in one go.
+///
+/// Returns the page and the cursor for the next one (`None` on the last page).
+/// Transient failures are retried like `list_matches`. A cursor that isn't
+/// shaped like a match RowKey is `Invalid`, so client input never reaches the
+/// filter unchecked.
+pub async fn list_matches_page(
+    storage: &StorageClient,
+    filter: &MatchPageFilter<'_>,
+    cursor: Option<&str>,
+    limit: usize,
+) -> Result<(Vec<MatchRecord>, Option<String>), MatchStorageError> {
+    let query = &page_query(filter, cursor)?;
+
+    // A transient failure part-way through restarts the whole query.
+    let mut matches = retry_with_backoff(storage.max_retries, || async move {
+        let mut stream = storage
+            .matches
+            .query()
+            .filter(query.clone())
+            .into_stream::<MatchEntity>();
+
+        // Fetch one extra match to learn whether another page exists.
+        let mut matches = Vec::new();
+        'pages: while let Some(page_result) = stream.next().await {
+            for entity in page_result?.entities {
+                if entity.deleted {
+                    continue;
+                }
+                match MatchRecord::try_from(entity) {
+                    Ok(record) => matches.push(record),
+                    Err(e) => tracing::warn!("Skipping match with invalid played_at: {e}"),
+                }
+                if matches.len() > limit {
+                    break 'pages;
                 }
             }
         }
-        if matches.len() >= max {
-            break;
+        Ok(matches)
+    })
+    .await
+    .map_err(MatchStorageError::from)?;
+
+    let next_cursor = if matches.len() > limit {
+        matches.truncate(limit);
+        matches.last().map(|m| m.id.clone())
+    } else {
+        None
+    };
+
+    Ok((matches, next_cursor))
+}
+
+/// The OData filter for `list_matches_page`, rejecting malformed cursors.
+fn page_query(
+    filter: &MatchPageFilter<'_>,
+    cursor: Option<&str>,
+) -> Result<String, MatchStorageError> {
+    let mut clauses = vec![format!("PartitionKey eq '{MATCH_PARTITION_KEY}'")];
+    if let Some(cursor) = cursor {
+        if !is_match_row_key(cursor) {
+            return Err(MatchStorageError::Invalid(format!(
+                "'{cursor}' is not a valid page cursor"
+            )));
         }
+        clauses.push(format!("RowKey gt '{cursor}'"));
+    }
+    if let Some(to) = filter.to {
+        clauses.push(format!("RowKey ge '{}'", row_key_prefix(&to)));
+    }
+    if let Some(from) = filter.from {
+        // Matches at exactly `from` have the prefix of `from`, which sorts
+        // below the prefix of the millisecond before it.
+        let bound = row_key_prefix(&(from - Duration::milliseconds(1)));
+        clauses.push(format!("RowKey lt '{bound}'"));
+    }
+    if let Some(player_id) = filter.player_id {
+        let id = odata_escape(player_id);
+        clauses.push(format!(
+            "(winner1_id eq '{id}' or winner2_id eq '{id}' or loser1_id eq '{id}' or loser2_id eq '{id}')"
+        ));
+    }
+    if let Some(league_id) = filter.league_id {
+        clauses.push(format!("league_id eq '{}'", odata_escape(league_id)));
     }
 
-    Ok(matches)
+    Ok(clauses.join(" and "))
+}
+
+/// Escape a value for use inside a single-quoted OData string literal.
+fn odata_escape(value: &str) -> String {
+    value.replace('\'', "''")
 }
 
 /// Get a single match by its ID (RowKey).
 ///
 /// This is a point read — the fastest possible query in Azure Table Storage.
 /// Used when editing a match to fetch the current record.
+/// Soft-deleted matches are `NotFound`.
 pub async fn get_match(
     storage: &StorageClient,
     match_id: &str,
 ) -> Result<MatchRecord, MatchStorageError> {
+    let entity = get_match_entity(storage, match_id).await?;
+    if entity.deleted {
+        return Err(MatchStorageError::NotFound(match_id.to_string()));
+    }
+    parse_match(entity)
+}
+
+/// Point read of a match whether or not it's soft-deleted, with the
+/// `deleted` flag alongside.
+pub async fn get_match_with_deleted(
+    storage: &StorageClient,
+    match_id: &str,
+) -> Result<(MatchRecord, bool), MatchStorageError> {
+    let entity = get_match_entity(storage, match_id).await?;
+    let deleted = entity.deleted;
+    Ok((parse_match(entity)?, deleted))
+}
+
+/// Point read of the raw entity, soft-deleted or not.
+async fn get_match_entity(
+    storage: &StorageClient,
+    match_id: &str,
+) -> Result<MatchEntity, MatchStorageError> {
     let response = storage
         .matches
         .partition_key_client(MATCH_PARTITION_KEY)
@@ -106,8 +318,11 @@ pub async fn get_match(
                 MatchStorageError::Azure(msg)
             }
         })?;
+    Ok(response.entity)
+}
 
-    MatchRecord::try_from(response.entity)
+fn parse_match(entity: MatchEntity) -> Result<MatchRecord, MatchStorageError> {
+    MatchRecord::try_from(entity)
         .map_err(|e| MatchStorageError::Azure(format!("Failed to parse match: {e}")))
 }
 
@@ -116,17 +331,97 @@ pub async fn create_match(
     storage: &StorageClient,
     record: MatchRecord,
 ) -> Result<MatchRecord, MatchStorageError> {
-    let entity = MatchEntity::from(record.clone());
+    insert_match(storage, &record).await?;
+    storage.bump_data_version();
+    Ok(record)
+}
 
-    let _: azure_data_tables::operations::InsertEntityResponse<MatchEntity> =
-        storage
+/// Insert one match row, retrying transient failures.
+///
+/// Every RowKey carries a fresh UUID, so the only way to hit 409
+/// EntityAlreadyExists is a retry whose earlier attempt reached Azure before
+/// timing out. The row is then already stored, so that counts as success.
+async fn insert_match(
+    storage: &StorageClient,
+    record: &MatchRecord,
+) -> Result<(), MatchStorageError> {
+    let entity = &MatchEntity::from(record.clone());
+    let result: azure_core::Result<InsertEntityResponse<MatchEntity>> =
+        retry_with_backoff(storage.max_retries, || async move {
+            storage.matches.insert(entity)?.await
+        })
+        .await;
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) if http_status(&e) == Some(StatusCode::Conflict) => {
+            tracing::info!("Match {} was already stored by an earlier attempt", record.id);
+            Ok(())
+        }
+        Err(e) => Err(MatchStorageError::Azure(format!("{e}"))),
+    }
+}
+
+/// Most matches `create_matches` stores at once: Azure's limit on operations
+/// in one entity-group transaction.
+pub const MAX_BATCH_MATCHES: usize = 100;
+
+/// Record several matches together (a session entered in one go).
+///
+/// All rows share the match partition, so they go in as one entity-group
+/// transaction: either every game is stored or none is. More than
+/// `MAX_BATCH_MATCHES` records is `Invalid` before anything is sent.
+pub async fn create_matches(
+    storage: &StorageClient,
+    records: Vec<MatchRecord>,
+) -> Result<Vec<MatchRecord>, MatchStorageError> {
+    if records.len() > MAX_BATCH_MATCHES {
+        return Err(MatchStorageError::Invalid(format!(
+            "At most {MAX_BATCH_MATCHES} matches can be recorded together"
+        )));
+    }
+    if records.is_empty() {
+        return Ok(records);
+    }
+
+    let entities = &records.iter().cloned().map(MatchEntity::from).collect::<Vec<_>>();
+    let response = retry_with_backoff(storage.max_retries, || async move {
+        let mut transaction = storage
             .matches
-            .insert(&entity)
-            .map_err(|e| MatchStorageError::Azure(format!("{e}")))?
-            .await
-            .map_err(|e| MatchStorageError::Azure(format!("{e}")))?;
+            .partition_key_client(MATCH_PARTITION_KEY)
+            .transaction();
+        for entity in entities {
+            transaction = transaction.insert(entity)?;
+        }
+        transaction.await
+    })
+    .await
+    .map_err(MatchStorageError::from)?;
 
-    Ok(record)
+    let statuses: Vec<StatusCode> =
+        response.operation_responses.iter().map(|r| r.status_code).collect();
+    transaction_outcome(&statuses)?;
+
+    storage.bump_data_version();
+    Ok(records)
+}
+
+/// Whether a session transaction stored its rows, judged by the per-operation
+/// statuses Azure answered with (a failed transaction reports just the
+/// operation that failed).
+///
+/// As in `insert_match`, 409 EntityAlreadyExists can only mean a retry whose
+/// earlier attempt went through — the whole batch did, being atomic.
+fn transaction_outcome(statuses: &[StatusCode]) -> Result<(), MatchStorageError> {
+    if statuses.contains(&StatusCode::Conflict) {
+        tracing::info!("Session was already stored by an earlier attempt");
+        return Ok(());
+    }
+    match statuses.iter().find(|s| !s.is_success()) {
+        Some(status) => Err(MatchStorageError::Azure(format!(
+            "Session transaction failed with status {status:?}"
+        ))),
+        None => Ok(()),
+    }
 }
 
 /// Update an existing match record (full replacement).
@@ -152,21 +447,173 @@ pub async fn update_match(
         .await
         .map_err(|e| MatchStorageError::Azure(format!("{e}")))?;
 
+    storage.bump_data_version();
     Ok(record)
 }
 
-/// Delete a match by its ID (RowKey).
+/// Outcome of a backfill migration run.
+#[derive(Debug, Serialize)]
+pub struct MigrationReport {
+    /// Matches examined.
+    pub scanned: usize,
+    /// Matches rewritten with backfilled properties.
+    pub updated: usize,
+}
+
+/// Rewrite every match that is missing a property added after launch
+/// (`comment`, `recorded_by`), filling in its default.
 ///
-/// Used when someone records wrong scores and needs to fix it.
-pub async fn delete_match(
+/// Entities are read through `LegacyMatchEntity` so missing properties are
+/// visible; only those actually missing something are written back, which
+/// makes the migration idempotent — a second run reports `updated: 0`.
+pub async fn migrate_matches(
+    storage: &StorageClient,
+) -> Result<MigrationReport, MatchStorageError> {
+    let mut report = MigrationReport {
+        scanned: 0,
+        updated: 0,
+    };
+
+    let mut stream = storage
+        .matches
+        .query()
+        .filter(format!("PartitionKey eq '{MATCH_PARTITION_KEY}'"))
+        .into_stream::<LegacyMatchEntity>();
+
+    while let Some(page_result) = stream.next().await {
+        let page = page_result.map_err(MatchStorageError::from)?;
+        for legacy in page.entities {
+            report.scanned += 1;
+            if !legacy.needs_backfill() {
+                continue;
+            }
+
+            let entity = MatchEntity::from(legacy);
+            storage
+                .matches
+                .partition_key_client(MATCH_PARTITION_KEY)
+                .entity_client(&entity.row_key)
+                .insert_or_replace(&entity)
+                .map_err(|e| MatchStorageError::Azure(format!("{e}")))?
+                .await
+                .map_err(|e| MatchStorageError::Azure(format!("{e}")))?;
+            report.updated += 1;
+        }
+    }
+
+    if report.updated > 0 {
+        storage.bump_data_version();
+    }
+    Ok(report)
+}
+
+/// Outcome of `reassign_player`.
+#[derive(Debug, Serialize)]
+pub struct ReassignReport {
+    /// Matches rewritten to the new player ID.
+    pub rewritten: usize,
+    /// Matches left alone because both IDs took part in them: rewriting would
+    /// put the same player in two slots.
+    pub skipped: Vec<String>,
+    /// Matches whose rewrite failed in storage (logged).
+    pub failed: Vec<String>,
+}
+
+/// Rewrite every match (soft-deleted ones included) that has `from` in any
+/// of its four player slots to use `into` instead.
+///
+/// Best effort: a failed write is recorded in the report and the rest carry
+/// on. Only reading the matches is fatal.
+pub async fn reassign_player(
+    storage: &StorageClient,
+    from: &str,
+    into: &str,
+) -> Result<ReassignReport, MatchStorageError> {
+    let mut report = ReassignReport {
+        rewritten: 0,
+        skipped: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    let mut stream = storage
+        .matches
+        .query()
+        .filter(format!("PartitionKey eq '{MATCH_PARTITION_KEY}'"))
+        .into_stream::<MatchEntity>();
+
+    while let Some(page_result) = stream.next().await {
+        let page = page_result.map_err(MatchStorageError::from)?;
+        for mut entity in page.entities {
+            let mut slots = [
+                &mut entity.winner1_id,
+                &mut entity.winner2_id,
+                &mut entity.loser1_id,
+                &mut entity.loser2_id,
+            ];
+            if !slots.iter().any(|id| id.as_str() == from) {
+                continue;
+            }
+            if slots.iter().any(|id| id.as_str() == into) {
+                report.skipped.push(entity.row_key);
+                continue;
+            }
+            for id in slots.iter_mut().filter(|id| id.as_str() == from) {
+                **id = into.to_string();
+            }
+
+            let written = &entity;
+            let result = retry_with_backoff(storage.max_retries, || async move {
+                storage
+                    .matches
+                    .partition_key_client(MATCH_PARTITION_KEY)
+                    .entity_client(&written.row_key)
+                    .insert_or_replace(written)?
+                    .await
+            })
+            .await;
+            match result {
+                Ok(_) => report.rewritten += 1,
+                Err(e) => {
+                    tracing::error!("Failed to reassign match {}: {e}", entity.row_key);
+                    report.failed.push(entity.row_key);
+                }
+            }
+        }
+    }
+
+    if report.rewritten > 0 {
+        storage.bump_data_version();
+    }
+    Ok(report)
+}
+
+/// Just the `deleted` property, merged onto an existing entity.
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct DeletedFlag<'a> {
+    partition_key: &'a str,
+    row_key: &'a str,
+    #[serde(rename = "deleted")]
+    deleted: bool,
+}
+
+/// Set or clear a match's `deleted` flag, leaving every other property as is.
+async fn set_deleted(
     storage: &StorageClient,
     match_id: &str,
+    deleted: bool,
 ) -> Result<(), MatchStorageError> {
+    let flag = DeletedFlag {
+        partition_key: MATCH_PARTITION_KEY,
+        row_key: match_id,
+        deleted,
+    };
     storage
         .matches
         .partition_key_client(MATCH_PARTITION_KEY)
         .entity_client(match_id)
-        .delete()
+        .merge(&flag, IfMatchCondition::Any)
+        .map_err(|e| MatchStorageError::Azure(format!("{e}")))?
         .await
         .map_err(|e| {
             let msg = format!("{e}");
@@ -177,5 +624,125 @@ pub async fn delete_match(
             }
         })?;
 
+    storage.bump_data_version();
+    Ok(())
+}
+
+/// Soft-delete a match by its ID (RowKey): it disappears from listings and
+/// stats but stays in storage, so `restore_match` can bring it back.
+///
+/// Used when someone records wrong scores and needs to fix it.
+pub async fn delete_match(
+    storage: &StorageClient,
+    match_id: &str,
+) -> Result<(), MatchStorageError> {
+    set_deleted(storage, match_id, true).await
+}
+
+/// Undo `delete_match`. Restoring a match that isn't deleted is a no-op.
+pub async fn restore_match(
+    storage: &StorageClient,
+    match_id: &str,
+) -> Result<MatchRecord, MatchStorageError> {
+    let mut entity = get_match_entity(storage, match_id).await?;
+    if entity.deleted {
+        set_deleted(storage, match_id, false).await?;
+        entity.deleted = false;
+    }
+    parse_match(entity)
+}
+
+/// Permanently remove a match row (deleted or not). There's no coming back.
+///
+/// A 404 on a retry means an earlier attempt already removed it.
+pub async fn hard_delete_match(
+    storage: &StorageClient,
+    match_id: &str,
+) -> Result<(), MatchStorageError> {
+    retry_write(storage.max_retries, StatusCode::NotFound, || async move {
+        storage
+            .matches
+            .partition_key_client(MATCH_PARTITION_KEY)
+            .entity_client(match_id)
+            .delete()
+            .await
+    })
+    .await
+    .map_err(|e| {
+        let msg = format!("{e}");
+        if msg.contains("ResourceNotFound") || msg.contains("404") {
+            MatchStorageError::NotFound(match_id.to_string())
+        } else {
+            MatchStorageError::Azure(msg)
+        }
+    })?;
+
+    storage.bump_data_version();
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::match_record::generate_match_row_key;
+
+    #[test]
+    fn cursor_and_player_filter_combine() {
+        let cursor = generate_match_row_key(&Utc::now()).unwrap();
+        let filter = MatchPageFilter {
+            player_id: Some("o'brien"),
+            ..Default::default()
+        };
+
+        let query = page_query(&filter, Some(&cursor)).unwrap();
+
+        assert_eq!(
+            query,
+            format!(
+                "PartitionKey eq '{MATCH_PARTITION_KEY}' and RowKey gt '{cursor}' and \
+                 (winner1_id eq 'o''brien' or winner2_id eq 'o''brien' \
+                 or loser1_id eq 'o''brien' or loser2_id eq 'o''brien')"
+            )
+        );
+    }
+
+    #[test]
+    fn malformed_cursors_never_reach_the_query() {
+        let filter = MatchPageFilter::default();
+        let err = page_query(&filter, Some("' or RowKey ne '")).unwrap_err();
+        assert!(matches!(err, MatchStorageError::Invalid(_)), "{err:?}");
+    }
+
+    #[test]
+    fn player_filter_matches_any_slot_within_the_league() {
+        let filter = MatchPageFilter {
+            player_id: Some("alice"),
+            league_id: Some("office"),
+            ..Default::default()
+        };
+
+        let query = page_query(&filter, None).unwrap();
+
+        assert_eq!(
+            query,
+            format!(
+                "PartitionKey eq '{MATCH_PARTITION_KEY}' and \
+                 (winner1_id eq 'alice' or winner2_id eq 'alice' \
+                 or loser1_id eq 'alice' or loser2_id eq 'alice') and league_id eq 'office'"
+            )
+        );
+        let unfiltered = page_query(&MatchPageFilter::default(), None).unwrap();
+        assert_eq!(unfiltered, format!("PartitionKey eq '{MATCH_PARTITION_KEY}'"));
+    }
+
+    #[test]
+    fn session_transactions_succeed_only_when_every_insert_did() {
+        assert!(transaction_outcome(&[StatusCode::NoContent, StatusCode::NoContent]).is_ok());
+        // The whole batch already went in on an earlier attempt.
+        assert!(transaction_outcome(&[StatusCode::Conflict]).is_ok());
+        assert!(matches!(
+            transaction_outcome(&[StatusCode::BadRequest]),
+            Err(MatchStorageError::Azure(_))
+        ));
+    }
+}
@@ -1,10 +1,13 @@
 // storage/mod.rs — Azure Table Storage module.
 //
 // Provides a client wrapper and CRUD operations for all Azure Table Storage
-// tables: players, matches, users, and leagues.
+// tables: players, matches, users, leagues, and the optional event log, plus
+// retrying of transient Azure failures.
 
 pub mod client;
+pub mod events;
 pub mod leagues;
 pub mod matches;
 pub mod players;
+pub mod retry;
 pub mod users;
@@ -11,11 +11,14 @@
 // - Delete: removes an entity by PartitionKey + RowKey
 // - Query:  lists entities, optionally filtered by OData expressions
 
-use azure_data_tables::operations::InsertEntityResponse;
+use azure_core::{Etag, StatusCode};
+use azure_data_tables::prelude::IfMatchCondition;
+use azure_data_tables::operations::{GetEntityResponse, InsertEntityResponse};
 use futures::StreamExt;
 
 use crate::models::player::{Player, PlayerEntity, PLAYER_PARTITION_KEY};
 use crate::storage::client::StorageClient;
+use crate::storage::retry::{retry_with_backoff, retry_write};
 
 /// Errors that can occur during player storage operations.
 #[derive(Debug, thiserror::Error)]
@@ -26,6 +29,14 @@ pub enum PlayerStorageError {
     #[error("Player '{0}' already exists")]
     AlreadyExists(String),
 
+    /// The player changed between our read and our write (ETag mismatch).
+    #[error("Player '{0}' was modified concurrently; reload and try again")]
+    Conflict(String),
+
+    /// The request can't be turned into a valid player.
+    #[error("Invalid player: {0}")]
+    Invalid(String),
+
     #[error("Azure Table Storage error: {0}")]
     Azure(String),
 }
@@ -51,24 +62,26 @@ impl From<azure_core::Error> for PlayerStorageError {
 /// all with a single partition query. With <10 players, this always returns
 /// in a single page.
 pub async fn list_players(storage: &StorageClient) -> Result<Vec<Player>, PlayerStorageError> {
-    let mut players = Vec::new();
-
     // Query all entities in the "player" partition.
-    // The Azure SDK returns a paginated stream — we collect all pages.
-    let mut stream = storage
-        .players
-        .query()
-        .filter(format!("PartitionKey eq '{PLAYER_PARTITION_KEY}'"))
-        .into_stream::<PlayerEntity>();
-
-    while let Some(page_result) = stream.next().await {
-        let page = page_result.map_err(PlayerStorageError::from)?;
-        for entity in page.entities {
-            players.push(Player::from(entity));
+    // The Azure SDK returns a paginated stream — we collect all pages. A
+    // transient failure part-way through restarts the whole query.
+    let entities = retry_with_backoff(storage.max_retries, || async move {
+        let mut entities = Vec::new();
+        let mut stream = storage
+            .players
+            .query()
+            .filter(format!("PartitionKey eq '{PLAYER_PARTITION_KEY}'"))
+            .into_stream::<PlayerEntity>();
+
+        while let Some(page_result) = stream.next().await {
+            entities.extend(page_result?.entities);
         }
-    }
+        Ok(entities)
+    })
+    .await
+    .map_err(PlayerStorageError::from)?;
 
-    Ok(players)
+    Ok(entities.into_iter().map(Player::from).collect())
 }
 
 /// Get a single player by their ID (RowKey).
@@ -76,9 +89,18 @@ pub async fn get_player(
     storage: &StorageClient,
     player_id: &str,
 ) -> Result<Player, PlayerStorageError> {
+    let response = get_player_entity(storage, player_id).await?;
+    Ok(Player::from(response.entity))
+}
+
+/// Fetch a player's raw entity together with its ETag.
+async fn get_player_entity(
+    storage: &StorageClient,
+    player_id: &str,
+) -> Result<GetEntityResponse<PlayerEntity>, PlayerStorageError> {
     // The get() method returns a GetEntityResponse<T> with an `entity` field.
     // We use turbofish on get() to tell it what type to deserialize into.
-    let response = storage
+    storage
         .players
         .partition_key_client(PLAYER_PARTITION_KEY)
         .entity_client(player_id)
@@ -91,36 +113,58 @@ pub async fn get_player(
             } else {
                 PlayerStorageError::Azure(msg)
             }
-        })?;
-
-    Ok(Player::from(response.entity))
+        })
 }
 
 /// Create a new player.
 ///
 /// Uses Azure Table Storage's "insert" operation, which fails with 409 Conflict
-/// if an entity with the same PartitionKey + RowKey already exists.
+/// if an entity with the same PartitionKey + RowKey already exists. A 409 on a
+/// retry means our own earlier attempt got through, so it isn't reported.
 pub async fn create_player(
     storage: &StorageClient,
     player: Player,
+) -> Result<Player, PlayerStorageError> {
+    let entity = &PlayerEntity::from(player.clone());
+
+    let _: Option<InsertEntityResponse<PlayerEntity>> =
+        retry_write(storage.max_retries, StatusCode::Conflict, || async move {
+            storage.players.insert(entity)?.await
+        })
+        .await
+        .map_err(|e| {
+            let msg = format!("{e}");
+            if msg.contains("EntityAlreadyExists") || msg.contains("409") {
+                PlayerStorageError::AlreadyExists(player.id.clone())
+            } else {
+                PlayerStorageError::Azure(msg)
+            }
+        })?;
+
+    storage.bump_data_version();
+    Ok(player)
+}
+
+/// Create or fully replace a player (upsert).
+///
+/// Uses `insert_or_replace`, so callers don't need to know whether the
+/// player already exists.
+pub async fn upsert_player(
+    storage: &StorageClient,
+    player: Player,
 ) -> Result<Player, PlayerStorageError> {
     let entity = PlayerEntity::from(player.clone());
 
-    let _: InsertEntityResponse<PlayerEntity> =
-        storage
-            .players
-            .insert(&entity)
-            .map_err(|e| PlayerStorageError::Azure(format!("{e}")))?
-            .await
-            .map_err(|e| {
-                let msg = format!("{e}");
-                if msg.contains("EntityAlreadyExists") || msg.contains("409") {
-                    PlayerStorageError::AlreadyExists(player.id.clone())
-                } else {
-                    PlayerStorageError::Azure(msg)
-                }
-            })?;
+    storage
+        .players
+        .partition_key_client(PLAYER_PARTITION_KEY)
+        .entity_client(&entity.row_key)
+        .insert_or_replace(&entity)
+        .map_err(|e| PlayerStorageError::Azure(format!("{e}")))?
+        .await
+        .map_err(|e| PlayerStorageError::Azure(format!("{e}")))?;
 
+    storage.bump_data_version();
     Ok(player)
 }
 
@@ -128,40 +172,81 @@ pub async fn create_player(
 ///
 /// We first fetch the current entity (to get its ETag for optimistic concurrency),
 /// then merge our changes. This means only the fields we provide are updated.
+///
+/// The write is conditional on that ETag: if someone else changed the player
+/// in between, Azure answers 412 and we return `Conflict` instead of silently
+/// overwriting their edit.
 pub async fn update_player(
     storage: &StorageClient,
     player_id: &str,
     name: Option<String>,
     nickname: Option<String>,
     avatar_emoji: Option<String>,
+    color: Option<Option<String>>,
 ) -> Result<Player, PlayerStorageError> {
     // First, get the current player to ensure it exists.
-    let mut current = get_player(storage, player_id).await?;
+    let response = get_player_entity(storage, player_id).await?;
+    let etag = response.etag;
+    let mut current = Player::from(response.entity);
 
-    // Apply updates
-    if let Some(n) = name {
-        current.name = n;
-    }
-    if let Some(n) = nickname {
-        current.nickname = n;
-    }
-    if let Some(a) = avatar_emoji {
-        current.avatar_emoji = a;
+    current.apply_update(name, nickname, avatar_emoji, color);
+
+    // Convert back to entity and replace it, provided it's unchanged since
+    // we read it.
+    replace_player(storage, &current, etag).await?;
+    Ok(current)
+}
+
+/// Archive a player: hide them from the player list while keeping their
+/// record, so historical matches and stats still resolve their name.
+///
+/// Archiving an already archived player is a no-op.
+pub async fn archive_player(
+    storage: &StorageClient,
+    player_id: &str,
+) -> Result<Player, PlayerStorageError> {
+    let response = get_player_entity(storage, player_id).await?;
+    let mut player = Player::from(response.entity);
+    if player.active {
+        player.active = false;
+        replace_player(storage, &player, response.etag).await?;
     }
+    Ok(player)
+}
 
-    // Convert back to entity and upsert (insert-or-replace).
-    let entity = PlayerEntity::from(current.clone());
+/// Overwrite a stored player, provided its ETag still matches `etag`.
+///
+/// If someone else changed the player since we read it, Azure answers 412
+/// and we return `Conflict`.
+async fn replace_player(
+    storage: &StorageClient,
+    player: &Player,
+    etag: Etag,
+) -> Result<(), PlayerStorageError> {
+    let entity = PlayerEntity::from(player.clone());
 
     storage
         .players
         .partition_key_client(PLAYER_PARTITION_KEY)
-        .entity_client(player_id)
-        .insert_or_replace(&entity)
+        .entity_client(&player.id)
+        .update(&entity, IfMatchCondition::Etag(etag))
         .map_err(|e| PlayerStorageError::Azure(format!("{e}")))?
         .await
-        .map_err(|e| PlayerStorageError::Azure(format!("{e}")))?;
+        .map_err(|e| replace_error(&player.id, e))?;
 
-    Ok(current)
+    storage.bump_data_version();
+    Ok(())
+}
+
+/// Classify a failed conditional replace of `player_id`: a 412 means our
+/// ETag was stale.
+fn replace_error(player_id: &str, e: azure_core::Error) -> PlayerStorageError {
+    let msg = format!("{e}");
+    if msg.contains("UpdateConditionNotSatisfied") || msg.contains("412") {
+        PlayerStorageError::Conflict(player_id.to_string())
+    } else {
+        PlayerStorageError::Azure(msg)
+    }
 }
 
 /// Delete a player by their ID.
@@ -170,21 +255,57 @@ pub async fn delete_player(
     player_id: &str,
 ) -> Result<(), PlayerStorageError> {
     // Use IfMatchCondition::Any to delete regardless of ETag (we don't need
-    // optimistic concurrency for deletes in our simple app).
-    storage
-        .players
-        .partition_key_client(PLAYER_PARTITION_KEY)
-        .entity_client(player_id)
-        .delete()
-        .await
-        .map_err(|e| {
-            let msg = format!("{e}");
-            if msg.contains("ResourceNotFound") || msg.contains("404") {
-                PlayerStorageError::NotFound(player_id.to_string())
-            } else {
-                PlayerStorageError::Azure(msg)
-            }
-        })?;
+    // optimistic concurrency for deletes in our simple app). A 404 on a retry
+    // means an earlier attempt already deleted it.
+    retry_write(storage.max_retries, StatusCode::NotFound, || async move {
+        storage
+            .players
+            .partition_key_client(PLAYER_PARTITION_KEY)
+            .entity_client(player_id)
+            .delete()
+            .await
+    })
+    .await
+    .map_err(|e| {
+        let msg = format!("{e}");
+        if msg.contains("ResourceNotFound") || msg.contains("404") {
+            PlayerStorageError::NotFound(player_id.to_string())
+        } else {
+            PlayerStorageError::Azure(msg)
+        }
+    })?;
 
+    storage.bump_data_version();
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azure_core::StatusCode;
+    use azure_core::error::ErrorKind;
+
+    fn http_error(status: StatusCode, message: &'static str) -> azure_core::Error {
+        let kind = ErrorKind::HttpResponse {
+            status,
+            error_code: None,
+        };
+        azure_core::Error::message(kind, message)
+    }
+
+    #[test]
+    fn a_stale_etag_is_a_conflict() {
+        let stale = http_error(
+            StatusCode::PreconditionFailed,
+            "UpdateConditionNotSatisfied: The update condition specified in the request was \
+             not satisfied.",
+        );
+        assert!(matches!(
+            replace_error("alice", stale),
+            PlayerStorageError::Conflict(id) if id == "alice"
+        ));
+
+        let outage = http_error(StatusCode::ServiceUnavailable, "ServerBusy");
+        assert!(matches!(replace_error("alice", outage), PlayerStorageError::Azure(_)));
+    }
+}
@@ -0,0 +1,236 @@
+// storage/retry.rs — Retrying transient Azure Table Storage failures.
+//
+// Table Storage occasionally answers with a 500/503 or times out under load;
+// the same request a moment later almost always succeeds. `retry_with_backoff`
+// reruns such operations with jittered exponential backoff. Client errors
+// (4xx — not found, conflict, bad request) are returned immediately, since
+// repeating them can't help.
+//
+// Failures are classified by the HTTP status the SDK reports (or an I/O
+// error, for connections that never got an answer), never by the error
+// message, which can contain entity keys or request IDs that happen to look
+// like status codes.
+//
+// A retried write whose first attempt actually reached Azure before timing
+// out comes back as 409 EntityAlreadyExists (insert) or 404 ResourceNotFound
+// (delete). Match inserts treat 409 as success outright, since their RowKeys
+// are fresh UUIDs (see storage/matches.rs). Writes that can legitimately hit
+// 409/404 on the first try — player inserts, deletes — go through
+// `retry_write`, which only forgives that status on a retry.
+
+use azure_core::StatusCode;
+use azure_core::error::ErrorKind;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Delay before the first retry; doubled for each one after that.
+const BASE_DELAY: Duration = Duration::from_millis(100);
+
+/// Upper bound for a single delay, however many retries came before.
+const MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// Run `op`, retrying up to `max_retries` more times while it fails with a
+/// transient error. The last error is returned once retries run out.
+pub async fn retry_with_backoff<T, F, Fut>(max_retries: u32, mut op: F) -> azure_core::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = azure_core::Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match op().await {
+            Err(e) if attempt < max_retries && is_transient(&e) => {
+                let delay = backoff_delay(attempt);
+                attempt += 1;
+                tracing::warn!(
+                    "Transient storage error (retry {attempt}/{max_retries} in {delay:?}): {e}"
+                );
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// `retry_with_backoff` for writes that aren't idempotent. If a *retry* (never
+/// the first attempt) fails with `already_done`, an earlier attempt must have
+/// reached Azure before its error, so the write counts as done: `Ok(None)`.
+/// The same status on the first attempt is a genuine failure and returned.
+pub async fn retry_write<T, F, Fut>(
+    max_retries: u32,
+    already_done: StatusCode,
+    mut op: F,
+) -> azure_core::Result<Option<T>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = azure_core::Result<T>>,
+{
+    let mut attempts = 0;
+    let result = retry_with_backoff(max_retries, || {
+        attempts += 1;
+        op()
+    })
+    .await;
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if attempts > 1 && http_status(&e) == Some(already_done) => {
+            tracing::info!("Write already applied by an earlier attempt: {e}");
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// The HTTP status Azure answered with, if the failure was an HTTP response
+/// at all.
+pub fn http_status(e: &azure_core::Error) -> Option<StatusCode> {
+    match e.kind() {
+        ErrorKind::HttpResponse { status, .. } => Some(*status),
+        _ => None,
+    }
+}
+
+/// Whether `e` is a server-side or network hiccup worth retrying: a 5xx or
+/// 408 response, or an I/O error (connection reset, client-side timeout).
+/// Every other 4xx is final.
+fn is_transient(e: &azure_core::Error) -> bool {
+    match e.kind() {
+        ErrorKind::HttpResponse { status, .. } => {
+            status.is_server_error() || *status == StatusCode::RequestTimeout
+        }
+        ErrorKind::Io => true,
+        _ => false,
+    }
+}
+
+/// Delay before retry number `attempt` (0-based): exponential, capped at
+/// `MAX_DELAY`, then jittered to a random point in its upper half so
+/// concurrent callers don't retry in lockstep.
+fn backoff_delay(attempt: u32) -> Duration {
+    let full = BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_DELAY);
+    let half = full / 2;
+    half + half.mul_f64(random_fraction())
+}
+
+/// A random number in `[0, 1)`. `RandomState` is randomly seeded per
+/// instance, which is plenty for jitter without pulling in a `rand` crate.
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn http_error(status: StatusCode, message: &'static str) -> azure_core::Error {
+        let kind = ErrorKind::HttpResponse {
+            status,
+            error_code: None,
+        };
+        azure_core::Error::message(kind, message)
+    }
+
+    #[test]
+    fn server_errors_timeouts_and_io_are_transient() {
+        assert!(is_transient(&http_error(StatusCode::InternalServerError, "InternalError")));
+        assert!(is_transient(&http_error(StatusCode::ServiceUnavailable, "ServerBusy")));
+        assert!(is_transient(&http_error(StatusCode::RequestTimeout, "OperationTimedOut")));
+        assert!(is_transient(&azure_core::Error::message(ErrorKind::Io, "connection reset")));
+    }
+
+    #[test]
+    fn client_errors_are_final_whatever_the_message_says() {
+        // Entity keys and request IDs can contain "500" or "timeout".
+        let not_found = http_error(StatusCode::NotFound, "match 500_timeout not found");
+        let conflict = http_error(StatusCode::Conflict, "request id 503-503 EntityAlreadyExists");
+        assert!(!is_transient(&not_found));
+        assert!(!is_transient(&conflict));
+        assert!(!is_transient(&azure_core::Error::message(ErrorKind::DataConversion, "500")));
+    }
+
+    #[test]
+    fn http_status_is_read_from_the_error_kind() {
+        let conflict = http_error(StatusCode::Conflict, "EntityAlreadyExists");
+        assert_eq!(http_status(&conflict), Some(StatusCode::Conflict));
+        assert_eq!(http_status(&azure_core::Error::message(ErrorKind::Io, "409")), None);
+    }
+
+    #[test]
+    fn backoff_grows_and_is_capped() {
+        for attempt in 0..10 {
+            let full = BASE_DELAY.saturating_mul(2u32.pow(attempt)).min(MAX_DELAY);
+            let delay = backoff_delay(attempt);
+            assert!(delay >= full / 2 && delay <= full, "attempt {attempt}: {delay:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn transient_failures_are_retried_until_retries_run_out() {
+        let calls = AtomicU32::new(0);
+        let result: azure_core::Result<()> = retry_with_backoff(2, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(http_error(StatusCode::ServiceUnavailable, "ServerBusy"))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn a_retry_that_succeeds_returns_its_value() {
+        let calls = AtomicU32::new(0);
+        let result = retry_with_backoff(3, || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(http_error(StatusCode::InternalServerError, "InternalError")),
+                _ => Ok(42),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        let calls = AtomicU32::new(0);
+        let result: azure_core::Result<()> = retry_with_backoff(3, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(http_error(StatusCode::NotFound, "ResourceNotFound 500"))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn a_write_that_already_landed_counts_as_done_on_retry() {
+        let calls = AtomicU32::new(0);
+        let result = retry_write(3, StatusCode::Conflict, || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(http_error(StatusCode::ServiceUnavailable, "OperationTimedOut")),
+                _ => Err::<(), _>(http_error(StatusCode::Conflict, "EntityAlreadyExists")),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), None);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn a_write_rejected_on_the_first_attempt_still_fails() {
+        let result: azure_core::Result<Option<()>> =
+            retry_write(3, StatusCode::NotFound, || async {
+                Err(http_error(StatusCode::NotFound, "ResourceNotFound"))
+            })
+            .await;
+        assert_eq!(http_status(&result.unwrap_err()), Some(StatusCode::NotFound));
+
+        let ok = retry_write(3, StatusCode::NotFound, || async { Ok(7) }).await;
+        assert_eq!(ok.unwrap(), Some(7));
+    }
+}
@@ -181,6 +181,56 @@ pub async fn link_player(
     upsert_user(storage, user).await
 }
 
+/// Point every user linked to player `from` at player `into` instead (used
+/// when merging players). Returns how many users were relinked.
+pub async fn relink_player_users(
+    storage: &StorageClient,
+    from: &str,
+    into: &str,
+) -> Result<usize, UserStorageError> {
+    let mut relinked = 0;
+    for mut user in list_users(storage).await? {
+        if user.player_id.as_deref() == Some(from) {
+            user.player_id = Some(into.to_string());
+            upsert_user(storage, user).await?;
+            relinked += 1;
+        }
+    }
+    Ok(relinked)
+}
+
+/// Remove the link to `player_id` from every user linked to it.
+///
+/// Normally at most one user is linked to a player, but nothing enforces
+/// that, so all of them are cleared. The link lives on the user row, so this
+/// reads the whole users table (one partition query; a few dozen rows for an
+/// office) and writes back only the users that change. Returns how many
+/// users were unlinked.
+pub async fn unlink_player_from_users(
+    storage: &StorageClient,
+    player_id: &str,
+) -> Result<usize, UserStorageError> {
+    let unlinked = clear_player_links(list_users(storage).await?, player_id);
+    let count = unlinked.len();
+    for user in unlinked {
+        upsert_user(storage, user).await?;
+    }
+    Ok(count)
+}
+
+/// The users in `all_users` that were linked to `player_id`, with that link
+/// cleared. Users linked elsewhere (or not at all) are dropped.
+pub fn clear_player_links(all_users: Vec<User>, player_id: &str) -> Vec<User> {
+    all_users
+        .into_iter()
+        .filter(|user| user.player_id.as_deref() == Some(player_id))
+        .map(|user| User {
+            player_id: None,
+            ..user
+        })
+        .collect()
+}
+
 /// Count the total number of users in the system.
 ///
 /// This is used by the auth callback to implement "first user is admin" logic.
@@ -0,0 +1,239 @@
+// webhook.rs — Outbound notifications when a match is recorded.
+//
+// If `MATCH_WEBHOOK_URL` is configured, every newly recorded match is POSTed
+// there as JSON so a Slack/Teams integration can announce it. The payload's
+// `text` field is a ready-made one-line summary, which is all a Slack
+// incoming webhook needs to post it as-is. Delivery runs
+// in a spawned task: the API response never waits on (or fails because of)
+// the webhook. Failures are logged and dropped — there is no retry queue.
+//
+// Verification:
+//   When `MATCH_WEBHOOK_SECRET` is set, the request carries an
+//   `X-Scoreboard-Signature: sha256=<hex>` header containing the HMAC-SHA256
+//   of the raw body, keyed with the secret. Receivers should recompute it
+//   and compare before trusting the payload.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+
+use crate::config::AppConfig;
+use crate::models::match_record::{MatchRecord, MatchType};
+use crate::storage::client::StorageClient;
+use crate::storage::players;
+
+/// Header carrying the HMAC signature of the request body.
+pub const SIGNATURE_HEADER: &str = "X-Scoreboard-Signature";
+
+/// How long we wait for the webhook receiver before giving up.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// JSON body sent to the webhook for a recorded match.
+#[derive(Debug, Serialize)]
+pub struct MatchWebhookPayload {
+    /// Human-readable summary, e.g. "Alice & Bob beat Carol & Dave 11–7".
+    /// Slack incoming webhooks post this field and ignore the rest.
+    pub text: String,
+    pub event: &'static str,
+    pub match_id: String,
+    pub match_type: MatchType,
+    /// Display names of the winning team (falls back to the player ID).
+    pub winners: Vec<String>,
+    /// Display names of the losing team (falls back to the player ID).
+    pub losers: Vec<String>,
+    pub winner_score: Option<i32>,
+    pub loser_score: Option<i32>,
+    /// True when the losing team forfeited (scores, if any, are not meaningful).
+    pub forfeit: bool,
+    pub comment: String,
+    pub played_at: DateTime<Utc>,
+}
+
+/// Fire the match webhook in the background, if one is configured.
+///
+/// Returns immediately. Player names are resolved inside the spawned task so
+/// the extra storage read doesn't delay the API response either.
+pub fn notify_match_recorded(config: &AppConfig, storage: StorageClient, record: MatchRecord) {
+    let Some(url) = config.match_webhook_url.clone() else {
+        return;
+    };
+    let secret = config.match_webhook_secret.clone();
+
+    tokio::spawn(async move {
+        if let Err(e) = deliver(&url, secret.as_deref(), &storage, record).await {
+            tracing::warn!("Match webhook delivery failed: {e}");
+        }
+    });
+}
+
+async fn deliver(
+    url: &str,
+    secret: Option<&str>,
+    storage: &StorageClient,
+    record: MatchRecord,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let names: HashMap<String, String> = players::list_players(storage)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p.name))
+        .collect();
+    post(url, secret, &build_payload(record, &names)).await
+}
+
+/// Build the payload for `record`, naming players via `names` (ID → name).
+fn build_payload(record: MatchRecord, names: &HashMap<String, String>) -> MatchWebhookPayload {
+    let name_of = |id: &str| names.get(id).cloned().unwrap_or_else(|| id.to_string());
+
+    let winners: Vec<String> = record.winner_ids().map(name_of).collect();
+    let losers: Vec<String> = record.loser_ids().map(name_of).collect();
+    MatchWebhookPayload {
+        text: summary_text(&record, &winners, &losers),
+        event: "match.recorded",
+        winners,
+        losers,
+        match_type: record.match_type,
+        match_id: record.id,
+        winner_score: record.winner_score,
+        loser_score: record.loser_score,
+        forfeit: record.forfeit,
+        comment: record.comment,
+        played_at: record.played_at,
+    }
+}
+
+/// POST `payload` to `url`, signed with `secret` if there is one.
+async fn post(
+    url: &str,
+    secret: Option<&str>,
+    payload: &MatchWebhookPayload,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let body = serde_json::to_vec(payload)?;
+
+    let client = reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build()?;
+    let mut request = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json");
+    if let Some(secret) = secret {
+        request = request.header(SIGNATURE_HEADER, sign(secret, &body));
+    }
+
+    let response = request.body(body).send().await?;
+    if !response.status().is_success() {
+        return Err(format!("receiver returned {}", response.status()).into());
+    }
+
+    Ok(())
+}
+
+/// One-line announcement of a match: who beat whom, the score (or that it was
+/// a forfeit) and the comment, if any.
+fn summary_text(record: &MatchRecord, winners: &[String], losers: &[String]) -> String {
+    let mut text = format!("🏓 {} beat {}", winners.join(" & "), losers.join(" & "));
+    if record.forfeit {
+        text.push_str(" by forfeit");
+    } else if let (Some(w), Some(l)) = (record.winner_score, record.loser_score) {
+        text.push_str(&format!(" {w}–{l}"));
+    }
+    if !record.comment.trim().is_empty() {
+        text.push_str(&format!(": \"{}\"", record.comment.trim()));
+    }
+    text
+}
+
+/// Compute the `sha256=<hex>` signature of `body` with `secret`.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    // HMAC accepts keys of any length, so this can't fail.
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC can take a key of any size");
+    mac.update(body);
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("sha256={hex}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::match_record::NewMatch;
+    use axum::{Router, body::Bytes, http::HeaderMap, routing::post as post_route};
+    use std::sync::{Arc, Mutex};
+
+    type Received = Arc<Mutex<Option<(HeaderMap, Bytes)>>>;
+
+    /// Start a receiver on a free local port that stores the last request.
+    async fn mock_receiver() -> (String, Received) {
+        let received: Received = Arc::default();
+        let store = received.clone();
+        let app = Router::new().route(
+            "/hook",
+            post_route(move |headers: HeaderMap, body: Bytes| async move {
+                *store.lock().unwrap() = Some((headers, body));
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, received)
+    }
+
+    fn record() -> MatchRecord {
+        MatchRecord::new(NewMatch {
+            winner1_id: "alice".to_string(),
+            winner2_id: Some("bob".to_string()),
+            loser1_id: "carol".to_string(),
+            loser2_id: Some("dave".to_string()),
+            winner_score: Some(11),
+            loser_score: Some(7),
+            comment: " close one ".to_string(),
+            played_at: Utc::now(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn posts_a_signed_match_summary() {
+        let (url, received) = mock_receiver().await;
+        let record = record();
+        let names = HashMap::from([
+            ("alice".to_string(), "Alice".to_string()),
+            ("bob".to_string(), "Bob".to_string()),
+            ("carol".to_string(), "Carol".to_string()),
+        ]);
+
+        post(&url, Some("s3cret"), &build_payload(record.clone(), &names))
+            .await
+            .unwrap();
+
+        let (headers, body) = received.lock().unwrap().take().unwrap();
+        assert_eq!(headers["content-type"], "application/json");
+        assert_eq!(headers[SIGNATURE_HEADER], sign("s3cret", &body).as_str());
+
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["event"], "match.recorded");
+        assert_eq!(json["match_id"], record.id.as_str());
+        assert_eq!(json["winners"], serde_json::json!(["Alice", "Bob"]));
+        // dave has no name on file, so his ID stands in.
+        assert_eq!(json["losers"], serde_json::json!(["Carol", "dave"]));
+        assert_eq!(json["winner_score"], 11);
+        assert_eq!(json["loser_score"], 7);
+        assert_eq!(json["forfeit"], false);
+        assert_eq!(json["text"], "🏓 Alice & Bob beat Carol & dave 11–7: \"close one\"");
+    }
+
+    #[tokio::test]
+    async fn unsigned_without_a_secret() {
+        let (url, received) = mock_receiver().await;
+        post(&url, None, &build_payload(record(), &HashMap::new())).await.unwrap();
+        let (headers, _) = received.lock().unwrap().take().unwrap();
+        assert!(!headers.contains_key(SIGNATURE_HEADER));
+    }
+}